@@ -0,0 +1,47 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The sources the findings depend on: the lints, and the parser when it is built
+/// along with them
+static SOURCES: &[&str] = &["src", "crates/systemd-parser/src"];
+
+/// Embed `SYSTEMD_LINTER_BUILD_ID`, a hash of the sources, so that the cache of the
+/// findings is not reused by a linter whose lints changed without a version bump
+fn main() {
+
+    let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR"));
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for dir in SOURCES.iter().map(|dir| root.join(dir)).filter(|dir| dir.is_dir()) {
+        println!("cargo:rerun-if-changed={}", dir.display());
+
+        let mut files = vec!();
+        walk(&dir, &mut files).expect("the sources are readable");
+        files.sort();
+
+        for file in files {
+            let name = file.strip_prefix(&root).unwrap_or(&file).to_string_lossy().into_owned();
+            let contents = fs::read(&file).expect("the sources are readable");
+            for &byte in name.as_bytes().iter().chain(&[0]).chain(&contents) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    println!("cargo:rustc-env=SYSTEMD_LINTER_BUILD_ID={:016x}", hash);
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
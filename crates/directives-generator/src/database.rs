@@ -0,0 +1,286 @@
+
+use man::ManDirective;
+use rustc_serialize::json::{Json, Object};
+use std::collections::BTreeMap;
+
+static MAN_URL: &str = "https://www.freedesktop.org/software/systemd/man/";
+
+/// The fields of the previous database which are curated by hand, and not found in
+/// the systemd sources: they are carried over to the generated one
+static CURATED_FIELDS: &[&str] = &["allowed", "deprecated", "removed", "replaced_by", "values"];
+
+/// A directive of a section of the unit files, as systemd parses it
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SourceEntry {
+    pub section: String,
+    pub key: String,
+    /// The `config_parse_*` function of systemd, when known
+    pub parser: Option<String>,
+    pub value_type: &'static str,
+    /// Each occurrence of the directive adds to a list
+    pub list: bool,
+}
+
+/// A man page of systemd, e.g. `systemd.service`, and the directives it documents
+pub struct ManPage {
+    pub name: String,
+    pub directives: Vec<ManDirective>,
+}
+
+/// An entry of the database embedded in the linter, `src/lint/directives.json`
+#[derive(PartialEq, Clone, Debug)]
+pub struct Directive {
+    pub url: String,
+    pub field: String,
+    pub sections: Vec<String>,
+    pub list: bool,
+    pub value_type: String,
+    pub introduced: Option<u32>,
+    /// The `config_parse_*` function of systemd, `None` when the sources do not tell
+    pub parser: Option<String>,
+    pub description: Option<String>,
+    /// Carried over from the previous database
+    pub curated: BTreeMap<String, Json>,
+}
+
+impl Directive {
+    /// Encode the directive on a single line, with the fields in the order of the
+    /// database, and without the empty ones
+    pub fn to_json(&self) -> String {
+
+        let mut fields: Vec<(&str, Json)> = vec!(
+            ("url", Json::String(self.url.clone())),
+            ("field", Json::String(self.field.clone())),
+            ("sections", Json::Array(self.sections.iter().cloned().map(Json::String).collect())),
+        );
+        if self.list {
+            fields.push(("list", Json::Boolean(true)));
+        }
+        fields.push(("type", Json::String(self.value_type.clone())));
+        if let Some(allowed) = self.curated.get("allowed") {
+            fields.push(("allowed", allowed.clone()));
+        }
+        if let Some(introduced) = self.introduced {
+            fields.push(("introduced", Json::U64(introduced.into())));
+        }
+        for &name in &["deprecated", "removed", "replaced_by", "values"] {
+            if let Some(value) = self.curated.get(name) {
+                fields.push((name, value.clone()));
+            }
+        }
+        if let Some(ref parser) = self.parser {
+            fields.push(("parser", Json::String(parser.clone())));
+        }
+        if let Some(ref description) = self.description {
+            fields.push(("description", Json::String(description.clone())));
+        }
+
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(name, value)| format!("{}:{}", Json::String(name.into()), value))
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Encode the database as the linter embeds it, an entry per line so that its
+/// changes can be reviewed
+pub fn to_json(directives: &[Directive]) -> String {
+    let entries: Vec<String> = directives.iter().map(Directive::to_json).collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// Decode a previous database, to carry its curated fields over
+pub fn parse_previous(input: &str) -> Result<Vec<Object>, String> {
+    match Json::from_str(input).map_err(|err| format!("invalid database: {}", err))? {
+        Json::Array(entries) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                Json::Object(object) => Ok(object),
+                other => Err(format!("invalid database: expected an object, found {}", other)),
+            })
+            .collect(),
+        _ => Err("invalid database: expected an array".into()),
+    }
+}
+
+/// Build the database: an entry for each directive documented by each man page, and
+/// the entries of the previous database, or one pointing to `systemd.directives`, for
+/// the directives of the sources which are not documented. The sections, the value type
+/// and whether the directive is a list come from the sources, the description and the
+/// version it was introduced in from the man page. The removed directives of the previous database are kept, the ones no
+/// section accepts are not: they belong to other configuration files, such as the
+/// udev rules or `os-release`, or are environment variables. Sorted by directive, then
+/// by URL.
+pub fn build(entries: &[SourceEntry], pages: &[ManPage], previous: &[Object]) -> Vec<Directive> {
+
+    let mut res = vec!();
+
+    for page in pages {
+        let mut seen: Vec<&str> = vec!();
+        for documented in &page.directives {
+            if seen.contains(&&documented.key[..]) {
+                continue;
+            }
+            seen.push(&documented.key);
+
+            let url = format!("{}{}.html#{}=", MAN_URL, page.name, documented.key);
+            let sections = sections_of(entries, pages, &page.name, &documented.key);
+            let description = Some(documented.description.clone()).filter(|description| !description.is_empty());
+            res.push(directive(entries, url, &documented.key, sections, documented.introduced, description));
+        }
+    }
+
+    for entry in entries {
+        if !res.iter().any(|directive: &Directive| directive.field == entry.key) {
+            res.extend(undocumented(entries, previous, &entry.key));
+        }
+    }
+
+    for directive in &mut res {
+        if let Some(previous) = previous.iter().find(|object| {
+            object.get("url").and_then(Json::as_string) == Some(&directive.url[..]) &&
+                object.get("field").and_then(Json::as_string) == Some(&directive.field[..])
+        }) {
+            merge(directive, previous);
+        }
+    }
+
+    // the directives removed from systemd are not in its sources anymore
+    for object in previous.iter().filter(|object| object.contains_key("removed")) {
+        let field = object.get("field").and_then(Json::as_string).unwrap_or_default();
+        if !res.iter().any(|directive| directive.field == field) {
+            let url = object.get("url").and_then(Json::as_string).unwrap_or_default();
+            let mut directive = directive(&[], url.into(), field, vec!(), None, None);
+            merge(&mut directive, object);
+            res.push(directive);
+        }
+    }
+
+    res.retain(|directive| !directive.sections.is_empty());
+    res.sort_by(|a, b| (&a.field, &a.url).cmp(&(&b.field, &b.url)));
+    res
+}
+
+fn directive(entries: &[SourceEntry], url: String, key: &str, sections: Vec<String>, introduced: Option<u32>,
+             description: Option<String>) -> Directive {
+
+    let entry = entries
+        .iter()
+        .filter(|entry| entry.key == key)
+        .find(|entry| sections.is_empty() || sections.contains(&entry.section));
+
+    Directive {
+        url,
+        field: key.into(),
+        sections,
+        list: entry.is_some_and(|entry| entry.list),
+        value_type: entry.map_or("string", |entry| entry.value_type).into(),
+        introduced,
+        parser: entry.and_then(|entry| entry.parser.clone()),
+        description,
+        curated: BTreeMap::new(),
+    }
+}
+
+/// The entries of a directive no man page documents: the ones of the previous database
+/// keep their URL, with the sections of the sources they claim, and one pointing to
+/// `systemd.directives` gets the sections they do not, e.g. the ones a directive was
+/// allowed in since
+fn undocumented(entries: &[SourceEntry], previous: &[Object], key: &str) -> Vec<Directive> {
+
+    let sections = sections_of(entries, &[], "", key);
+    let documented: Vec<&Object> = previous
+        .iter()
+        .filter(|object| object.get("field").and_then(Json::as_string) == Some(key))
+        .collect();
+
+    let mut res = vec!();
+    let mut unclaimed = sections.clone();
+    for object in &documented {
+        let previous_sections = strings(object.get("sections"));
+        let claimed: Vec<String> = sections
+            .iter()
+            .filter(|section| previous_sections.contains(section))
+            .cloned()
+            .collect();
+        if !claimed.is_empty() {
+            unclaimed.retain(|section| !claimed.contains(section));
+            let url = object.get("url").and_then(Json::as_string).unwrap_or_default();
+            res.push(directive(entries, url.into(), key, claimed, None, None));
+        }
+    }
+
+    if !unclaimed.is_empty() {
+        let url = format!("{}systemd.directives.html#{}=", MAN_URL, key);
+        res.push(directive(entries, url, key, unclaimed, None, None));
+    }
+    res
+}
+
+/// The strings of a JSON array, e.g. the sections of a previous entry
+fn strings(value: Option<&Json>) -> Vec<String> {
+    value
+        .and_then(Json::as_array)
+        .map_or(vec!(), |values| values.iter().filter_map(Json::as_string).map(String::from).collect())
+}
+
+/// The sections of the sources accepting the directive. The man page named after a
+/// section, e.g. `systemd.mount` for [Mount], claims it: the `Type=` of
+/// `systemd.service` is not the one of `systemd.mount`. The other pages, such as
+/// `systemd.exec`, get the sections no page claims.
+fn sections_of(entries: &[SourceEntry], pages: &[ManPage], page: &str, key: &str) -> Vec<String> {
+
+    let page_of = |section: &str| format!("systemd.{}", section.to_lowercase());
+    let mut sections: Vec<String> = vec!();
+    for entry in entries.iter().filter(|entry| entry.key == key) {
+        if !sections.contains(&entry.section) {
+            sections.push(entry.section.clone());
+        }
+    }
+
+    if let Some(section) = sections.iter().find(|section| page_of(section) == page) {
+        return vec![section.clone()];
+    }
+    sections
+        .into_iter()
+        .filter(|section| {
+            !pages.iter().any(|other| {
+                other.name == page_of(section) && other.directives.iter().any(|documented| documented.key == key)
+            })
+        })
+        .collect()
+}
+
+/// Carry the curated fields of the previous entry over, and what the sources do not
+/// say: the sections of the directives missing from the gperf file, such as the ones
+/// of [Install], the types of the enumerations and of the values of several kinds, and
+/// the parser and the description when the sources or the man pages do not tell them
+fn merge(directive: &mut Directive, previous: &Object) {
+
+    for &name in CURATED_FIELDS {
+        if let Some(value) = previous.get(name) {
+            directive.curated.insert(name.into(), value.clone());
+        }
+    }
+    if directive.introduced.is_none() {
+        directive.introduced = previous.get("introduced").and_then(Json::as_u64).map(|version| version as u32);
+    }
+    if directive.sections.is_empty() {
+        directive.sections = strings(previous.get("sections"));
+    }
+    if directive.value_type == "string" {
+        if let Some(value_type) = previous.get("type").and_then(Json::as_string) {
+            directive.value_type = value_type.into();
+        }
+    }
+    if previous.get("list").and_then(Json::as_boolean) == Some(true) {
+        directive.list = true;
+    }
+    if directive.parser.is_none() {
+        directive.parser = previous.get("parser").and_then(Json::as_string).map(String::from);
+    }
+    if directive.description.is_none() {
+        directive.description = previous.get("description").and_then(Json::as_string).map(String::from);
+    }
+}
@@ -0,0 +1,67 @@
+use database::SourceEntry;
+use gperf::ParserInfo;
+
+/// The kinds of values of `systemd --dump-configuration-items` the linter has a type
+/// for. `PATH` and `LIMIT` stand for several types, e.g. the device of `What=` or the
+/// size of `MemoryMax=`: the previous database tells which. So does it for the lists
+/// of other values, as `MountFlags=` claims to be one while systemd keeps its last value.
+static TYPES: &[(&str, &str, bool)] = &[
+    ("BOOLEAN", "boolean", false),
+    ("SECONDS", "timespan", false),
+    ("NANOSECONDS", "timespan", false),
+    ("SIZE", "size", false),
+    ("INTEGER", "integer", false),
+    ("UNSIGNED", "integer", false),
+    ("NICE", "integer", false),
+    ("OOMSCOREADJUST", "integer", false),
+    ("UNIT", "unit", false),
+    ("SLICE", "unit", false),
+    ("SERVICE", "unit", false),
+    ("UNIT [...]", "units", true),
+    ("SOCKETS", "units", true),
+    ("PATH [...]", "paths", true),
+    ("PATH [ARGUMENT [...]]", "command", true),
+    ("CONDITION", "string", true),
+];
+
+pub fn type_info(dump_type: &str) -> ParserInfo {
+    TYPES
+        .iter()
+        .find(|&&(name, _, _)| name == dump_type)
+        .map_or(ParserInfo { value_type: "string", list: false }, |&(_, value_type, list)| {
+            ParserInfo { value_type, list }
+        })
+}
+
+/// Parse the output of `systemd --dump-configuration-items`: the sections of the unit
+/// files, each followed by its directives and the kind of value they take, e.g.
+///
+/// ```text
+/// [Unit]
+/// Description=STRING
+/// After=UNIT [...]
+/// ```
+pub fn parse(input: &str) -> Vec<SourceEntry> {
+
+    let mut section: Option<&str> = None;
+    let mut res = vec!();
+
+    for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = Some(&line[1..line.len() - 1]);
+            continue;
+        }
+        if let (Some(section), Some(equal)) = (section, line.find('=')) {
+            let info = type_info(&line[equal + 1..]);
+            res.push(SourceEntry {
+                section: section.into(),
+                key: line[..equal].into(),
+                parser: None,
+                value_type: info.value_type,
+                list: info.list,
+            });
+        }
+    }
+
+    res
+}
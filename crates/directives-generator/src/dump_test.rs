@@ -0,0 +1,44 @@
+pub use dump::*;
+pub use database::SourceEntry;
+
+fn entry(section: &str, key: &str, value_type: &'static str, list: bool) -> SourceEntry {
+    SourceEntry { section: section.into(), key: key.into(), parser: None, value_type, list }
+}
+
+#[test]
+fn it_should_parse_the_directives_of_each_section() {
+    let input = "
+[Unit]
+Description=STRING
+After=UNIT [...]
+
+[Service]
+ExecStart=PATH [ARGUMENT [...]]
+Restart=SERVICERESTART
+";
+
+    assert_eq!(vec![
+        entry("Unit", "Description", "string", false),
+        entry("Unit", "After", "units", true),
+        entry("Service", "ExecStart", "command", true),
+        entry("Service", "Restart", "string", false),
+    ], parse(input))
+}
+
+#[test]
+fn it_should_skip_the_lines_before_the_first_section() {
+    let input = "Bogus=BOOLEAN\n[Timer]\nUnit=UNIT\n";
+
+    assert_eq!(vec![entry("Timer", "Unit", "unit", false)], parse(input))
+}
+
+#[test]
+fn it_should_type_the_known_kinds_of_values_only() {
+    let boolean = type_info("BOOLEAN");
+    let limit = type_info("LIMIT");
+    let paths = type_info("PATH[:PATH[:OPTIONS]] [...]");
+
+    assert_eq!(("boolean", false), (boolean.value_type, boolean.list));
+    assert_eq!(("string", false), (limit.value_type, limit.list));
+    assert_eq!(("string", false), (paths.value_type, paths.list));
+}
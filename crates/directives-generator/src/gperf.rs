@@ -0,0 +1,110 @@
+use database::SourceEntry;
+
+/// A line of systemd's `load-fragment-gperf.gperf`, e.g.
+/// `Service.ExecStart, config_parse_exec, SERVICE_EXEC_START, offsetof(Service, exec_command)`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GperfEntry {
+    pub section: String,
+    pub key: String,
+    /// The `config_parse_*` function systemd parses the value with
+    pub parser: String,
+}
+
+impl GperfEntry {
+    pub fn to_source(&self) -> SourceEntry {
+        let info = parser_info(&self.parser);
+        SourceEntry {
+            section: self.section.clone(),
+            key: self.key.clone(),
+            parser: Some(self.parser.clone()),
+            value_type: info.value_type,
+            list: info.list,
+        }
+    }
+}
+
+/// What the linter knows of a value parser: the value type, and whether each
+/// occurrence of the directive adds to a list
+pub struct ParserInfo {
+    pub value_type: &'static str,
+    pub list: bool,
+}
+
+/// The value parsers the linter has a type for. The others parse strings, or
+/// enumerations whose values are not in the gperf file.
+static PARSERS: &[(&str, &str, bool)] = &[
+    ("config_parse_bool", "boolean", false),
+    ("config_parse_tristate", "boolean", false),
+    ("config_parse_sec", "timespan", false),
+    ("config_parse_sec_fix_0", "timespan", false),
+    ("config_parse_sec_def_infinity", "timespan", false),
+    ("config_parse_usec", "timespan", false),
+    ("config_parse_service_timeout", "timespan", false),
+    ("config_parse_service_timeout_abort", "timespan", false),
+    ("config_parse_timeout_abort", "timespan", false),
+    ("config_parse_job_timeout_sec", "timespan", false),
+    ("config_parse_job_running_timeout_sec", "timespan", false),
+    ("config_parse_iec_size", "size", false),
+    ("config_parse_iec_uint64", "size", false),
+    ("config_parse_si_uint64", "size", false),
+    ("config_parse_memory_limit", "size", false),
+    ("config_parse_tasks_max", "size", false),
+    ("config_parse_int", "integer", false),
+    ("config_parse_unsigned", "integer", false),
+    ("config_parse_uint64", "integer", false),
+    ("config_parse_nice", "integer", false),
+    ("config_parse_oom_score_adjust", "integer", false),
+    ("config_parse_rlimit", "limit", false),
+    ("config_parse_path", "path", false),
+    ("config_parse_unit_path_printf", "path", false),
+    ("config_parse_unit_path_strv_printf", "paths", true),
+    ("config_parse_namespace_path_strv", "paths", true),
+    ("config_parse_unit_requires_mounts_for", "paths", true),
+    ("config_parse_unit_slice", "unit", false),
+    ("config_parse_socket_service", "unit", false),
+    ("config_parse_trigger_unit", "unit", false),
+    ("config_parse_unit_deps", "units", true),
+    ("config_parse_exec", "command", true),
+    ("config_parse_unit_condition_path", "string", true),
+    ("config_parse_unit_condition_string", "string", true),
+    ("config_parse_strv", "string", true),
+    ("config_parse_environ", "string", true),
+    ("config_parse_unit_env_file", "string", true),
+    ("config_parse_documentation", "string", true),
+    ("config_parse_socket_listen", "string", true),
+    ("config_parse_syscall_filter", "string", true),
+];
+
+pub fn parser_info(parser: &str) -> ParserInfo {
+    PARSERS
+        .iter()
+        .find(|&&(name, _, _)| name == parser)
+        .map_or(ParserInfo { value_type: "string", list: false }, |&(_, value_type, list)| {
+            ParserInfo { value_type, list }
+        })
+}
+
+/// Parse the directives of the gperf file, as generated in the build directory of
+/// systemd: the lines between the two `%%` separators, once the templates are expanded.
+/// Lines without a section, such as the ones of other configuration files, are skipped.
+pub fn parse(input: &str) -> Vec<GperfEntry> {
+
+    input
+        .lines()
+        .skip_while(|line| line.trim() != "%%")
+        .skip(1)
+        .take_while(|line| line.trim() != "%%")
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let mut columns = line.split(',').map(|column| column.trim());
+            let name = columns.next()?;
+            let parser = columns.next()?;
+            let dot = name.find('.')?;
+            Some(GperfEntry {
+                section: name[..dot].into(),
+                key: name[dot + 1..].into(),
+                parser: parser.into(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+pub use gperf::*;
+
+fn entry(section: &str, key: &str, parser: &str) -> GperfEntry {
+    GperfEntry { section: section.into(), key: key.into(), parser: parser.into() }
+}
+
+#[test]
+fn it_should_parse_the_lines_between_the_separators() {
+    let input = "
+%{
+#include \"load-fragment.h\"
+%}
+struct ConfigPerfItem;
+%%
+Unit.Description,     config_parse_unit_string_printf, 0, offsetof(Unit, description)
+# a comment
+Service.ExecStart,    config_parse_exec,               SERVICE_EXEC_START, offsetof(Service, exec_command)
+
+Service.Restart,      config_parse_service_restart,    0, offsetof(Service, restart)
+%%
+Not.Directive, config_parse_bool, 0, 0
+";
+
+    assert_eq!(vec![
+        entry("Unit", "Description", "config_parse_unit_string_printf"),
+        entry("Service", "ExecStart", "config_parse_exec"),
+        entry("Service", "Restart", "config_parse_service_restart"),
+    ], parse(input))
+}
+
+#[test]
+fn it_should_skip_the_lines_without_a_section() {
+    let input = "%%\nBogus, config_parse_bool, 0, 0\nUnit.Wants, config_parse_unit_deps, UNIT_WANTS, 0\n%%\n";
+
+    assert_eq!(vec![entry("Unit", "Wants", "config_parse_unit_deps")], parse(input))
+}
+
+#[test]
+fn it_should_type_the_known_parsers_only() {
+    let exec = parser_info("config_parse_exec");
+    let restart = parser_info("config_parse_service_restart");
+    let slice = parser_info("config_parse_unit_slice");
+
+    assert_eq!(("command", true), (exec.value_type, exec.list));
+    assert_eq!(("unit", false), (slice.value_type, slice.list));
+    assert_eq!(("string", false), (restart.value_type, restart.list));
+}
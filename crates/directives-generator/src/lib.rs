@@ -0,0 +1,67 @@
+extern crate rustc_serialize;
+extern crate xml;
+
+pub mod database;
+pub mod dump;
+pub mod gperf;
+pub mod man;
+
+use std::fs;
+use std::path::Path;
+
+#[cfg(test)]
+mod dump_test;
+#[cfg(test)]
+mod gperf_test;
+#[cfg(test)]
+mod man_test;
+
+/// Where the directives of the unit files and their values are read from
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Source<'a> {
+    /// The `load-fragment-gperf.gperf` of the build directory of a systemd checkout
+    Gperf(&'a Path),
+    /// The output of `systemd --dump-configuration-items`
+    Dump(&'a Path),
+}
+
+/// Generate the database from the directives of systemd and the directory of its man
+/// pages, carrying the curated fields of the previous database over. Without the man
+/// pages, the directives keep the URLs and the descriptions of the previous database.
+pub fn generate(source: Source, man_dir: Option<&Path>, previous: Option<&Path>) -> Result<String, String> {
+
+    let read = |path: &Path| fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path.display(), err));
+
+    let (path, entries) = match source {
+        Source::Gperf(path) => (path, gperf::parse(&read(path)?).iter().map(gperf::GperfEntry::to_source).collect()),
+        Source::Dump(path) => (path, dump::parse(&read(path)?)),
+    };
+    if entries.is_empty() {
+        return Err(format!("no directive found in {}", path.display()));
+    }
+
+    let mut pages = vec!();
+    if let Some(man_dir) = man_dir {
+        let mut paths: Vec<_> = fs::read_dir(man_dir)
+            .map_err(|err| format!("can't read {}: {}", man_dir.display(), err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "xml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let directives = man::parse(&read(&path)?).map_err(|err| format!("{}: {}", path.display(), err))?;
+            if !directives.is_empty() {
+                pages.push(database::ManPage { name, directives });
+            }
+        }
+    }
+
+    let previous = match previous {
+        Some(path) => database::parse_previous(&read(path)?)?,
+        None => vec!(),
+    };
+
+    Ok(database::to_json(&database::build(&entries, &pages, &previous)))
+}
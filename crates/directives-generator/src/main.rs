@@ -0,0 +1,66 @@
+extern crate clap;
+extern crate systemd_directives_generator;
+
+use clap::{App, Arg, ArgGroup};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use systemd_directives_generator::Source;
+
+fn main() {
+    let matches = App::new("generate-directives")
+        .version("0.1.0")
+        .author("Thomas Wickham <twickham@octo.com>")
+        .about("generate the directive database of systemd-linter from the systemd sources")
+        .arg(Arg::with_name("gperf")
+            .long("gperf")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("The load-fragment-gperf.gperf of the build directory of systemd"))
+        .arg(Arg::with_name("dump")
+            .long("dump")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("The output of systemd --dump-configuration-items"))
+        .group(ArgGroup::with_name("directives")
+            .args(&["gperf", "dump"])
+            .required(true))
+        .arg(Arg::with_name("man")
+            .long("man")
+            .value_name("DIR")
+            .takes_value(true)
+            .help("The man/ directory of systemd"))
+        .arg(Arg::with_name("previous")
+            .long("previous")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("A previous database, whose curated fields are carried over"))
+        .arg(Arg::with_name("output")
+            .long("output")
+            .short("o")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Writes the database to the file instead of the standard output"))
+        .get_matches();
+
+    let source = match matches.value_of("gperf") {
+        Some(path) => Source::Gperf(Path::new(path)),
+        None => Source::Dump(Path::new(matches.value_of("dump").unwrap())),
+    };
+    let generated = systemd_directives_generator::generate(
+        source,
+        matches.value_of("man").map(Path::new),
+        matches.value_of("previous").map(Path::new),
+    );
+
+    let res = generated.and_then(|database| match matches.value_of("output") {
+        Some(path) => fs::write(path, database).map_err(|err| format!("can't write {}: {}", path, err)),
+        None => io::stdout().write_all(database.as_bytes()).map_err(|err| err.to_string()),
+    });
+
+    if let Err(err) = res {
+        let _ = writeln!(io::stderr(), "Error: {}", err);
+        process::exit(1);
+    }
+}
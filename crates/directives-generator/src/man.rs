@@ -0,0 +1,168 @@
+
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+
+/// A directive, as documented in a man page of systemd
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ManDirective {
+    pub key: String,
+    /// The first sentence of its documentation
+    pub description: String,
+    /// From the `<xi:include href="version-info.xml" xpointer="v243"/>` of its entry
+    pub introduced: Option<u32>,
+}
+
+/// An open `<varlistentry>`: a term listing directives, and their documentation
+#[derive(Default)]
+struct Entry {
+    keys: Vec<String>,
+    in_term: bool,
+    in_listitem: bool,
+    description: Option<String>,
+    introduced: Option<u32>,
+}
+
+/// Entities of the XML standard, the others are defined by the build of systemd
+static XML_ENTITIES: &[&str] = &["amp", "lt", "gt", "quot", "apos"];
+
+/// Parse the directives documented by a man page, e.g. `man/systemd.service.xml`:
+/// the `<varname>Key=</varname>` terms of its variable lists
+pub fn parse(input: &str) -> Result<Vec<ManDirective>, String> {
+
+    let input = expand_entities(&strip_doctype(input));
+    let mut res = vec!();
+
+    let mut entries: Vec<Entry> = vec!();
+    // the text of the varname or para being read, and how many paras are nested in it
+    let mut varname: Option<String> = None;
+    let mut para: Option<(String, usize)> = None;
+
+    for event in EventReader::new(input.as_bytes()) {
+        let event = event.map_err(|err| format!("{}: {}", err.position(), err.msg()))?;
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                "varlistentry" => entries.push(Entry::default()),
+                "term" => if let Some(entry) = entries.last_mut() { entry.in_term = true },
+                "varname" if entries.last().is_some_and(|entry| entry.in_term) => varname = Some(String::new()),
+                "listitem" => if let Some(entry) = entries.last_mut() { entry.in_listitem = true },
+                "para" => match para {
+                    Some((_, ref mut depth)) => *depth += 1,
+                    None if entries.last().is_some_and(|entry| entry.in_listitem && entry.description.is_none()) => {
+                        para = Some((String::new(), 0))
+                    }
+                    None => {}
+                },
+                "include" => {
+                    let attribute = |key: &str| {
+                        attributes.iter().find(|attribute| attribute.name.local_name == key).map(|attribute| &attribute.value[..])
+                    };
+                    if attribute("href") == Some("version-info.xml") {
+                        let version = attribute("xpointer").and_then(|pointer| pointer.trim_start_matches('v').parse().ok());
+                        if let Some(entry) = entries.last_mut() {
+                            entry.introduced = entry.introduced.or(version);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::EndElement { name } => match &name.local_name[..] {
+                "varlistentry" => {
+                    let entry = entries.pop().expect("the XML is well-formed");
+                    for key in entry.keys {
+                        res.push(ManDirective {
+                            key,
+                            description: entry.description.clone().unwrap_or_default(),
+                            introduced: entry.introduced,
+                        });
+                    }
+                }
+                "term" => if let Some(entry) = entries.last_mut() { entry.in_term = false },
+                "varname" => {
+                    let text = varname.take().unwrap_or_default();
+                    let text = text.trim();
+                    if text.ends_with('=') && !text.contains(char::is_whitespace) {
+                        if let Some(entry) = entries.last_mut() {
+                            entry.keys.push(text.trim_end_matches('=').into());
+                        }
+                    }
+                }
+                "para" => match para.take() {
+                    Some((text, 0)) => {
+                        if let Some(entry) = entries.last_mut() {
+                            entry.description = Some(first_sentence(&text));
+                        }
+                    }
+                    Some((text, depth)) => para = Some((text, depth - 1)),
+                    None => {}
+                },
+                _ => {}
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) | XmlEvent::Whitespace(text) => {
+                if let Some(ref mut varname) = varname {
+                    varname.push_str(&text);
+                }
+                if let Some((ref mut para, _)) = para {
+                    para.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(res)
+}
+
+/// Up to the first period followed by a space, with the whitespace collapsed
+fn first_sentence(text: &str) -> String {
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.find(". ") {
+        Some(idx) => text[..idx + 1].into(),
+        None => text,
+    }
+}
+
+/// The DOCTYPE of the man pages pulls the entities of the build, which are not there
+fn strip_doctype(input: &str) -> String {
+
+    let start = match input.find("<!DOCTYPE") {
+        Some(start) => start,
+        None => return input.into(),
+    };
+    let rest = &input[start..];
+    let end = match (rest.find('['), rest.find('>')) {
+        (Some(bracket), Some(gt)) if bracket < gt => rest.find("]>").map(|idx| idx + 2),
+        (_, Some(gt)) => Some(gt + 1),
+        _ => None,
+    };
+
+    match end {
+        Some(end) => format!("{}{}", &input[..start], &rest[end..]),
+        None => input.into(),
+    }
+}
+
+/// Replace the entities defined by the build of systemd, e.g. `&fstab;`, by their name
+fn expand_entities(input: &str) -> String {
+
+    let mut res = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(idx) = rest.find('&') {
+        res.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let name = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        match name {
+            Some(name) if !name.is_empty() && !XML_ENTITIES.contains(&name) && !name.starts_with('#') &&
+                name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) => {
+                res.push_str(name);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
@@ -0,0 +1,75 @@
+pub use man::*;
+
+fn directive(key: &str, description: &str, introduced: Option<u32>) -> ManDirective {
+    ManDirective { key: key.into(), description: description.into(), introduced }
+}
+
+#[test]
+fn it_should_parse_the_directives_of_the_variable_lists() {
+    let input = r#"<?xml version='1.0'?>
+<!DOCTYPE refentry PUBLIC "-//OASIS//DTD DocBook XML V4.5//EN"
+  "http://www.oasis-open.org/docbook/xml/4.5/docbookx.dtd" [
+<!ENTITY % entities SYSTEM "custom-entities.ent" >
+%entities;
+]>
+<refentry id="systemd.service" xmlns:xi="http://www.w3.org/2001/XInclude">
+  <refsect1>
+    <variablelist class='unit-directives'>
+      <varlistentry>
+        <term><varname>ExecStart=</varname></term>
+        <listitem><para>Commands that are executed when
+        this service is started. The value is split into zero or more command lines.</para>
+        <para>Unless <varname>Type=</varname> is <option>oneshot</option>, exactly one command must be given.</para></listitem>
+      </varlistentry>
+      <varlistentry>
+        <term><varname>ExecStartPre=</varname></term>
+        <term><varname>ExecStartPost=</varname></term>
+        <listitem><para>Additional commands in &fstab; that are executed before or after the command in
+        <varname>ExecStart=</varname>, respectively.</para>
+
+        <xi:include href="version-info.xml" xpointer="v243"/></listitem>
+      </varlistentry>
+      <varlistentry>
+        <term><literal>$MAINPID</literal></term>
+        <listitem><para>Not a directive.</para></listitem>
+      </varlistentry>
+    </variablelist>
+  </refsect1>
+</refentry>
+"#;
+
+    assert_eq!(Ok(vec![
+        directive("ExecStart", "Commands that are executed when this service is started.", None),
+        directive("ExecStartPre", "Additional commands in fstab that are executed before or after the command in ExecStart=, respectively.", Some(243)),
+        directive("ExecStartPost", "Additional commands in fstab that are executed before or after the command in ExecStart=, respectively.", Some(243)),
+    ]), parse(input))
+}
+
+#[test]
+fn it_should_attribute_the_nested_lists_to_their_own_entries() {
+    let input = r#"<refentry xmlns:xi="http://www.w3.org/2001/XInclude"><variablelist>
+      <varlistentry>
+        <term><varname>Type=</varname></term>
+        <listitem>
+          <variablelist>
+            <varlistentry>
+              <term><option>simple</option></term>
+              <listitem><para>The default.</para><xi:include href="version-info.xml" xpointer="v240"/></listitem>
+            </varlistentry>
+          </variablelist>
+          <para>Configures the mechanism via which the service notifies the manager that the service start-up has finished.</para>
+        </listitem>
+      </varlistentry>
+    </variablelist></refentry>"#;
+
+    assert_eq!(Ok(vec![
+        directive("Type", "Configures the mechanism via which the service notifies the manager that the service start-up has finished.", None),
+    ]), parse(input))
+}
+
+#[test]
+fn it_should_report_the_position_of_invalid_xml() {
+    let res = parse("<refentry>\n<variablelist></refentry>");
+
+    assert!(res.as_ref().err().is_some_and(|err| err.starts_with("2:")), "{:?}", res)
+}
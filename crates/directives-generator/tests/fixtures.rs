@@ -0,0 +1,48 @@
+extern crate systemd_directives_generator;
+
+use std::fs;
+use std::path::Path;
+use systemd_directives_generator::Source;
+
+/// The fixtures are excerpts of the systemd sources. When the generator changes on
+/// purpose, regenerate `expected.json` by running it on them.
+#[test]
+fn it_should_generate_the_expected_database_from_the_fixtures() {
+
+    let fixtures = Path::new("./tests/fixtures");
+    let expected = fs::read_to_string(fixtures.join("expected.json")).expect("expected database is readable");
+
+    let generated = systemd_directives_generator::generate(
+        Source::Gperf(&fixtures.join("load-fragment-gperf.gperf")),
+        Some(&fixtures.join("man")),
+        Some(&fixtures.join("previous.json")),
+    );
+
+    assert_eq!(Ok(expected), generated)
+}
+
+/// Without the man pages, the directives keep the URLs of the previous database
+#[test]
+fn it_should_generate_the_expected_database_from_a_dump() {
+
+    let fixtures = Path::new("./tests/fixtures");
+    let expected = fs::read_to_string(fixtures.join("expected-dump.json")).expect("expected database is readable");
+
+    let generated = systemd_directives_generator::generate(
+        Source::Dump(&fixtures.join("dump.txt")),
+        None,
+        Some(&fixtures.join("previous.json")),
+    );
+
+    assert_eq!(Ok(expected), generated)
+}
+
+#[test]
+fn it_should_fail_on_a_gperf_file_without_directives() {
+
+    let fixtures = Path::new("./tests/fixtures");
+
+    let generated = systemd_directives_generator::generate(Source::Gperf(&fixtures.join("previous.json")), None, None);
+
+    assert_eq!(Err("no directive found in ./tests/fixtures/previous.json".into()), generated)
+}
@@ -0,0 +1,23 @@
+extern crate systemd_directives_generator;
+
+use std::fs;
+use std::path::Path;
+use systemd_directives_generator::Source;
+
+/// The database of the linter is generated from the configuration items of systemd 252,
+/// in `data/`: regenerating it must not change it. When the generator changes on
+/// purpose, regenerate the database as the README tells.
+#[test]
+fn the_database_of_the_linter_should_be_up_to_date() {
+
+    let database = Path::new("../../src/lint/directives.json");
+    let expected = fs::read_to_string(database).expect("database is readable");
+
+    let generated = systemd_directives_generator::generate(
+        Source::Dump(Path::new("./data/systemd-252.dump")),
+        None,
+        Some(database),
+    );
+
+    assert!(generated == Ok(expected), "src/lint/directives.json is not the output of the generator")
+}
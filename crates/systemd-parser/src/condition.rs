@@ -0,0 +1,275 @@
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum ConditionKind {
+    Architecture,
+    Virtualization,
+    Host,
+    KernelCommandLine,
+    KernelVersion,
+    Security,
+    Capability,
+    ACPower,
+    NeedsUpdate,
+    FirstBoot,
+    PathExists,
+    PathExistsGlob,
+    PathIsDirectory,
+    PathIsSymbolicLink,
+    PathIsMountPoint,
+    PathIsReadWrite,
+    DirectoryNotEmpty,
+    FileNotEmpty,
+    FileIsExecutable,
+    User,
+    Group,
+    ControlGroupController,
+    Memory,
+    CPUs,
+    Environment,
+    Firmware,
+    OSRelease,
+    CPUFeature,
+    Credential,
+}
+
+static CONDITION_KINDS: &[(&str, ConditionKind)] = &[
+    ("Architecture", ConditionKind::Architecture),
+    ("Virtualization", ConditionKind::Virtualization),
+    ("Host", ConditionKind::Host),
+    ("KernelCommandLine", ConditionKind::KernelCommandLine),
+    ("KernelVersion", ConditionKind::KernelVersion),
+    ("Security", ConditionKind::Security),
+    ("Capability", ConditionKind::Capability),
+    ("ACPower", ConditionKind::ACPower),
+    ("NeedsUpdate", ConditionKind::NeedsUpdate),
+    ("FirstBoot", ConditionKind::FirstBoot),
+    ("PathExists", ConditionKind::PathExists),
+    ("PathExistsGlob", ConditionKind::PathExistsGlob),
+    ("PathIsDirectory", ConditionKind::PathIsDirectory),
+    ("PathIsSymbolicLink", ConditionKind::PathIsSymbolicLink),
+    ("PathIsMountPoint", ConditionKind::PathIsMountPoint),
+    ("PathIsReadWrite", ConditionKind::PathIsReadWrite),
+    ("DirectoryNotEmpty", ConditionKind::DirectoryNotEmpty),
+    ("FileNotEmpty", ConditionKind::FileNotEmpty),
+    ("FileIsExecutable", ConditionKind::FileIsExecutable),
+    ("User", ConditionKind::User),
+    ("Group", ConditionKind::Group),
+    ("ControlGroupController", ConditionKind::ControlGroupController),
+    ("Memory", ConditionKind::Memory),
+    ("CPUs", ConditionKind::CPUs),
+    ("Environment", ConditionKind::Environment),
+    ("Firmware", ConditionKind::Firmware),
+    ("OSRelease", ConditionKind::OSRelease),
+    ("CPUFeature", ConditionKind::CPUFeature),
+    ("Credential", ConditionKind::Credential),
+];
+
+pub static VIRTUALIZATIONS: &[&str] = &[
+    "vm", "container", "qemu", "kvm", "amazon", "zvm", "vmware", "microsoft", "oracle",
+    "powervm", "xen", "bochs", "uml", "bhyve", "qnx", "apple", "sre", "google", "parallels",
+    "openvz", "lxc", "lxc-libvirt", "systemd-nspawn", "docker", "podman", "rkt", "wsl",
+    "proot", "pouch", "acrn",
+];
+
+pub static ARCHITECTURES: &[&str] = &[
+    "native", "x86", "x86-64", "ppc", "ppc-le", "ppc64", "ppc64-le", "ia64", "parisc",
+    "parisc64", "s390", "s390x", "sparc", "sparc64", "mips", "mips-le", "mips64", "mips64-le",
+    "alpha", "arm", "arm-be", "arm64", "arm64-be", "sh", "sh64", "m68k", "tilegx", "cris",
+    "nios2", "riscv32", "riscv64", "arc", "arc-be", "loongarch64",
+];
+
+pub static SECURITY_FRAMEWORKS: &[&str] = &[
+    "selinux", "apparmor", "tomoyo", "ima", "smack", "audit", "uefi-secureboot", "tpm2",
+    "cvm", "measured-uki",
+];
+
+static BOOLEANS: &[&str] = &["1", "yes", "y", "true", "t", "on", "0", "no", "n", "false", "f", "off"];
+
+static COMPARISON_OPERATORS: &[&str] = &["<=", ">=", "!=", "<", ">", "="];
+
+/// The operators of `ConditionOSRelease=`, the fnmatch ones first
+static OS_RELEASE_OPERATORS: &[&str] = &["!$=", "$=", "<=", ">=", "!=", "<", ">", "="];
+
+/// `ConditionFirmware=` checks, the ones taking an argument end with `(`
+static FIRMWARES: &[&str] = &["uefi", "device-tree", "device-tree-compatible(", "smbios-field("];
+
+impl ConditionKind {
+    pub fn from_name(name: &str) -> Option<ConditionKind> {
+        CONDITION_KINDS
+            .iter()
+            .find(|&&(kind_name, _)| kind_name == name)
+            .map(|&(_, kind)| kind)
+    }
+
+    pub fn name(&self) -> &'static str {
+        CONDITION_KINDS
+            .iter()
+            .find(|&&(_, kind)| kind == *self)
+            .map(|&(name, _)| name)
+            .expect("every kind is in CONDITION_KINDS")
+    }
+}
+
+/// A `Condition*=` or `Assert*=` directive, e.g. `ConditionPathExists=|!/etc/foo`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Condition {
+    pub kind: ConditionKind,
+    /// `Assert*=` rather than `Condition*=`
+    pub assert: bool,
+    /// `!` prefix: the condition holds when the check fails
+    pub negated: bool,
+    /// `|` prefix: the unit starts if any of the triggering conditions holds
+    pub triggering: bool,
+    pub argument: String,
+}
+
+impl Condition {
+
+    /// Tells if `key` looks like a condition or an assertion
+    pub fn is_condition_key(key: &str) -> bool {
+        key.starts_with("Condition") || key.starts_with("Assert")
+    }
+
+    pub fn parse(key: &str, value: &str) -> Result<Condition, String> {
+
+        let (assert, name) = if let Some(name) = key.strip_prefix("Condition") {
+            (false, name)
+        } else if let Some(name) = key.strip_prefix("Assert") {
+            (true, name)
+        } else {
+            return Err(format!("not a condition nor an assertion: {}", key));
+        };
+
+        let kind = ConditionKind::from_name(name)
+            .ok_or_else(|| format!("unknown condition: {}", key))?;
+
+        let mut argument = value.trim_start();
+        let triggering = argument.starts_with('|');
+        if triggering {
+            argument = argument[1..].trim_start();
+        }
+        let negated = argument.starts_with('!');
+        if negated {
+            argument = argument[1..].trim_start();
+        }
+
+        Ok(Condition {
+            kind,
+            assert,
+            negated,
+            triggering,
+            argument: argument.trim_end().into(),
+        })
+    }
+
+    /// Check the argument makes sense for this kind of condition
+    pub fn validate(&self) -> Result<(), String> {
+        use self::ConditionKind::*;
+
+        let arg = &self.argument[..];
+        if arg.is_empty() {
+            return Err(format!("{} needs an argument", self.kind.name()));
+        }
+
+        match self.kind {
+            Architecture => check_one_of(arg, ARCHITECTURES, "architecture"),
+            Virtualization => {
+                if BOOLEANS.contains(&arg) {
+                    Ok(())
+                } else {
+                    check_one_of(arg, VIRTUALIZATIONS, "virtualization technology")
+                }
+            }
+            Security => check_one_of(arg, SECURITY_FRAMEWORKS, "security framework"),
+            ACPower | FirstBoot => check_one_of(arg, BOOLEANS, "boolean"),
+            Capability => {
+                if arg.starts_with("CAP_") && arg[4..].bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_') {
+                    Ok(())
+                } else {
+                    Err(format!("invalid capability: {}", arg))
+                }
+            }
+            NeedsUpdate => check_one_of(arg, &["/etc", "/var"], "directory"),
+            PathExists | PathExistsGlob | PathIsDirectory | PathIsSymbolicLink |
+            PathIsMountPoint | PathIsReadWrite | DirectoryNotEmpty | FileNotEmpty |
+            FileIsExecutable => check_absolute_path(arg),
+            KernelVersion => check_comparison(arg, |version| !version.is_empty()),
+            Memory => check_comparison(arg, is_size),
+            CPUs => check_comparison(arg, |cpus| cpus.parse::<u32>().is_ok()),
+            Firmware => check_firmware(arg),
+            OSRelease => check_os_release(arg),
+            Host | KernelCommandLine | User | Group | ControlGroupController | Environment |
+            CPUFeature | Credential => Ok(()),
+        }
+    }
+}
+
+fn check_one_of(arg: &str, allowed: &[&str], what: &str) -> Result<(), String> {
+    if allowed.contains(&arg) {
+        Ok(())
+    } else {
+        Err(format!("unknown {}: {} (expected one of: {})", what, arg, allowed.join(", ")))
+    }
+}
+
+fn check_absolute_path(arg: &str) -> Result<(), String> {
+    // a specifier such as %f may expand to an absolute path
+    if arg.starts_with('/') || arg.starts_with('%') {
+        Ok(())
+    } else {
+        Err(format!("path must be absolute: {}", arg))
+    }
+}
+
+/// `[OPERATOR] VALUE`, e.g. `>= 4.10` or `2G`
+fn check_comparison<F>(arg: &str, is_valid_value: F) -> Result<(), String>
+    where F: Fn(&str) -> bool {
+
+    let value = match COMPARISON_OPERATORS.iter().find(|op| arg.starts_with(*op)) {
+        Some(op) => arg[op.len()..].trim(),
+        None => arg,
+    };
+
+    if is_valid_value(value) {
+        Ok(())
+    } else {
+        Err(format!("invalid value in comparison: {}", arg))
+    }
+}
+
+/// `uefi`, `device-tree`, `device-tree-compatible(VALUE)` or `smbios-field(FIELD OP VALUE)`
+fn check_firmware(arg: &str) -> Result<(), String> {
+    let valid = FIRMWARES.iter().any(|firmware| match firmware.strip_suffix('(') {
+        Some(_) => arg.starts_with(firmware) && arg.ends_with(')') && arg.len() > firmware.len() + 1,
+        None => arg == *firmware,
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("unknown firmware check: {} (expected one of: uefi, device-tree, \
+                     device-tree-compatible(VALUE), smbios-field(FIELD OP VALUE))", arg))
+    }
+}
+
+/// `KEY OPERATOR VALUE`, e.g. `ID=fedora` or `VERSION_ID>=38`
+fn check_os_release(arg: &str) -> Result<(), String> {
+    let key_len = arg.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(arg.len());
+    let rest = &arg[key_len..];
+
+    if key_len > 0 && OS_RELEASE_OPERATORS.iter().any(|op| rest.starts_with(op)) {
+        Ok(())
+    } else {
+        Err(format!("expected KEY=VALUE, with =, !=, <, <=, >, >=, $= or !$=: {}", arg))
+    }
+}
+
+/// A size with an optional fraction and suffix, e.g. `512M` or `1.5G`
+fn is_size(arg: &str) -> bool {
+    let number = arg.strip_suffix(|c| "KMGTPE".contains(c)).unwrap_or(arg);
+    let mut parts = number.splitn(2, '.');
+    let integer = parts.next().unwrap_or("");
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+
+    is_digits(integer) && parts.next().map_or(true, is_digits)
+}
@@ -0,0 +1,136 @@
+
+pub use condition::*;
+
+mod parse {
+    pub use super::*;
+
+    #[test]
+    fn it_should_parse_a_plain_condition() {
+        let expected = Condition {
+            kind: ConditionKind::PathExists,
+            assert: false,
+            negated: false,
+            triggering: false,
+            argument: "/etc/fstab".into(),
+        };
+
+        assert_eq!(Ok(expected), Condition::parse("ConditionPathExists", "/etc/fstab"))
+    }
+
+    #[test]
+    fn it_should_parse_assertions() {
+        let res = Condition::parse("AssertVirtualization", "kvm").unwrap();
+
+        assert!(res.assert);
+        assert_eq!(ConditionKind::Virtualization, res.kind)
+    }
+
+    #[test]
+    fn it_should_take_the_prefixes_out_of_the_argument() {
+        let res = Condition::parse("ConditionPathExists", "|!/lib/udev/hwdb.bin").unwrap();
+
+        assert!(res.triggering);
+        assert!(res.negated);
+        assert_eq!("/lib/udev/hwdb.bin", res.argument)
+    }
+
+    #[test]
+    fn it_should_accept_spaces_after_the_prefixes() {
+        let res = Condition::parse("ConditionKernelCommandLine", "| ! quiet").unwrap();
+
+        assert!(res.triggering && res.negated);
+        assert_eq!("quiet", res.argument)
+    }
+
+    #[test]
+    fn it_should_not_take_a_pipe_after_a_bang_as_a_prefix() {
+        let res = Condition::parse("ConditionHost", "!|plop").unwrap();
+
+        assert!(!res.triggering);
+        assert_eq!("|plop", res.argument)
+    }
+
+    #[test]
+    fn it_should_reject_unknown_conditions() {
+        assert!(Condition::parse("ConditionPathExist", "/etc").is_err());
+        assert!(Condition::parse("Description", "/etc").is_err());
+    }
+}
+
+mod validate {
+    pub use super::*;
+
+    fn validate(key: &str, value: &str) -> Result<(), String> {
+        Condition::parse(key, value).unwrap().validate()
+    }
+
+    #[test]
+    fn it_should_check_virtualization_names() {
+        assert!(validate("ConditionVirtualization", "!container").is_ok());
+        assert!(validate("ConditionVirtualization", "no").is_ok());
+        assert!(validate("ConditionVirtualization", "virtualbox").is_err());
+    }
+
+    #[test]
+    fn it_should_check_architecture_names() {
+        assert!(validate("ConditionArchitecture", "x86-64").is_ok());
+        assert!(validate("ConditionArchitecture", "amd64").is_err());
+    }
+
+    #[test]
+    fn it_should_check_paths_are_absolute() {
+        assert!(validate("ConditionPathExists", "!/usr/.noupdate").is_ok());
+        assert!(validate("ConditionFileNotEmpty", "%f").is_ok());
+        assert!(validate("AssertPathIsDirectory", "var/lib").is_err());
+    }
+
+    #[test]
+    fn it_should_check_version_comparisons() {
+        assert!(validate("ConditionKernelVersion", ">= 4.10").is_ok());
+        assert!(validate("ConditionKernelVersion", "4.*").is_ok());
+        assert!(validate("ConditionKernelVersion", ">=").is_err());
+        assert!(validate("ConditionMemory", ">=2G").is_ok());
+        assert!(validate("ConditionMemory", ">=lots").is_err());
+        assert!(validate("ConditionMemory", ">= 1.5G").is_ok());
+        assert!(validate("ConditionMemory", "1.G").is_err());
+        assert!(validate("ConditionMemory", "2GG").is_err());
+        assert!(validate("ConditionCPUs", "> 2").is_ok());
+    }
+
+    #[test]
+    fn it_should_check_capabilities_and_booleans() {
+        assert!(validate("ConditionCapability", "CAP_SYS_ADMIN").is_ok());
+        assert!(validate("ConditionCapability", "sys_admin").is_err());
+        assert!(validate("ConditionFirstBoot", "yes").is_ok());
+        assert!(validate("ConditionACPower", "plugged").is_err());
+    }
+
+    #[test]
+    fn it_should_check_firmwares() {
+        assert!(validate("ConditionFirmware", "uefi").is_ok());
+        assert!(validate("ConditionFirmware", "device-tree-compatible(raspberrypi,4-model-b)").is_ok());
+        assert!(validate("ConditionFirmware", "smbios-field(board_vendor = Foo)").is_ok());
+        assert!(validate("ConditionFirmware", "bios").is_err());
+        assert!(validate("ConditionFirmware", "smbios-field()").is_err());
+    }
+
+    #[test]
+    fn it_should_check_os_release_comparisons() {
+        assert!(validate("ConditionOSRelease", "ID=fedora").is_ok());
+        assert!(validate("ConditionOSRelease", "VERSION_ID>=38").is_ok());
+        assert!(validate("AssertOSRelease", "!ID$=debian*").is_ok());
+        assert!(validate("ConditionOSRelease", "fedora").is_err());
+        assert!(validate("ConditionOSRelease", "=fedora").is_err());
+    }
+
+    #[test]
+    fn it_should_know_cpu_features_and_credentials() {
+        assert!(validate("ConditionCPUFeature", "sse4_2").is_ok());
+        assert!(validate("AssertCredential", "mykey").is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_missing_arguments() {
+        assert!(validate("ConditionHost", "!").is_err())
+    }
+}
@@ -0,0 +1,126 @@
+
+use std::fmt;
+use std::str;
+
+static UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DecodeIssue {
+    /// A byte sequence which is not valid UTF-8, replaced by U+FFFD
+    InvalidUtf8 { len: usize },
+    /// A NUL byte, dropped from the text
+    NulByte,
+    /// A carriage return which does not end a CRLF line, kept in the text
+    StrayCarriageReturn,
+}
+
+/// Something odd found in the raw input, at byte `offset` of the original bytes.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct DecodeDiagnostic {
+    pub offset: usize,
+    pub issue: DecodeIssue,
+}
+
+impl fmt::Display for DecodeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.issue {
+            DecodeIssue::InvalidUtf8 { len } => {
+                write!(f, "byte {}: invalid UTF-8 sequence of {} byte(s)", self.offset, len)
+            }
+            DecodeIssue::NulByte => write!(f, "byte {}: NUL byte", self.offset),
+            DecodeIssue::StrayCarriageReturn => {
+                write!(f, "byte {}: carriage return out of a CRLF line ending", self.offset)
+            }
+        }
+    }
+}
+
+/// The text of a unit file, ready to be parsed: the BOM is stripped, CRLF line
+/// endings are turned into LF, NUL bytes are dropped and invalid UTF-8 is replaced.
+/// Lone carriage returns are kept, but reported.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DecodedInput {
+    text: String,
+    diagnostics: Vec<DecodeDiagnostic>,
+    /// (offset in text, offset in input) at every point where the two diverge
+    offsets: Vec<(usize, usize)>,
+}
+
+impl DecodedInput {
+    pub fn text(&self) -> &str { &self.text }
+    pub fn diagnostics(&self) -> &[DecodeDiagnostic] { &self.diagnostics }
+
+    /// Map an offset in the decoded text back to the offset in the original bytes
+    pub fn original_offset(&self, offset: usize) -> usize {
+        let idx = self.offsets.partition_point(|&(text_offset, _)| text_offset <= offset);
+        match idx {
+            0 => offset,
+            _ => {
+                let (text_offset, input_offset) = self.offsets[idx - 1];
+                input_offset + offset - text_offset
+            }
+        }
+    }
+}
+
+pub fn decode(input: &[u8]) -> DecodedInput {
+
+    let mut res = DecodedInput {
+        text: String::with_capacity(input.len()),
+        diagnostics: vec!(),
+        offsets: vec!(),
+    };
+
+    let mut pos = 0;
+    if input.starts_with(UTF8_BOM) {
+        pos = UTF8_BOM.len();
+        res.offsets.push((0, pos));
+    }
+
+    while pos < input.len() {
+        let (valid, invalid_len) = match str::from_utf8(&input[pos..]) {
+            Ok(valid) => (valid, None),
+            Err(err) => {
+                let valid = str::from_utf8(&input[pos..pos + err.valid_up_to()]).expect("checked by from_utf8");
+                (valid, Some(err.error_len().unwrap_or(input.len() - pos - err.valid_up_to())))
+            }
+        };
+
+        push_normalized(&mut res, valid, pos);
+        pos += valid.len();
+
+        if let Some(len) = invalid_len {
+            res.diagnostics.push(DecodeDiagnostic { offset: pos, issue: DecodeIssue::InvalidUtf8 { len } });
+            res.text.push('\u{FFFD}');
+            pos += len;
+            res.offsets.push((res.text.len(), pos));
+        }
+    }
+
+    res
+}
+
+fn push_normalized(res: &mut DecodedInput, valid: &str, input_offset: usize) {
+
+    let bytes = valid.as_bytes();
+    let mut chunk_start = 0;
+
+    for (idx, &byte) in bytes.iter().enumerate() {
+        let is_crlf = byte == b'\r' && bytes.get(idx + 1) == Some(&b'\n');
+        if byte == b'\r' && !is_crlf {
+            res.diagnostics.push(DecodeDiagnostic { offset: input_offset + idx, issue: DecodeIssue::StrayCarriageReturn });
+        }
+        if byte != 0 && !is_crlf {
+            continue;
+        }
+
+        res.text.push_str(&valid[chunk_start..idx]);
+        chunk_start = idx + 1;
+        if byte == 0 {
+            res.diagnostics.push(DecodeDiagnostic { offset: input_offset + idx, issue: DecodeIssue::NulByte });
+        }
+        res.offsets.push((res.text.len(), input_offset + chunk_start));
+    }
+
+    res.text.push_str(&valid[chunk_start..]);
+}
@@ -0,0 +1,102 @@
+
+pub use decode::*;
+pub use parse_bytes;
+
+mod decode {
+    pub use super::*;
+
+    #[test]
+    fn it_should_keep_valid_utf8_as_is() {
+        let res = decode("[Unit]\nDescription=👋\n".as_bytes());
+
+        assert_eq!("[Unit]\nDescription=👋\n", res.text());
+        assert!(res.diagnostics().is_empty())
+    }
+
+    #[test]
+    fn it_should_strip_the_bom() {
+        let res = decode(b"\xEF\xBB\xBF[Unit]");
+
+        assert_eq!("[Unit]", res.text());
+        assert_eq!(3, res.original_offset(0))
+    }
+
+    #[test]
+    fn it_should_normalize_crlf() {
+        let res = decode(b"[Unit]\r\nDescription=plop\r\n");
+
+        assert_eq!("[Unit]\nDescription=plop\n", res.text());
+        assert!(res.diagnostics().is_empty())
+    }
+
+    #[test]
+    fn it_should_map_offsets_after_crlf_to_the_original_bytes() {
+        let res = decode(b"[Unit]\r\nDescription=plop\r\n");
+
+        assert_eq!(5, res.original_offset(5));
+        // the line feeds and everything after them are shifted by the dropped "\r"
+        assert_eq!(7, res.original_offset(6));
+        assert_eq!(8, res.original_offset(7));
+        assert_eq!(25, res.original_offset(23))
+    }
+
+    #[test]
+    fn it_should_keep_and_report_lone_carriage_returns() {
+        let res = decode(b"[Unit]\rplop");
+
+        assert_eq!("[Unit]\rplop", res.text());
+        assert_eq!(&[DecodeDiagnostic { offset: 6, issue: DecodeIssue::StrayCarriageReturn }], res.diagnostics())
+    }
+
+    #[test]
+    fn it_should_report_and_drop_nul_bytes() {
+        let res = decode(b"[Unit]\n\0\0");
+
+        assert_eq!("[Unit]\n", res.text());
+        assert_eq!(&[
+            DecodeDiagnostic { offset: 7, issue: DecodeIssue::NulByte },
+            DecodeDiagnostic { offset: 8, issue: DecodeIssue::NulByte },
+        ], res.diagnostics())
+    }
+
+    #[test]
+    fn it_should_report_and_replace_invalid_utf8() {
+        let res = decode(b"[Unit]\nDescription=caf\xE9\nAfter=a");
+
+        assert_eq!("[Unit]\nDescription=caf\u{FFFD}\nAfter=a", res.text());
+        assert_eq!(&[
+            DecodeDiagnostic { offset: 22, issue: DecodeIssue::InvalidUtf8 { len: 1 } },
+        ], res.diagnostics());
+        assert_eq!(23, res.original_offset(25))
+    }
+
+    #[test]
+    fn it_should_report_truncated_sequences_at_the_end() {
+        let res = decode(b"[Unit]\xF0\x9F");
+
+        assert_eq!("[Unit]\u{FFFD}", res.text());
+        assert_eq!(&[
+            DecodeDiagnostic { offset: 6, issue: DecodeIssue::InvalidUtf8 { len: 2 } },
+        ], res.diagnostics())
+    }
+}
+
+mod parse_bytes {
+    pub use super::*;
+
+    #[test]
+    fn it_should_parse_a_crlf_file_with_a_bom() {
+        let res = parse_bytes(b"\xEF\xBB\xBF[Unit]\r\nDescription=plop\r\n");
+
+        assert!(res.is_ok());
+        assert!(res.unwrap().0.has_key("Description"))
+    }
+
+    #[test]
+    fn it_should_parse_despite_invalid_bytes() {
+        let (unit, diagnostics) = parse_bytes(b"[Unit]\nDescription=caf\xE9\n\0").unwrap();
+
+        assert!(unit.has_key("Description"));
+        assert_eq!(2, diagnostics.len())
+    }
+}
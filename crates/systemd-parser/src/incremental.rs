@@ -0,0 +1,328 @@
+
+use errors::{ParserError, ParseErrorInternal};
+use items::{SystemdItem, SystemdUnit};
+use parser::parse_line;
+use std::ops::Range;
+
+/// A text edit, expressed in byte offsets of the text it applies to.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, replacement: &str) -> TextEdit {
+        TextEdit {
+            range,
+            replacement: String::from(replacement),
+        }
+    }
+}
+
+quick_error!(
+    /// Why an edit cannot be applied to a `Document`
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub enum EditError {
+        OutOfBounds(range: Range<usize>, len: usize) {
+            description("The edit is out of the source")
+            display("edit {:?} is out of the source of {} bytes", range, len)
+        }
+        NotCharBoundary(offset: usize) {
+            description("The edit splits a character")
+            display("edit boundary {} is inside a character", offset)
+        }
+    }
+);
+
+/// Where a directive is in the source of a `Document`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DirectiveSpan {
+    pub category: String,
+    pub key: String,
+    /// The whole logical line, including its final line feed
+    pub line: Range<usize>,
+    /// The raw value, continuation lines included, without the surrounding spaces
+    pub value: Range<usize>,
+}
+
+/// Where a category is in the source of a `Document`
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CategorySpan {
+    pub category: String,
+    /// From the header to the end of the last directive of the category
+    pub span: Range<usize>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum LineItem {
+    Blank,
+    Comment(String),
+    Category(String),
+    Directive(String, Option<String>),
+    Error(String),
+}
+
+impl LineItem {
+    fn as_systemd_item<'a>(&'a self) -> Option<SystemdItem<'a>> {
+        match *self {
+            LineItem::Comment(ref comment) => Some(SystemdItem::Comment(comment)),
+            LineItem::Category(ref cat) => Some(SystemdItem::Category(cat)),
+            LineItem::Directive(ref key, ref value) => {
+                Some(SystemdItem::Directive(key, value.as_ref().map(|s| &s[..])))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A logical line: one or more physical lines joined by trailing backslashes.
+/// `span` covers the raw text of the line, including its final line feed.
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct LogicalLine {
+    span: Range<usize>,
+    terminated: bool,
+    item: LineItem,
+}
+
+/// A parsed unit file which keeps its source and the parse result of every line,
+/// so that it can be updated after an edit by re-parsing only the lines it touches.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Document {
+    source: String,
+    lines: Vec<LogicalLine>,
+}
+
+impl Document {
+    pub fn parse(input: &str) -> Document {
+        let source = String::from(input);
+        let lines = lex_lines(&source, 0, None);
+        Document {
+            source,
+            lines,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Build the unit from the cached lines. Gives the same result as
+    /// `parse_string(self.source())`.
+    pub fn unit(&self) -> Result<SystemdUnit, ParserError> {
+
+        let errors: Vec<ParseErrorInternal> = self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| match line.item {
+                LineItem::Error(ref err) => Some((err.clone(), idx as u32 + 1).into()),
+                _ => None,
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            return Err(ParserError::ParseError(errors));
+        }
+
+        let mut items = vec!();
+        let mut lines = vec!();
+        let mut number = 1;
+        let mut counted = 0;
+        for line in &self.lines {
+            number += self.source[counted..line.span.start].matches('\n').count() as u32;
+            counted = line.span.start;
+            if let Some(item) = line.item.as_systemd_item() {
+                items.push(item);
+                lines.push(number);
+            }
+        }
+
+        Ok(SystemdUnit::with_lines(&items, &lines)?)
+    }
+
+    /// Every directive with its position, in source order
+    pub fn directive_spans(&self) -> Vec<DirectiveSpan> {
+
+        let mut res = vec!();
+        let mut category = None;
+
+        for line in &self.lines {
+            match line.item {
+                LineItem::Category(ref cat) => category = Some(cat),
+                LineItem::Directive(ref key, _) => {
+                    if let Some(cat) = category {
+                        res.push(DirectiveSpan {
+                            category: cat.clone(),
+                            key: key.clone(),
+                            line: line.span.clone(),
+                            value: self.value_span(&line.span),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        res
+    }
+
+    /// Every category with its position, in source order. A category which appears
+    /// twice has two spans.
+    pub fn category_spans(&self) -> Vec<CategorySpan> {
+
+        let mut res: Vec<CategorySpan> = vec!();
+
+        for line in &self.lines {
+            match line.item {
+                LineItem::Category(ref cat) => res.push(CategorySpan {
+                    category: cat.clone(),
+                    span: line.span.clone(),
+                }),
+                LineItem::Directive(..) => {
+                    if let Some(last) = res.last_mut() {
+                        last.span.end = line.span.end;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        res
+    }
+
+    fn value_span(&self, line: &Range<usize>) -> Range<usize> {
+
+        let raw = &self.source[line.clone()];
+        let after_equal = raw.find('=').map_or(raw.len(), |idx| idx + 1);
+        let value = raw[after_equal..].trim_end();
+        let leading_spaces = value.len() - value.trim_start_matches(' ').len();
+
+        let value_start = line.start + after_equal;
+        value_start + leading_spaces..value_start + value.len()
+    }
+
+    /// Apply `edit` and re-parse the lines it affects. Returns the spans, in the
+    /// edited source, of the lines which have been re-parsed. The document is left
+    /// untouched when the edit is out of the source or splits a character.
+    pub fn apply_edit(&mut self, edit: &TextEdit) -> Result<Vec<Range<usize>>, EditError> {
+
+        let old_len = self.source.len();
+        let start = edit.range.start;
+        let end = edit.range.end;
+        if start > end || end > old_len {
+            return Err(EditError::OutOfBounds(edit.range.clone(), old_len));
+        }
+        if let Some(&offset) = [start, end].iter().find(|&&offset| !self.source.is_char_boundary(offset)) {
+            return Err(EditError::NotCharBoundary(offset));
+        }
+
+        // first line touched by the edit: an insertion at the very start of a
+        // line does not change the previous line, unless that one is not terminated
+        let mut first = self.lines
+            .iter()
+            .position(|line| line.span.end > start)
+            .unwrap_or(self.lines.len());
+        if first > 0 && !self.lines[first - 1].terminated {
+            first -= 1;
+        }
+        let lex_start = self.lines.get(first).map_or(old_len, |line| line.span.start);
+
+        self.source.drain(start..end);
+        self.source.insert_str(start, &edit.replacement);
+
+        let shift = |pos: usize| pos - (end - start) + edit.replacement.len();
+        let edit_end = start + edit.replacement.len();
+
+        // lines starting after the edit are still valid once the lexer lands on
+        // one of their boundaries again
+        let resync_candidates: Vec<usize> = self.lines[first..]
+            .iter()
+            .filter(|line| line.span.start >= end)
+            .map(|line| shift(line.span.start))
+            .collect();
+
+        let new_lines = lex_lines(&self.source, lex_start, Some((edit_end, &resync_candidates)));
+        let resync_at = new_lines.last().map_or(lex_start, |line| line.span.end);
+
+        let reused_from = self.lines[first..]
+            .iter()
+            .position(|line| line.span.start >= end && shift(line.span.start) == resync_at)
+            .map_or(self.lines.len(), |idx| first + idx);
+
+        let changed = new_lines.iter().map(|line| line.span.clone()).collect();
+
+        let mut tail = self.lines.split_off(reused_from);
+        for line in &mut tail {
+            line.span = shift(line.span.start)..shift(line.span.end);
+        }
+        self.lines.truncate(first);
+        self.lines.extend(new_lines);
+        self.lines.extend(tail);
+
+        Ok(changed)
+    }
+}
+
+/// Split `source` in logical lines from `from` and parse each one. When `resync` is
+/// given, stop at the first line boundary past `resync.0` which is listed in `resync.1`.
+fn lex_lines(source: &str, from: usize, resync: Option<(usize, &[usize])>) -> Vec<LogicalLine> {
+
+    let bytes = source.as_bytes();
+    let mut res = vec!();
+    let mut line_start = from;
+
+    while line_start < source.len() {
+        if let Some((edit_end, candidates)) = resync {
+            if line_start >= edit_end && candidates.binary_search(&line_start).is_ok() {
+                break;
+            }
+        }
+
+        let mut content = String::new();
+        let mut segment_start = line_start;
+        let mut line_end = source.len();
+        let mut terminated = false;
+
+        while let Some(offset) = source[segment_start..].find('\n') {
+            let newline = segment_start + offset;
+            if newline > segment_start && bytes[newline - 1] == b'\\' {
+                // continuation line: drop the backslash and the line feed
+                content.push_str(&source[segment_start..newline - 1]);
+                segment_start = newline + 1;
+            } else {
+                content.push_str(&source[segment_start..newline]);
+                line_end = newline + 1;
+                terminated = true;
+                break;
+            }
+        }
+        if !terminated {
+            content.push_str(&source[segment_start..]);
+        } else if content.ends_with('\r') {
+            content.pop();
+        }
+
+        res.push(LogicalLine {
+            span: line_start..line_end,
+            terminated,
+            item: parse_logical_line(&content),
+        });
+        line_start = line_end;
+    }
+
+    res
+}
+
+fn parse_logical_line(content: &str) -> LineItem {
+
+    if content.trim().is_empty() {
+        return LineItem::Blank;
+    }
+
+    match parse_line(content).to_full_result() {
+        Ok(SystemdItem::Comment(comment)) => LineItem::Comment(comment.into()),
+        Ok(SystemdItem::Category(cat)) => LineItem::Category(cat.into()),
+        Ok(SystemdItem::Directive(key, value)) => LineItem::Directive(key.into(), value.map(String::from)),
+        Err(err) => LineItem::Error(format!("{:?}", err)),
+    }
+}
@@ -0,0 +1,162 @@
+
+pub use incremental::*;
+pub use parse_string;
+
+fn apply(input: &str, edit: TextEdit) -> (Document, Vec<::std::ops::Range<usize>>) {
+    let mut doc = Document::parse(input);
+    let changed = doc.apply_edit(&edit).unwrap();
+    (doc, changed)
+}
+
+mod parse {
+    pub use super::*;
+
+    #[test]
+    fn it_should_give_the_same_unit_as_parse_string() {
+        let input = "[Unit]\nDescription=plop\n\n[Service]\nExecStart=/bin/true\n";
+        let doc = Document::parse(input);
+
+        assert_eq!(parse_string(input).unwrap(), doc.unit().unwrap())
+    }
+
+    #[test]
+    fn it_should_join_continuation_lines() {
+        let input = "[Service]\nExecStart=/bin/echo \\\n  plop\n";
+        let doc = Document::parse(input);
+
+        assert_eq!(parse_string(input).unwrap(), doc.unit().unwrap())
+    }
+
+    #[test]
+    fn it_should_keep_the_lines_of_the_directives() {
+        let input = "[Service]\nExecStart=/bin/echo \\\n  plop\n\n# comment\nType=simple\nType=exec\n";
+        let unit = Document::parse(input).unit().unwrap();
+
+        let lines: Vec<Option<u32>> = match *unit.lookup_by_key("Type").unwrap() {
+            ::items::DirectiveEntry::Many(ref entries) => entries.iter().map(|entry| entry.line()).collect(),
+            ::items::DirectiveEntry::Solo(_) => vec!(),
+        };
+        assert_eq!(vec![Some(6), Some(7)], lines);
+        assert_eq!(parse_string(input).unwrap(), unit)
+    }
+
+    #[test]
+    fn it_should_err_on_bad_lines() {
+        let input = "[Unit]\nplop\n";
+        let doc = Document::parse(input);
+
+        assert!(doc.unit().is_err())
+    }
+}
+
+mod apply_edit {
+    pub use super::*;
+
+    #[test]
+    fn it_should_update_the_source() {
+        let (doc, _) = apply("[Unit]\nDescription=plop\n", TextEdit::new(19..23, "yo"));
+
+        assert_eq!("[Unit]\nDescription=yo\n", doc.source())
+    }
+
+    #[test]
+    fn it_should_only_reparse_the_edited_line() {
+        let input = "[Unit]\nDescription=plop\n[Service]\nExecStart=/bin/true\n";
+        let (_, changed) = apply(input, TextEdit::new(19..23, "yo"));
+
+        assert_eq!(vec![7..22], changed)
+    }
+
+    #[test]
+    fn it_should_reparse_both_lines_when_joining_them() {
+        let input = "[Unit]\nDescription=plop\n[Service]\nExecStart=/bin/true\n";
+        let (doc, changed) = apply(input, TextEdit::new(23..23, " \\"));
+
+        assert_eq!(vec![7..36], changed);
+        assert!(doc.unit().is_ok())
+    }
+
+    #[test]
+    fn it_should_extend_an_unterminated_last_line() {
+        let input = "[Service]\nExecStart=/bin/tr";
+        let (doc, _) = apply(input, TextEdit::new(27..27, "ue"));
+
+        assert_eq!(parse_string(doc.source()).unwrap(), doc.unit().unwrap())
+    }
+
+    #[test]
+    fn it_should_not_reparse_anything_when_deleting_whole_lines() {
+        let input = "[Unit]\nDescription=plop\n[Service]\nExecStart=/bin/true\n";
+        let (doc, changed) = apply(input, TextEdit::new(7..24, ""));
+
+        assert!(changed.is_empty());
+        assert_eq!(parse_string(doc.source()).unwrap(), doc.unit().unwrap())
+    }
+
+    #[test]
+    fn it_should_reject_edits_out_of_the_source() {
+        let input = "[Unit]\nDescription=plop\n";
+        let mut doc = Document::parse(input);
+
+        assert_eq!(Err(EditError::OutOfBounds(20..30, 24)), doc.apply_edit(&TextEdit::new(20..30, "")));
+        assert_eq!(Err(EditError::OutOfBounds(10..5, 24)), doc.apply_edit(&TextEdit::new(10..5, "")));
+        assert_eq!(Document::parse(input), doc)
+    }
+
+    #[test]
+    fn it_should_reject_edits_splitting_a_character() {
+        let input = "[Unit]\nDescription=plé\n";
+        let mut doc = Document::parse(input);
+
+        assert_eq!(Err(EditError::NotCharBoundary(22)), doc.apply_edit(&TextEdit::new(21..22, "")));
+        assert_eq!(Document::parse(input), doc)
+    }
+
+    #[test]
+    fn it_should_report_new_errors() {
+        let input = "[Unit]\nDescription=plop\n";
+        let (doc, _) = apply(input, TextEdit::new(18..19, ""));
+
+        assert!(doc.unit().is_err())
+    }
+}
+
+mod spans {
+    pub use super::*;
+
+    #[test]
+    fn it_should_locate_directives_and_their_values() {
+        let input = "[Unit]\nDescription = plop \n[Service]\nExecStart=/bin/echo \\\n  plop\nType=\n";
+        let doc = Document::parse(input);
+
+        let spans = doc.directive_spans();
+        let found: Vec<(&str, &str, &str, &str)> = spans
+            .iter()
+            .map(|span| (&span.category[..], &span.key[..], &input[span.line.clone()], &input[span.value.clone()]))
+            .collect();
+
+        assert_eq!(vec![
+            ("Unit", "Description", "Description = plop \n", "plop"),
+            ("Service", "ExecStart", "ExecStart=/bin/echo \\\n  plop\n", "/bin/echo \\\n  plop"),
+            ("Service", "Type", "Type=\n", ""),
+        ], found)
+    }
+
+    #[test]
+    fn it_should_locate_categories_up_to_their_last_directive() {
+        let input = "[Unit]\nDescription=plop\n\n# about the service\n[Service]\n\n[Unit]\nWants=foo\n# the end\n";
+        let doc = Document::parse(input);
+
+        let spans = doc.category_spans();
+        let found: Vec<(&str, &str)> = spans
+            .iter()
+            .map(|span| (&span.category[..], &input[span.span.clone()]))
+            .collect();
+
+        assert_eq!(vec![
+            ("Unit", "[Unit]\nDescription=plop\n"),
+            ("Service", "[Service]\n"),
+            ("Unit", "[Unit]\nWants=foo\n"),
+        ], found)
+    }
+}
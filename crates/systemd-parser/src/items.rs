@@ -1,5 +1,7 @@
 
 use std::collections::HashMap;
+use std::path::Path;
+use suppression::{collect_suppressions, Suppression};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum SystemdItem<'a> {
@@ -19,12 +21,15 @@ pub struct UnitDirective {
     key: String,
     value: Option<String>,
     category: String,
+    /// The line of the source the directive starts on, when parsed from a source
+    line: Option<u32>,
 }
 
 impl UnitDirective {
     pub fn value(&self) -> Option<&str> { self.value.as_ref().map(|s| &s[..]) }
     pub fn key(&self) -> &str { &self.key }
     pub fn category(&self) -> &str { &self.category }
+    pub fn line(&self) -> Option<u32> { self.line }
 }
 
 impl UnitDirective {
@@ -33,12 +38,20 @@ impl UnitDirective {
             category: String::from(category),
             value: value.map(String::from),
             key: String::from(key),
+            line: None,
         }
     }
 
     pub fn item_list_to_unit_directive_list(unit_items: &Vec<SystemdItem>)
         -> Result<Vec<UnitDirective>, String> {
 
+        UnitDirective::item_list_with_lines(unit_items, &[])
+    }
+
+    /// `lines` holds the line number of each item, if known
+    fn item_list_with_lines(unit_items: &Vec<SystemdItem>, lines: &[u32])
+        -> Result<Vec<UnitDirective>, String> {
+
         use self::SystemdItem::*;
 
         let directive_count = unit_items.iter().filter(|items| items.is_directive()).count();
@@ -49,11 +62,14 @@ impl UnitDirective {
         let mut cat = try!(UnitDirective::get_first_category(unit_items));
         let mut res = vec!();
 
-        for item in unit_items {
+        for (idx, item) in unit_items.iter().enumerate() {
             match *item {
                 Category(new_cat) => cat = new_cat,
-                Directive(key, value) => res.push(UnitDirective::new(cat, key, value)),
-                _ => () // TODO: do something with comments ?
+                Directive(key, value) => res.push(UnitDirective {
+                    line: lines.get(idx).cloned(),
+                    ..UnitDirective::new(cat, key, value)
+                }),
+                _ => () // comments are read by `collect_suppressions`
             }
         }
 
@@ -76,9 +92,119 @@ impl UnitDirective {
     }
 }
 
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum UnitType {
+    Service,
+    Socket,
+    Target,
+    Device,
+    Mount,
+    Automount,
+    Swap,
+    Timer,
+    Path,
+    Slice,
+    Scope,
+    /// A `.conf` file extending or overriding another unit
+    DropIn,
+}
+
+static UNIT_TYPE_SUFFIXES: &[(&str, UnitType)] = &[
+    ("service", UnitType::Service),
+    ("socket", UnitType::Socket),
+    ("target", UnitType::Target),
+    ("device", UnitType::Device),
+    ("mount", UnitType::Mount),
+    ("automount", UnitType::Automount),
+    ("swap", UnitType::Swap),
+    ("timer", UnitType::Timer),
+    ("path", UnitType::Path),
+    ("slice", UnitType::Slice),
+    ("scope", UnitType::Scope),
+];
+
+impl UnitType {
+    /// Unit type given by the file name suffix, e.g. `sshd.service`. A `.conf` file is
+    /// a drop-in only in the `.d` directory of a unit or of a unit type, e.g.
+    /// `sshd.service.d/override.conf` or `service.d/limits.conf`: the configuration
+    /// files of the daemons, e.g. `journald.conf`, are not units.
+    pub fn from_path(path: &Path) -> Option<UnitType> {
+        let extension = match path.extension() {
+            Some(extension) => extension.to_string_lossy(),
+            None => return None,
+        };
+
+        if extension == "conf" {
+            let dir = path.parent().and_then(|dir| dir.file_name()).map(|dir| dir.to_string_lossy());
+            let unit = dir.as_ref().and_then(|dir| dir.strip_suffix(".d"));
+            return match unit {
+                Some(unit) if UnitType::from_suffix(unit.rsplit('.').next().unwrap_or(unit)).is_some() => {
+                    Some(UnitType::DropIn)
+                }
+                _ => None,
+            };
+        }
+
+        UnitType::from_suffix(&extension)
+    }
+
+    fn from_suffix(suffix: &str) -> Option<UnitType> {
+        UNIT_TYPE_SUFFIXES
+            .iter()
+            .find(|&&(known, _)| known == suffix)
+            .map(|&(_, unit_type)| unit_type)
+    }
+
+    /// Unit type given by the type-specific section of the unit, e.g. `[Service]`.
+    /// Targets and devices have no such section, and the type is ambiguous when the
+    /// unit has many of them.
+    pub fn from_categories(categories: &[String]) -> Option<UnitType> {
+        let mut found = UNIT_TYPE_SUFFIXES
+            .iter()
+            .filter(|&&(_, unit_type)| match unit_type.category() {
+                Some(category) => categories.iter().any(|cat| cat == category),
+                None => false,
+            })
+            .map(|&(_, unit_type)| unit_type);
+
+        match (found.next(), found.next()) {
+            (Some(unit_type), None) => Some(unit_type),
+            _ => None,
+        }
+    }
+
+    /// The type-specific sections of every unit type, e.g. `Service`
+    pub fn categories() -> Vec<&'static str> {
+        UNIT_TYPE_SUFFIXES
+            .iter()
+            .filter_map(|&(_, unit_type)| unit_type.category())
+            .collect()
+    }
+
+    /// The section holding the type-specific directives, if any
+    pub fn category(&self) -> Option<&'static str> {
+        use self::UnitType::*;
+
+        match *self {
+            Service => Some("Service"),
+            Socket => Some("Socket"),
+            Mount => Some("Mount"),
+            Automount => Some("Automount"),
+            Swap => Some("Swap"),
+            Timer => Some("Timer"),
+            Path => Some("Path"),
+            Slice => Some("Slice"),
+            Scope => Some("Scope"),
+            Target | Device | DropIn => None,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct SystemdUnit {
     directives: HashMap<String, DirectiveEntry>,
+    unit_type: Option<UnitType>,
+    suppressions: Vec<Suppression>,
 }
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -110,18 +236,26 @@ impl DirectiveEntry {
 impl SystemdUnit {
 
     pub fn new(unit_items: &Vec<SystemdItem>) -> Result<SystemdUnit, String> {
+        SystemdUnit::with_lines(unit_items, &[])
+    }
+
+    /// Like `new`, `lines` holding the line number of each item in the source
+    pub fn with_lines(unit_items: &Vec<SystemdItem>, lines: &[u32]) -> Result<SystemdUnit, String> {
 
         let directives = try!(
-            UnitDirective::item_list_to_unit_directive_list(&unit_items)
+            UnitDirective::item_list_with_lines(&unit_items, lines)
         );
 
         let directives_hash = try!(
             SystemdUnit::hash_from_directives(directives)
         );
 
-        let res = SystemdUnit {
-            directives: directives_hash
+        let mut res = SystemdUnit {
+            directives: directives_hash,
+            unit_type: None,
+            suppressions: collect_suppressions(unit_items),
         };
+        res.unit_type = UnitType::from_categories(&res.categories());
         Ok(res)
     }
 
@@ -168,6 +302,23 @@ impl SystemdUnit {
         }
     }
 
+    /// Use the file name to know the unit type, falling back on the content when the
+    /// file name has no known suffix.
+    pub fn set_file_name(&mut self, path: &Path) {
+        if let Some(unit_type) = UnitType::from_path(path) {
+            self.unit_type = Some(unit_type);
+        }
+    }
+
+    pub fn unit_type(&self) -> Option<UnitType> {
+        self.unit_type
+    }
+
+    /// The `# systemd-lint: disable...` comments of the unit
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
+    }
+
     pub fn lookup_by_key(&self, key: &str) -> Option<&DirectiveEntry> {
         self.directives.get(key)
     }
@@ -189,8 +340,11 @@ impl SystemdUnit {
             .any(|directive| directive.category() == category)
     }
 
+    /// All the entries, sorted by key
     pub fn keys(&self) -> Vec<&DirectiveEntry> {
-        self.directives.values().collect()
+        let mut keys: Vec<&DirectiveEntry> = self.directives.values().collect();
+        keys.sort_by_key(|entry| entry.key());
+        keys
     }
 
     pub fn categories(&self) -> Vec<String> {
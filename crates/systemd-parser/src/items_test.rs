@@ -309,3 +309,87 @@ mod systemd_unit {
         }
     }
 }
+
+mod unit_type {
+    pub use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn it_should_be_found_from_the_file_name() {
+        assert_eq!(Some(UnitType::Service), UnitType::from_path(Path::new("/etc/systemd/system/sshd.service")));
+        assert_eq!(Some(UnitType::Automount), UnitType::from_path(Path::new("boot.automount")));
+        assert_eq!(Some(UnitType::Slice), UnitType::from_path(Path::new("-.slice")));
+        assert_eq!(Some(UnitType::DropIn), UnitType::from_path(Path::new("sshd.service.d/override.conf")));
+        assert_eq!(Some(UnitType::DropIn), UnitType::from_path(Path::new("/etc/systemd/system/service.d/limits.conf")));
+    }
+
+    #[test]
+    fn it_should_be_none_for_unknown_file_names() {
+        assert_eq!(None, UnitType::from_path(Path::new("README")));
+        assert_eq!(None, UnitType::from_path(Path::new("sshd.service.bak")));
+    }
+
+    #[test]
+    fn it_should_be_none_for_conf_files_out_of_a_drop_in_directory() {
+        assert_eq!(None, UnitType::from_path(Path::new("/etc/systemd/journald.conf")));
+        assert_eq!(None, UnitType::from_path(Path::new("/etc/systemd/journald.conf.d/storage.conf")));
+        assert_eq!(None, UnitType::from_path(Path::new("override.conf")));
+    }
+
+    #[test]
+    fn it_should_list_the_type_specific_sections() {
+        assert_eq!(
+            vec!["Service", "Socket", "Mount", "Automount", "Swap", "Timer", "Path", "Slice", "Scope"],
+            UnitType::categories()
+        );
+    }
+
+    #[test]
+    fn it_should_fallback_on_the_content() {
+        let input = vec![
+            Category("Unit"),
+            Directive("Description", Some("A dummy unit file")),
+            Category("Socket"),
+            Directive("ListenStream", Some("22")),
+        ];
+        let unit = SystemdUnit::new(&input).unwrap();
+
+        assert_eq!(Some(UnitType::Socket), unit.unit_type())
+    }
+
+    #[test]
+    fn it_should_be_none_when_the_content_is_ambiguous() {
+        let input = vec![
+            Category("Service"),
+            Directive("ExecStart", Some("/bin/true")),
+            Category("Socket"),
+            Directive("ListenStream", Some("22")),
+        ];
+        let unit = SystemdUnit::new(&input).unwrap();
+
+        assert_eq!(None, unit.unit_type())
+    }
+
+    #[test]
+    fn it_should_be_none_without_file_name_nor_type_section() {
+        let input = vec![
+            Category("Unit"),
+            Directive("Description", Some("A dummy unit file")),
+        ];
+        let unit = SystemdUnit::new(&input).unwrap();
+
+        assert_eq!(None, unit.unit_type())
+    }
+
+    #[test]
+    fn the_file_name_should_win_over_the_content() {
+        let input = vec![
+            Category("Service"),
+            Directive("Environment", Some("A=1")),
+        ];
+        let mut unit = SystemdUnit::new(&input).unwrap();
+        unit.set_file_name(Path::new("sshd.service.d/env.conf"));
+
+        assert_eq!(Some(UnitType::DropIn), unit.unit_type())
+    }
+}
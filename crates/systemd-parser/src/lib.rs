@@ -7,21 +7,58 @@ extern crate nom;
 #[macro_use]
 extern crate quick_error;
 
+pub mod condition;
+pub mod decode;
 pub mod errors;
+pub mod incremental;
 pub mod items;
 pub mod parser;
+pub mod socket_address;
+pub mod suppression;
+pub mod values;
+
+use std::path::Path;
 
 #[cfg(test)]
 mod parser_test;
 #[cfg(test)]
 mod items_test;
+#[cfg(test)]
+mod incremental_test;
+#[cfg(test)]
+mod decode_test;
+#[cfg(test)]
+mod socket_address_test;
+#[cfg(test)]
+mod condition_test;
+#[cfg(test)]
+mod suppression_test;
+#[cfg(test)]
+mod values_test;
 
 pub fn parse_string(input: &str) -> Result<items::SystemdUnit, errors::ParserError> {
 
     // FIXME: this should be inside `parse_unit` but then, the lifetime would be wrong
-    let input = String::from(input).replace("\\\n", "");
-    let units = try!(parser::parse_unit(&input));
-    let systemd_unit = try!(items::SystemdUnit::new(&units));
+    let joined = String::from(input).replace("\\\n", "");
+    let units = try!(parser::parse_unit(&joined));
+    let systemd_unit = try!(items::SystemdUnit::with_lines(&units, &parser::item_lines(input)));
     Ok(systemd_unit)
 }
 
+/// Parse the contents of the file at `path`, whose name tells the unit type.
+pub fn parse_unit_file(path: &Path, input: &str) -> Result<items::SystemdUnit, errors::ParserError> {
+
+    let mut systemd_unit = parse_string(input)?;
+    systemd_unit.set_file_name(path);
+    Ok(systemd_unit)
+}
+
+/// Parse raw bytes: decoding problems (invalid UTF-8, NUL bytes, lone carriage
+/// returns) do not prevent the parsing, they are returned alongside the unit.
+pub fn parse_bytes(input: &[u8])
+    -> Result<(items::SystemdUnit, Vec<decode::DecodeDiagnostic>), errors::ParserError> {
+
+    let decoded = decode::decode(input);
+    let systemd_unit = parse_string(decoded.text())?;
+    Ok((systemd_unit, decoded.diagnostics().to_vec()))
+}
@@ -14,7 +14,7 @@ fn c_is_value_element(c: char) -> bool {
 }
 fn c_is_key_element(c: char) -> bool {
     match c {
-        '!'|'|'|'@' => true,
+        '@' => true,
         c if c.is_alphabetic() => true,
         _ => false
     }
@@ -91,6 +91,33 @@ pub fn parse_unit(input: &str) -> Result<Vec<SystemdItem>, Vec<(IError<&str>, u3
     }
 }
 
+/// The line numbers of the items `parse_unit` returns for `input`, once its continuation
+/// lines are joined: the first line of each logical line which is not blank
+pub fn item_lines(input: &str) -> Vec<u32> {
+
+    let mut res = vec!();
+    let mut number = 0;
+    let mut start = 1;
+    let mut blank = true;
+    let mut lines = input.split('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        number += 1;
+        let continued = line.ends_with('\\') && lines.peek().is_some();
+        let content = if continued { &line[..line.len() - 1] } else { line };
+        blank = blank && content.trim().is_empty();
+        if !continued {
+            if !blank {
+                res.push(start);
+            }
+            start = number + 1;
+            blank = true;
+        }
+    }
+
+    res
+}
+
 fn enhance_with_line_numbers<'a>(errors: Vec<IError<&'a str>>, input: &str)
     -> Vec<(IError<&'a str>, u32)> {
 
@@ -124,8 +124,8 @@ mod parse_directive {
     #[test]
     fn it_should_accept_exotic_output() {
         let inputs = vec![
-            ("!ConditionPathIsMountPoint=/mnt/plop", "bang in keys"),
-            ("|ConditionPathIsMountPoint=/mnt/plop", "pipe in keys"),
+            ("ConditionPathIsMountPoint=!/mnt/plop", "bang in value"),
+            ("ConditionPathIsMountPoint=|/mnt/plop", "pipe in value"),
             ("Alias=foo.service.wants/bar.service", "Alias=foo.service.wants/bar.service"),
             ("ExecStart=-/bin/false", "- in value"),
             ("ExecStart=@/bin/echo", "@ in value"),
@@ -141,6 +141,20 @@ mod parse_directive {
             assert!(res.is_done(), "it should accept {}", msg)
         }
     }
+
+    #[test]
+    fn it_should_keep_the_condition_prefixes_in_the_value() {
+        let res = parse_directive("ConditionPathExists=|!/etc/foo");
+        let expected = SystemdItem::Directive("ConditionPathExists", Some("|!/etc/foo"));
+
+        assert_eq!(expected, res.unwrap().1)
+    }
+
+    #[test]
+    fn it_should_reject_condition_prefixes_in_the_key() {
+        assert!(parse_line("!ConditionPathExists=/etc/foo").is_err());
+        assert!(parse_line("|ConditionPathExists=/etc/foo").is_err())
+    }
 }
 
 mod parse_line {
@@ -262,3 +276,26 @@ mod parse_unit {
     }
 }
 
+mod item_lines {
+    pub use super::*;
+
+    #[test]
+    fn it_should_skip_the_blank_lines() {
+        let input = "# a comment\n\n[Unit]\n  \nDescription=plop\n";
+        assert_eq!(vec![1, 3, 5], item_lines(input))
+    }
+
+    #[test]
+    fn it_should_number_continued_lines_by_their_first_line() {
+        let input = "[Service]\nExecStart=/bin/echo \\\n  plop\nUser=nobody\r\n";
+        assert_eq!(vec![1, 2, 4], item_lines(input))
+    }
+
+    #[test]
+    fn it_should_give_a_line_to_each_item_of_parse_unit() {
+        let input = "[Unit]\n\\\n\nDescription=a \\\nb\n\n[Service]\nType=simple\\";
+        let joined = input.replace("\\\n", "");
+        let items = parse_unit(&joined).unwrap();
+        assert_eq!(items.len(), item_lines(input).len())
+    }
+}
@@ -0,0 +1,159 @@
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Maximum length of a unix socket path, the size of `sun_path` minus the final NUL
+pub const UNIX_PATH_MAX: usize = 107;
+
+/// Netlink families known by systemd for `ListenNetlink=`
+pub static NETLINK_FAMILIES: &[&str] = &[
+    "route", "firewall", "inet-diag", "nflog", "xfrm", "selinux", "iscsi", "audit",
+    "fib-lookup", "connector", "netfilter", "ip6-fw", "dnrtmsg", "kobject-uevent",
+    "generic", "scsitransport", "ecryptfs", "rdma", "crypto",
+];
+
+/// An address as found in `ListenStream=`, `ListenDatagram=`, `ListenSequentialPacket=`
+/// or `ListenNetlink=`.
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub enum SocketAddress {
+    /// `80`: the port on all the addresses
+    Port(u16),
+    /// `127.0.0.1:80`, `[::1]:80` or `[fe80::1%eth0]:80`
+    Inet { ip: IpAddr, scope: Option<String>, port: u16 },
+    /// `/run/foo.sock`
+    Unix(String),
+    /// `@foo`, the name is stored without the leading `@`
+    Abstract(String),
+    /// `vsock:2:1234`, the CID may be omitted (`vsock::1234`) to listen on any CID
+    Vsock { cid: Option<u32>, port: u32 },
+    /// `kobject-uevent 1`
+    Netlink { family: String, group: u32 },
+}
+
+impl SocketAddress {
+
+    /// Parse the value of `ListenStream=`, `ListenDatagram=` or `ListenSequentialPacket=`
+    pub fn parse(input: &str) -> Result<SocketAddress, String> {
+
+        let input = input.trim();
+
+        if input.is_empty() {
+            Err("empty socket address".into())
+        } else if input.starts_with('/') || input.starts_with('%') {
+            // a path, maybe starting with a specifier such as %t
+            check_unix_path_len(input).map(|_| SocketAddress::Unix(input.into()))
+        } else if let Some(name) = input.strip_prefix('@') {
+            // the leading `@` stands for the NUL byte, which takes the place of the final one
+            check_unix_path_len(name).map(|_| SocketAddress::Abstract(name.into()))
+        } else if let Some(rest) = input.strip_prefix("vsock:") {
+            parse_vsock(rest)
+        } else if input.starts_with('[') {
+            parse_ipv6(input)
+        } else if input.bytes().all(|b| b.is_ascii_digit()) {
+            parse_port(input).map(SocketAddress::Port)
+        } else {
+            parse_ipv4(input)
+        }
+    }
+
+    /// Parse the value of `ListenNetlink=`: a family and an optional multicast group
+    pub fn parse_netlink(input: &str) -> Result<SocketAddress, String> {
+
+        let mut words = input.split_whitespace();
+        let family = words.next().ok_or_else(|| String::from("empty netlink address"))?;
+
+        if !NETLINK_FAMILIES.contains(&family) {
+            return Err(format!("unknown netlink family: {}", family));
+        }
+
+        let group = match words.next() {
+            None => 0,
+            Some(group) => group.parse().map_err(|_| format!("invalid netlink group: {}", group))?,
+        };
+
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected trailing value in netlink address: {}", extra));
+        }
+
+        Ok(SocketAddress::Netlink { family: family.into(), group })
+    }
+
+    /// Two addresses of the same socket type which can't be bound at the same time
+    pub fn conflicts_with(&self, other: &SocketAddress) -> bool {
+        use self::SocketAddress::*;
+
+        match (self, other) {
+            (&Port(port), &Port(other_port)) => port == other_port,
+            (&Port(port), &Inet { port: other_port, .. }) |
+            (&Inet { port, .. }, &Port(other_port)) => port == other_port,
+            (&Inet { ip, port, .. }, &Inet { ip: other_ip, port: other_port, .. }) => {
+                port == other_port && (ip == other_ip || ip.is_unspecified() || other_ip.is_unspecified())
+            }
+            (&Vsock { cid, port }, &Vsock { cid: other_cid, port: other_port }) => {
+                port == other_port && (cid == other_cid || cid.is_none() || other_cid.is_none())
+            }
+            _ => self == other,
+        }
+    }
+}
+
+fn check_unix_path_len(path: &str) -> Result<(), String> {
+    if path.len() > UNIX_PATH_MAX {
+        Err(format!("socket path is longer than {} bytes: {}", UNIX_PATH_MAX, path))
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_port(input: &str) -> Result<u16, String> {
+    match input.parse::<u16>() {
+        Ok(0) | Err(_) => Err(format!("invalid port number: {}", input)),
+        Ok(port) => Ok(port),
+    }
+}
+
+fn parse_vsock(input: &str) -> Result<SocketAddress, String> {
+
+    let (cid, port) = split_port(input, "vsock")?;
+
+    let cid = if cid.is_empty() {
+        None
+    } else {
+        Some(cid.parse().map_err(|_| format!("invalid vsock CID: {}", cid))?)
+    };
+    let port = port.parse().map_err(|_| format!("invalid vsock port: {}", port))?;
+
+    Ok(SocketAddress::Vsock { cid, port })
+}
+
+fn parse_ipv6(input: &str) -> Result<SocketAddress, String> {
+
+    let close = input.find(']').ok_or_else(|| format!("missing ] in IPv6 address: {}", input))?;
+    let (host, scope) = match input[1..close].find('%') {
+        Some(idx) => (&input[1..1 + idx], Some(String::from(&input[2 + idx..close]))),
+        None => (&input[1..close], None),
+    };
+
+    let ip: Ipv6Addr = host.parse().map_err(|_| format!("invalid IPv6 address: {}", host))?;
+    let port = match input[close + 1..].strip_prefix(':') {
+        Some(port) => parse_port(port)?,
+        None => return Err(format!("missing port after IPv6 address: {}", input)),
+    };
+
+    Ok(SocketAddress::Inet { ip: IpAddr::V6(ip), scope, port })
+}
+
+fn parse_ipv4(input: &str) -> Result<SocketAddress, String> {
+
+    let (host, port) = split_port(input, "IPv4")?;
+    let ip: Ipv4Addr = host.parse().map_err(|_| format!("invalid IPv4 address: {}", host))?;
+    let port = parse_port(port)?;
+
+    Ok(SocketAddress::Inet { ip: IpAddr::V4(ip), scope: None, port })
+}
+
+fn split_port<'a>(input: &'a str, what: &str) -> Result<(&'a str, &'a str), String> {
+    match input.rfind(':') {
+        Some(idx) => Ok((&input[..idx], &input[idx + 1..])),
+        None => Err(format!("missing port in {} address: {}", what, input)),
+    }
+}
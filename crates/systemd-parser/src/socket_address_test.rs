@@ -0,0 +1,160 @@
+
+pub use socket_address::*;
+pub use socket_address::SocketAddress::*;
+pub use std::net::IpAddr;
+
+fn ip(input: &str) -> IpAddr {
+    input.parse().unwrap()
+}
+
+mod parse {
+    pub use super::*;
+
+    #[test]
+    fn it_should_parse_port_numbers() {
+        assert_eq!(Ok(Port(22)), SocketAddress::parse("22"))
+    }
+
+    #[test]
+    fn it_should_reject_out_of_range_ports() {
+        assert!(SocketAddress::parse("0").is_err());
+        assert!(SocketAddress::parse("65536").is_err());
+    }
+
+    #[test]
+    fn it_should_parse_ipv4_addresses() {
+        let expected = Inet { ip: ip("127.0.0.1"), scope: None, port: 8080 };
+        assert_eq!(Ok(expected), SocketAddress::parse("127.0.0.1:8080"))
+    }
+
+    #[test]
+    fn it_should_reject_ipv4_without_port() {
+        assert!(SocketAddress::parse("127.0.0.1").is_err())
+    }
+
+    #[test]
+    fn it_should_reject_host_names() {
+        assert!(SocketAddress::parse("localhost:80").is_err())
+    }
+
+    #[test]
+    fn it_should_parse_ipv6_addresses() {
+        let expected = Inet { ip: ip("::1"), scope: None, port: 443 };
+        assert_eq!(Ok(expected), SocketAddress::parse("[::1]:443"))
+    }
+
+    #[test]
+    fn it_should_parse_ipv6_scopes() {
+        let expected = Inet { ip: ip("fe80::1"), scope: Some("eth0".into()), port: 443 };
+        assert_eq!(Ok(expected), SocketAddress::parse("[fe80::1%eth0]:443"))
+    }
+
+    #[test]
+    fn it_should_reject_ipv6_without_brackets_or_port() {
+        assert!(SocketAddress::parse("::1:443").is_err());
+        assert!(SocketAddress::parse("[::1]").is_err());
+        assert!(SocketAddress::parse("[::1:443").is_err());
+    }
+
+    #[test]
+    fn it_should_parse_unix_paths() {
+        assert_eq!(Ok(Unix("/run/docker.sock".into())), SocketAddress::parse("/run/docker.sock"));
+        assert_eq!(Ok(Unix("%t/foo.sock".into())), SocketAddress::parse("%t/foo.sock"));
+    }
+
+    #[test]
+    fn it_should_reject_too_long_unix_paths() {
+        let input = format!("/run/{}", "a".repeat(UNIX_PATH_MAX));
+        assert!(SocketAddress::parse(&input).is_err())
+    }
+
+    #[test]
+    fn it_should_accept_unix_paths_of_the_maximum_length() {
+        let input = format!("/{}", "a".repeat(UNIX_PATH_MAX - 1));
+        assert_eq!(Ok(Unix(input.clone())), SocketAddress::parse(&input))
+    }
+
+    #[test]
+    fn it_should_parse_abstract_sockets() {
+        assert_eq!(Ok(Abstract("/org/kernel/linux/storage/multipathd".into())),
+                   SocketAddress::parse("@/org/kernel/linux/storage/multipathd"))
+    }
+
+    #[test]
+    fn it_should_not_count_the_at_sign_of_abstract_sockets() {
+        let name = "a".repeat(UNIX_PATH_MAX);
+        assert_eq!(Ok(Abstract(name.clone())), SocketAddress::parse(&format!("@{}", name)));
+        assert!(SocketAddress::parse(&format!("@{}a", name)).is_err())
+    }
+
+    #[test]
+    fn it_should_parse_vsock_addresses() {
+        assert_eq!(Ok(Vsock { cid: Some(2), port: 1234 }), SocketAddress::parse("vsock:2:1234"));
+        assert_eq!(Ok(Vsock { cid: None, port: 1234 }), SocketAddress::parse("vsock::1234"));
+        assert!(SocketAddress::parse("vsock:2").is_err());
+        assert!(SocketAddress::parse("vsock:x:1").is_err());
+    }
+
+    #[test]
+    fn it_should_reject_empty_addresses() {
+        assert!(SocketAddress::parse(" ").is_err())
+    }
+}
+
+mod parse_netlink {
+    pub use super::*;
+
+    #[test]
+    fn it_should_parse_family_and_group() {
+        let expected = Netlink { family: "kobject-uevent".into(), group: 1 };
+        assert_eq!(Ok(expected), SocketAddress::parse_netlink("kobject-uevent 1"))
+    }
+
+    #[test]
+    fn it_should_default_to_group_zero() {
+        let expected = Netlink { family: "audit".into(), group: 0 };
+        assert_eq!(Ok(expected), SocketAddress::parse_netlink("audit"))
+    }
+
+    #[test]
+    fn it_should_reject_unknown_families_and_bad_groups() {
+        assert!(SocketAddress::parse_netlink("plop 1").is_err());
+        assert!(SocketAddress::parse_netlink("route x").is_err());
+        assert!(SocketAddress::parse_netlink("route 1 2").is_err());
+    }
+}
+
+mod conflicts_with {
+    pub use super::*;
+
+    fn conflicts(a: &str, b: &str) -> bool {
+        SocketAddress::parse(a).unwrap().conflicts_with(&SocketAddress::parse(b).unwrap())
+    }
+
+    #[test]
+    fn it_should_detect_same_addresses() {
+        assert!(conflicts("22", "22"));
+        assert!(conflicts("/run/a.sock", "/run/a.sock"));
+        assert!(conflicts("@a", "@a"));
+    }
+
+    #[test]
+    fn it_should_detect_a_port_against_any_address() {
+        assert!(conflicts("22", "127.0.0.1:22"));
+        assert!(conflicts("[::1]:22", "22"));
+    }
+
+    #[test]
+    fn it_should_detect_unspecified_addresses() {
+        assert!(conflicts("0.0.0.0:80", "10.0.0.1:80"));
+        assert!(conflicts("vsock::80", "vsock:3:80"));
+    }
+
+    #[test]
+    fn it_should_accept_different_addresses() {
+        assert!(!conflicts("22", "23"));
+        assert!(!conflicts("10.0.0.1:80", "10.0.0.2:80"));
+        assert!(!conflicts("/run/a.sock", "@/run/a.sock"));
+        assert!(!conflicts("vsock:2:80", "vsock:3:80"));
+    }
+}
@@ -0,0 +1,111 @@
+
+use items::SystemdItem;
+
+static PREFIX: &str = "systemd-lint:";
+
+/// What a suppression comment applies to
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SuppressionScope {
+    /// `disable-file=`: the whole file
+    File,
+    /// `disable-section=`: the category the comment is in, or the first category
+    /// when the comment comes before it
+    Section(String),
+    /// `disable=`: the next directive, `None` when no directive follows the comment
+    /// in its category
+    Directive(Option<(String, String)>),
+}
+
+/// A `# systemd-lint: disable=ErrorUnknownDirective` comment. The lints are kept
+/// as written: they may be given by name or code.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Suppression {
+    pub scope: SuppressionScope,
+    pub lints: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Kind {
+    File,
+    Section,
+    Directive,
+}
+
+/// Parse the text of a comment, without its `#`
+fn parse_comment(comment: &str) -> Option<(Kind, Vec<String>)> {
+
+    let comment = comment.trim();
+    if !comment.starts_with(PREFIX) {
+        return None;
+    }
+
+    let mut parts = comment[PREFIX.len()..].splitn(2, '=');
+    let kind = match parts.next().map(|kind| kind.trim()) {
+        Some("disable") => Kind::Directive,
+        Some("disable-section") => Kind::Section,
+        Some("disable-file") => Kind::File,
+        _ => return None,
+    };
+
+    let lints: Vec<String> = parts.next()
+        .unwrap_or("")
+        .split(',')
+        .map(|lint| lint.trim())
+        .filter(|lint| !lint.is_empty())
+        .map(String::from)
+        .collect();
+
+    if lints.is_empty() {
+        None
+    } else {
+        Some((kind, lints))
+    }
+}
+
+/// Find the suppression comments of a unit and what they apply to
+pub fn collect_suppressions(unit_items: &[SystemdItem]) -> Vec<Suppression> {
+
+    use self::SystemdItem::*;
+
+    let first_category = unit_items.iter().filter_map(|item| match *item {
+        Category(cat) => Some(cat),
+        _ => None,
+    }).next();
+
+    let mut res = vec!();
+    let mut category = first_category;
+    // `disable=` comments waiting for their directive
+    let mut pending: Vec<Vec<String>> = vec!();
+
+    for item in unit_items {
+        match *item {
+            Comment(comment) => match parse_comment(comment) {
+                Some((Kind::File, lints)) => res.push(Suppression { scope: SuppressionScope::File, lints }),
+                Some((Kind::Section, lints)) => {
+                    if let Some(cat) = category {
+                        res.push(Suppression { scope: SuppressionScope::Section(cat.into()), lints });
+                    }
+                }
+                Some((Kind::Directive, lints)) => pending.push(lints),
+                None => (),
+            },
+            Category(cat) => {
+                flush(&mut res, &mut pending, None);
+                category = Some(cat);
+            }
+            Directive(key, _) => {
+                let target = category.map(|cat| (String::from(cat), String::from(key)));
+                flush(&mut res, &mut pending, target);
+            }
+        }
+    }
+    flush(&mut res, &mut pending, None);
+
+    res
+}
+
+fn flush(res: &mut Vec<Suppression>, pending: &mut Vec<Vec<String>>, target: Option<(String, String)>) {
+    for lints in pending.drain(..) {
+        res.push(Suppression { scope: SuppressionScope::Directive(target.clone()), lints });
+    }
+}
@@ -0,0 +1,112 @@
+
+pub use suppression::*;
+use parser::parse_unit;
+
+fn suppressions_of(input: &str) -> Vec<Suppression> {
+    collect_suppressions(&parse_unit(input).unwrap())
+}
+
+fn directive(category: &str, key: &str) -> SuppressionScope {
+    SuppressionScope::Directive(Some((category.into(), key.into())))
+}
+
+#[test]
+fn it_should_ignore_plain_comments() {
+    let input = "
+        # systemd-lint is nice
+        [Unit]
+        # disable=ErrorUnknownDirective
+        Description=foo
+    ";
+
+    assert!(suppressions_of(input).is_empty())
+}
+
+#[test]
+fn disable_should_apply_to_the_next_directive() {
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        # systemd-lint: disable=ErrorUnknownDirective
+        # some explanation
+        ExecStrat=/bin/false
+    ";
+
+    assert_eq!(vec![Suppression {
+        scope: directive("Service", "ExecStrat"),
+        lints: vec!["ErrorUnknownDirective".into()],
+    }], suppressions_of(input))
+}
+
+#[test]
+fn it_should_read_a_list_of_lints() {
+    let input = "
+        [Service]
+        # systemd-lint: disable = unknown-directive, 40004
+        ExecStrat=/bin/false
+    ";
+
+    assert_eq!(vec!["unknown-directive", "40004"], suppressions_of(input)[0].lints)
+}
+
+#[test]
+fn disable_should_apply_to_nothing_when_a_category_follows() {
+    let input = "
+        [Unit]
+        Description=foo
+        # systemd-lint: disable=unknown-directive
+        [Service]
+        ExecStrat=/bin/false
+    ";
+
+    assert_eq!(SuppressionScope::Directive(None), suppressions_of(input)[0].scope)
+}
+
+#[test]
+fn disable_section_should_apply_to_the_current_category() {
+    let input = "
+        [Unit]
+        Description=foo
+        [Internal]
+        # systemd-lint: disable-section=unknown-directive
+        Owner=infra
+    ";
+
+    assert_eq!(SuppressionScope::Section("Internal".into()), suppressions_of(input)[0].scope)
+}
+
+#[test]
+fn disable_section_should_apply_to_the_first_category_when_before_it() {
+    let input = "
+        # systemd-lint: disable-section=missing-description
+        [Unit]
+        Wants=foo.service
+    ";
+
+    assert_eq!(SuppressionScope::Section("Unit".into()), suppressions_of(input)[0].scope)
+}
+
+#[test]
+fn disable_file_should_apply_to_the_whole_file() {
+    let input = "
+        [Unit]
+        Description=foo
+        # systemd-lint: disable-file=unknown-directive,unknown-category
+    ";
+
+    assert_eq!(vec![Suppression {
+        scope: SuppressionScope::File,
+        lints: vec!["unknown-directive".into(), "unknown-category".into()],
+    }], suppressions_of(input))
+}
+
+#[test]
+fn it_should_ignore_suppressions_without_lints() {
+    let input = "
+        [Unit]
+        # systemd-lint: disable=
+        Description=foo
+    ";
+
+    assert!(suppressions_of(input).is_empty())
+}
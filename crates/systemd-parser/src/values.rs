@@ -0,0 +1,166 @@
+
+use items::UnitType;
+use std::path::Path;
+
+/// The spellings of a boolean accepted by systemd, whatever their case
+pub static TRUE_VALUES: &[&str] = &["1", "yes", "y", "true", "t", "on"];
+pub static FALSE_VALUES: &[&str] = &["0", "no", "n", "false", "f", "off"];
+
+/// Time span units, with their length in microseconds, as listed in systemd.time(7)
+static TIME_UNITS: &[(&str, u64)] = &[
+    ("usec", 1), ("us", 1), ("µs", 1), ("μs", 1),
+    ("msec", 1_000), ("ms", 1_000),
+    ("seconds", SEC), ("second", SEC), ("sec", SEC), ("s", SEC),
+    ("minutes", 60 * SEC), ("minute", 60 * SEC), ("min", 60 * SEC), ("m", 60 * SEC),
+    ("hours", HOUR), ("hour", HOUR), ("hr", HOUR), ("h", HOUR),
+    ("days", DAY), ("day", DAY), ("d", DAY),
+    ("weeks", 7 * DAY), ("week", 7 * DAY), ("w", 7 * DAY),
+    ("months", MONTH), ("month", MONTH), ("M", MONTH),
+    ("years", YEAR), ("year", YEAR), ("y", YEAR),
+];
+
+const SEC: u64 = 1_000_000;
+const HOUR: u64 = 3600 * SEC;
+const DAY: u64 = 24 * HOUR;
+const MONTH: u64 = 2_629_800 * SEC;
+const YEAR: u64 = 31_557_600 * SEC;
+
+/// Unit types systemd does not support anymore, which still appear in unit names
+static LEGACY_UNIT_SUFFIXES: &[&str] = &["busname", "snapshot"];
+
+/// Size suffixes, in powers of 1024
+static SIZE_SUFFIXES: &[&str] = &["K", "M", "G", "T", "P", "E"];
+
+/// Parse a boolean, e.g. `PrivateTmp=yes`
+pub fn parse_boolean(input: &str) -> Result<bool, String> {
+
+    let lowercase = input.trim().to_lowercase();
+
+    if TRUE_VALUES.contains(&&lowercase[..]) {
+        Ok(true)
+    } else if FALSE_VALUES.contains(&&lowercase[..]) {
+        Ok(false)
+    } else {
+        Err(format!("\"{}\" is not a boolean", input.trim()))
+    }
+}
+
+/// Parse a time span in microseconds, e.g. `RestartSec=1min 30s`. A number without
+/// unit is in seconds. `None` stands for `infinity`.
+pub fn parse_time_span(input: &str) -> Result<Option<u64>, String> {
+
+    let input = input.trim();
+    if input == "infinity" {
+        return Ok(None);
+    }
+    if input.is_empty() {
+        return Err("empty time span".into());
+    }
+
+    let mut total = 0f64;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (number, after_number) = split_number(rest)
+            .ok_or_else(|| format!("expected a number at \"{}\"", rest))?;
+
+        let after_number = after_number.trim_start();
+        let unit_len = after_number.find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+            .unwrap_or(after_number.len());
+        let (unit, after_unit) = after_number.split_at(unit_len);
+
+        let multiplier = match unit {
+            "" => SEC,
+            _ => TIME_UNITS
+                .iter()
+                .find(|&&(name, _)| name == unit)
+                .map(|&(_, multiplier)| multiplier)
+                .ok_or_else(|| format!("unknown time unit \"{}\"", unit))?,
+        };
+
+        total += number * multiplier as f64;
+        rest = after_unit.trim_start();
+    }
+
+    Ok(Some(total as u64))
+}
+
+/// A size, as in `MemoryMax=` or `TasksMax=`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Size {
+    /// In bytes, or in units for `TasksMax=`
+    Absolute(u64),
+    /// Of the available amount, e.g. `50%`
+    Percentage(f64),
+    Infinity,
+}
+
+/// Parse a size: a number with an optional B, K, M, G, T, P or E suffix (powers of
+/// 1024), a percentage or `infinity`. Like time spans, sizes may be written in
+/// several parts which add up, e.g. `1G 512M`.
+pub fn parse_size(input: &str) -> Result<Size, String> {
+
+    let input = input.trim();
+    if input == "infinity" {
+        return Ok(Size::Infinity);
+    }
+    if input.is_empty() {
+        return Err("empty size".into());
+    }
+    if let Some((number, "%")) = split_number(input) {
+        return Ok(Size::Percentage(number));
+    }
+
+    let mut total = 0f64;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (number, after_number) = split_number(rest)
+            .ok_or_else(|| format!("\"{}\" is not a size", input))?;
+
+        let after_number = after_number.trim_start();
+        let suffix_len = after_number.find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+            .unwrap_or(after_number.len());
+        let (suffix, after_suffix) = after_number.split_at(suffix_len);
+
+        let multiplier = match suffix {
+            "" | "B" => 1,
+            _ => SIZE_SUFFIXES
+                .iter()
+                .position(|&known| known == suffix)
+                .map(|exponent| 1024u64.pow(exponent as u32 + 1))
+                .ok_or_else(|| format!("unknown size suffix \"{}\", expected B, K, M, G, T, P or E", suffix))?,
+        };
+
+        total += number * multiplier as f64;
+        rest = after_suffix.trim_start();
+    }
+
+    Ok(Size::Absolute(total as u64))
+}
+
+/// Tells if the name is a valid unit name, such as `foo.service`, `getty@tty1.service`,
+/// `getty@.service` or `foo@%i.service`
+pub fn is_unit_name(name: &str) -> bool {
+
+    let (prefix, suffix) = match name.rfind('.') {
+        Some(idx) => (&name[..idx], &name[idx + 1..]),
+        None => return false,
+    };
+
+    let known_suffix = match UnitType::from_path(Path::new(name)) {
+        Some(UnitType::DropIn) => false,
+        Some(_) => true,
+        None => LEGACY_UNIT_SUFFIXES.contains(&suffix),
+    };
+    let valid_chars = prefix.chars().all(|c| c.is_ascii_alphanumeric() || ":-_.\\@%".contains(c));
+
+    known_suffix && !prefix.is_empty() && valid_chars
+}
+
+/// The leading number of the input, possibly with a fractional part
+fn split_number(input: &str) -> Option<(f64, &str)> {
+
+    let len = input.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(input.len());
+    let (number, rest) = input.split_at(len);
+
+    number.parse().ok().map(|number| (number, rest))
+}
@@ -0,0 +1,108 @@
+
+pub use values::*;
+
+mod parse_boolean {
+    pub use super::*;
+
+    #[test]
+    fn it_should_accept_every_spelling_whatever_the_case() {
+        for input in &["1", "yes", "y", "true", "t", "on", "Yes", "TRUE"] {
+            assert_eq!(Ok(true), parse_boolean(input), "{}", input);
+        }
+        for input in &["0", "no", "n", "false", "f", "off", "No"] {
+            assert_eq!(Ok(false), parse_boolean(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn it_should_reject_other_words() {
+        assert!(parse_boolean("maybe").is_err());
+        assert!(parse_boolean("").is_err());
+    }
+}
+
+mod parse_time_span {
+    pub use super::*;
+
+    #[test]
+    fn it_should_read_seconds_without_unit() {
+        assert_eq!(Ok(Some(5_000_000)), parse_time_span("5"))
+    }
+
+    #[test]
+    fn it_should_add_the_components() {
+        assert_eq!(Ok(Some(90_000_000)), parse_time_span("1min 30s"));
+        assert_eq!(Ok(Some(90_000_000)), parse_time_span("1min30s"));
+        assert_eq!(Ok(Some(300_000_000)), parse_time_span("5 minutes"));
+        assert_eq!(Ok(Some(1_500)), parse_time_span("1.5ms"));
+    }
+
+    #[test]
+    fn it_should_read_infinity() {
+        assert_eq!(Ok(None), parse_time_span("infinity"))
+    }
+
+    #[test]
+    fn it_should_reject_unknown_units() {
+        assert_eq!(Err("unknown time unit \"minuts\"".into()), parse_time_span("5 minuts"));
+        assert!(parse_time_span("5mins").is_err());
+    }
+
+    #[test]
+    fn it_should_reject_missing_numbers() {
+        assert!(parse_time_span("").is_err());
+        assert!(parse_time_span("s").is_err());
+        assert!(parse_time_span("forever").is_err());
+    }
+}
+
+mod parse_size {
+    pub use super::*;
+
+    #[test]
+    fn it_should_read_the_suffixes() {
+        assert_eq!(Ok(Size::Absolute(512)), parse_size("512"));
+        assert_eq!(Ok(Size::Absolute(1536)), parse_size("1.5K"));
+        assert_eq!(Ok(Size::Absolute(1 << 30)), parse_size("1G"));
+        assert_eq!(Ok(Size::Absolute(8192)), parse_size("8192B"));
+    }
+
+    #[test]
+    fn it_should_add_up_the_parts() {
+        assert_eq!(Ok(Size::Absolute((1 << 30) + (512 << 20))), parse_size("1G 512M"));
+        assert_eq!(Ok(Size::Absolute(1536)), parse_size("1K 512 B"));
+    }
+
+    #[test]
+    fn it_should_read_percentages_and_infinity() {
+        assert_eq!(Ok(Size::Percentage(50.0)), parse_size("50%"));
+        assert_eq!(Ok(Size::Infinity), parse_size("infinity"));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_suffixes() {
+        assert!(parse_size("1GB").is_err());
+        assert!(parse_size("1g").is_err());
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("").is_err());
+        assert!(parse_size("1G lots").is_err());
+    }
+}
+
+mod is_unit_name {
+    pub use super::*;
+
+    #[test]
+    fn it_should_accept_unit_names() {
+        for name in &["foo.service", "network-online.target", "getty@tty1.service", "getty@.service", "foo@%i.service", "dev-sda1.device", "org.freedesktop.network1.busname"] {
+            assert!(is_unit_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn it_should_reject_other_names() {
+        for name in &["network", "foo.conf", ".service", "foo bar.service", "foo.services"] {
+            assert!(!is_unit_name(name), "{}", name);
+        }
+    }
+}
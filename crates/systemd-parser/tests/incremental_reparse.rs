@@ -0,0 +1,74 @@
+extern crate systemd_parser;
+
+use std::fs;
+use std::io::Read;
+use systemd_parser::incremental::{Document, TextEdit};
+
+// tiny xorshift generator, so that failures are reproducible from the seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % (max as u64 + 1)) as usize
+    }
+}
+
+static SNIPPETS: &[&str] =
+    &["", "\n", "\\", "\\\n", "\r\n", "=", "[", "]", "# ", " ", "\t", "Type", "Unit", "x", "[Service]\n"];
+
+fn random_edit(rng: &mut Rng, source: &str) -> TextEdit {
+    let mut start = rng.below(source.len());
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = start + rng.below(::std::cmp::min(12, source.len() - start));
+    while !source.is_char_boundary(end) {
+        end += 1;
+    }
+    let replacement = SNIPPETS[rng.below(SNIPPETS.len() - 1)];
+
+    TextEdit::new(start..end, replacement)
+}
+
+#[test]
+fn it_should_be_equivalent_to_a_full_parse_after_random_edits() {
+
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    let entries = fs::read_dir("./tests/success_units/").expect("directory exists");
+    for entry in entries {
+        let path = entry.expect("entry is ok").path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let mut buffer = String::with_capacity(4096);
+        fs::File::open(&path).expect("file must be open-able")
+            .read_to_string(&mut buffer).expect("file must be readable");
+
+        let mut doc = Document::parse(&buffer);
+        for _ in 0..30 {
+            let edit = random_edit(&mut rng, doc.source());
+            doc.apply_edit(&edit).expect("random edits are within the source");
+
+            let expected = systemd_parser::parse_string(doc.source());
+            let fresh = Document::parse(doc.source());
+            assert_eq!(fresh, doc, "{:?} after {:?}", path, edit);
+
+            match (expected, doc.unit()) {
+                (Ok(expected), Ok(res)) => assert_eq!(expected, res, "{:?} after {:?}", path, edit),
+                (Err(_), Err(_)) => (),
+                (expected, res) => {
+                    panic!("{:?} after {:?}: expected {:?}, got {:?}", path, edit, expected, res)
+                }
+            }
+        }
+    }
+}
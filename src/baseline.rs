@@ -0,0 +1,324 @@
+use lint::LintResult;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use toml;
+
+/// The findings accepted when the linter was turned on, written by `--write-baseline`
+/// and read by `--baseline`:
+///
+/// ```toml
+/// [[finding]]
+/// file = "units/foo.service"
+/// code = 20000
+/// fingerprint = "b0f3a4f4c4d7a1e2"
+/// message = "Consider filling the Description= field"
+/// ```
+///
+/// The files are relative to the directory of the baseline. The findings of the
+/// custom rules and of the plugins have a `rule` too, e.g. `rule = "house-rules/x-owner"`. The fingerprint
+/// hashes the lint code, the rule, the location (category and key) and the message,
+/// so moving a directive around does not make its finding new. The message is only
+/// there for the reviewers.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Baseline {
+    entries: Vec<Entry>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Entry {
+    pub file: String,
+    pub code: u32,
+    pub rule: Option<String>,
+    pub fingerprint: String,
+    pub message: String,
+}
+
+/// The findings of a file, split against the baseline
+pub struct Filtered<'a> {
+    /// Findings which are not in the baseline
+    pub new: Vec<LintResult>,
+    /// Entries of the baseline which are not found anymore
+    pub resolved: Vec<&'a Entry>,
+}
+
+impl Baseline {
+
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        Baseline::parse(&contents)
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(input: &str) -> Result<Baseline, String> {
+
+        let mut parser = toml::Parser::new(input);
+        let table = match parser.parse() {
+            Some(table) => table,
+            None => {
+                let err = &parser.errors[0];
+                let (line, col) = parser.to_linecol(err.lo);
+                return Err(format!("line {}, column {}: {}", line + 1, col + 1, err.desc));
+            }
+        };
+
+        let mut entries = vec!();
+        for (key, value) in &table {
+            match &key[..] {
+                "finding" => {
+                    let findings = value.as_slice().ok_or("finding should be an array of tables")?;
+                    for finding in findings {
+                        entries.push(parse_entry(finding)?);
+                    }
+                }
+                _ => return Err(format!("unknown key: {}", key)),
+            }
+        }
+
+        Ok(Baseline { entries })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        File::create(path)
+            .and_then(|mut file| file.write_all(self.to_toml().as_bytes()))
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Sorted by file, so that the baseline diffs well
+    pub fn to_toml(&self) -> String {
+
+        let mut out = String::new();
+        for entry in &self.entries {
+            out.push_str("[[finding]]\n");
+            out.push_str(&format!("file = {}\n", toml::Value::String(entry.file.clone())));
+            out.push_str(&format!("code = {}\n", entry.code));
+            if let Some(ref rule) = entry.rule {
+                out.push_str(&format!("rule = {}\n", toml::Value::String(rule.clone())));
+            }
+            out.push_str(&format!("fingerprint = {}\n", toml::Value::String(entry.fingerprint.clone())));
+            out.push_str(&format!("message = {}\n\n", toml::Value::String(entry.message.clone())));
+        }
+        out
+    }
+
+    /// Replace the entries of `file` by its current findings
+    pub fn record(&mut self, file: &str, findings: &[LintResult]) {
+
+        self.entries.retain(|entry| entry.file != file);
+        self.entries.extend(findings.iter().map(|finding| Entry {
+            file: file.into(),
+            code: finding.code() as u32,
+            rule: finding.rule().map(String::from),
+            fingerprint: fingerprint(finding),
+            message: finding.message().into(),
+        }));
+        self.entries.sort_by(|a, b| (&a.file, a.code, &a.fingerprint).cmp(&(&b.file, b.code, &b.fingerprint)));
+    }
+
+    /// Each entry of the baseline grandfathers one finding of `file`, so that a
+    /// finding which appears once more is still reported
+    pub fn filter(&self, file: &str, findings: Vec<LintResult>) -> Filtered<'_> {
+
+        let mut remaining: Vec<&Entry> = self.entries.iter().filter(|entry| entry.file == file).collect();
+        let mut new = vec!();
+
+        for finding in findings {
+            let code = finding.code() as u32;
+            let fingerprint = fingerprint(&finding);
+            match remaining.iter().position(|entry| entry.code == code && entry.fingerprint == fingerprint) {
+                Some(idx) => { remaining.remove(idx); }
+                None => new.push(finding),
+            }
+        }
+
+        Filtered { new, resolved: remaining }
+    }
+}
+
+/// How the baseline refers to the unit: relative to the directory of the baseline
+/// when possible, with forward slashes whatever the platform
+pub fn file_key(baseline: &Path, unit: &Path) -> String {
+
+    let root = baseline.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+    let relative = match (root.canonicalize(), unit.canonicalize()) {
+        (Ok(root), Ok(unit)) => unit.strip_prefix(&root).map(|relative| relative.to_path_buf()).ok(),
+        _ => None,
+    };
+
+    let path = relative.unwrap_or_else(|| unit.to_path_buf());
+    let path = path.to_string_lossy();
+    // elsewhere, backslashes are escapes in unit names, e.g. dev-disk-by\x2dlabel.device
+    if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.into_owned()
+    }
+}
+
+/// The fingerprints of the findings of the built-in lints, which have no rule, are
+/// the ones of the baselines written before the rules
+fn fingerprint(finding: &LintResult) -> String {
+    let code = match finding.rule() {
+        Some(rule) => format!("{} {}", finding.code() as u32, rule),
+        None => (finding.code() as u32).to_string(),
+    };
+    let input = format!("{}\n{}\n{}", code, finding.location(), finding.message());
+    hash(input.as_bytes())
+}
+
+/// FNV-1a, which, unlike the hasher of the standard library, is stable across releases
+pub fn hash(input: &[u8]) -> String {
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in input {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn parse_entry(value: &toml::Value) -> Result<Entry, String> {
+
+    let table = match *value {
+        toml::Value::Table(ref table) => table,
+        _ => return Err("finding should be a table".into()),
+    };
+
+    let string = |key: &str| {
+        table.get(key)
+            .and_then(|value| value.as_str())
+            .map(String::from)
+            .ok_or_else(|| format!("finding: {} should be a string", key))
+    };
+    let code = match table.get("code") {
+        Some(&toml::Value::Integer(code)) if code > 0 => code as u32,
+        _ => return Err("finding: code should be a positive integer".into()),
+    };
+
+    Ok(Entry {
+        file: string("file")?,
+        code,
+        rule: match table.get("rule") {
+            None => None,
+            Some(_) => Some(string("rule")?),
+        },
+        fingerprint: string("fingerprint")?,
+        message: string("message").unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+    use lint::{CustomRule, Lint, LintSeverity, Registry, RuleCheck};
+    use rustc_serialize::json::Json;
+    use systemd_parser;
+
+    fn findings(input: &str) -> Vec<LintResult> {
+        let unit = systemd_parser::parse_string(input).unwrap();
+        let registry = Registry::new();
+        let mut findings = registry.find("missing-description").unwrap().run(&unit);
+        findings.extend(registry.find("unknown-directive").unwrap().run(&unit));
+        findings
+    }
+
+    #[test]
+    fn it_should_grandfather_the_recorded_findings_whatever_their_line() {
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &findings("[Service]\nFoo=1\nExecStart=/bin/true\n"));
+
+        let filtered = baseline.filter("foo.service", findings("[Service]\nExecStart=/bin/true\n\nFoo=1\n"));
+
+        assert!(filtered.new.is_empty(), "{:?}", filtered.new);
+        assert!(filtered.resolved.is_empty());
+    }
+
+    #[test]
+    fn it_should_report_new_findings_and_resolved_entries() {
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &findings("[Service]\nFoo=1\n"));
+
+        let filtered = baseline.filter("foo.service", findings("[Unit]\nDescription=foo\n[Service]\nBar=1\n"));
+
+        assert_eq!(1, filtered.new.len());
+        assert!(filtered.new[0].message().contains("Bar"), "{}", filtered.new[0].message());
+        let resolved: Vec<u32> = filtered.resolved.iter().map(|entry| entry.code).collect();
+        assert_eq!(vec![20_000, 40_001], resolved);
+    }
+
+    #[test]
+    fn entries_should_only_apply_to_their_file() {
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &findings("[Service]\nFoo=1\n"));
+
+        let filtered = baseline.filter("bar.service", findings("[Service]\nFoo=1\n"));
+
+        assert_eq!(2, filtered.new.len());
+    }
+
+    #[test]
+    fn recording_a_file_should_replace_its_entries_only() {
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &findings("[Service]\nFoo=1\n"));
+        baseline.record("bar.service", &findings("[Service]\nFoo=1\n"));
+
+        baseline.record("foo.service", &[]);
+
+        assert!(baseline.entries.iter().all(|entry| entry.file == "bar.service"));
+        assert_eq!(2, baseline.entries.len());
+    }
+
+    #[test]
+    fn it_should_read_back_what_it_writes() {
+        let mut baseline = Baseline::default();
+        baseline.record("units/foo \"bar\".service", &findings("[Service]\nFoo=1\n"));
+
+        assert_eq!(Ok(baseline.clone()), Baseline::parse(&baseline.to_toml()));
+    }
+
+    #[test]
+    fn it_should_tell_the_diagnostics_of_the_plugins_apart() {
+        let diagnostic = |rule: &str| {
+            let finding = format!(r#"{{"code": 30004, "rule": "{}", "severity": "warning", "message": "Not allowed"}}"#, rule);
+            LintResult::from_json(&Json::from_str(&finding).unwrap(), &Registry::new()).unwrap()
+        };
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &[diagnostic("house-rules/x-owner")]);
+
+        let filtered = baseline.filter("foo.service", vec![diagnostic("house-rules/x-team")]);
+
+        assert_eq!(1, filtered.new.len());
+        assert_eq!(Some("house-rules/x-owner"), filtered.resolved[0].rule.as_ref().map(|rule| &rule[..]));
+        assert_eq!(Ok(baseline.clone()), Baseline::parse(&baseline.to_toml()));
+    }
+
+    #[test]
+    fn it_should_tell_the_custom_rules_apart() {
+        let rule = |id: &str| CustomRule {
+            id: id.into(),
+            message: "Not allowed".into(),
+            severity: LintSeverity::Error,
+            section: None,
+            check: RuleCheck::Forbid { key: "User".into(), when: None },
+        };
+        let unit = systemd_parser::parse_string("[Service]\nUser=root\n").unwrap();
+        let mut baseline = Baseline::default();
+        baseline.record("foo.service", &rule("no-user").run(&unit));
+
+        let filtered = baseline.filter("foo.service", rule("no-root").run(&unit));
+
+        assert_eq!(1, filtered.new.len());
+        assert_eq!(1, filtered.resolved.len());
+    }
+
+    #[test]
+    fn it_should_reject_malformed_entries() {
+        assert!(Baseline::parse("[[finding]]\nfile = \"foo.service\"\ncode = \"plop\"\nfingerprint = \"0\"").is_err());
+        assert!(Baseline::parse("findings = []").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use baseline::hash;
+use lint::{CustomRule, LintContext, LintId, LintResult, Plugin, Registry, RuleCheck, Settings, DIRECTIVE_DATABASE};
+use rustc_serialize::json::{self, Json};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The findings of the units linted before, so that the unchanged ones are neither
+/// parsed nor linted again. Each unit has a file in the cache directory, named
+/// after its path and replaced when the unit is linted again:
+///
+/// ```json
+/// {"file": "/etc/systemd/system/foo.service", "version": "0.1.4", "build": "3d2c1b0a9f8e7d6c",
+///  "database": "8c5e1f06a0b2d3e4", "settings": "5a1f0c2b9d8e7f60", "content": "0f3e2d1c4b5a6978",
+///  "findings": [{"code": 20000, "severity": "lint", "message": "...", "location": null, "fixes": []}]}
+/// ```
+///
+/// The findings are reused when the version of the linter and its build, a hash of
+/// its sources, the directive database, the settings (the configuration and the
+/// arguments) and the content of the unit are the same. The findings are encoded as
+/// the diagnostics of the plugins.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// What the findings of a unit depend on
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Key {
+    file: String,
+    version: String,
+    build: String,
+    database: String,
+    settings: String,
+    content: String,
+}
+
+impl Key {
+    /// `contents` are the bytes of the unit, as the problems of its decoding are
+    /// findings too
+    pub fn new(file: &Path, contents: &[u8], settings: &Settings) -> Key {
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+
+        Key {
+            file: file.display().to_string(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            build: env!("SYSTEMD_LINTER_BUILD_ID").into(),
+            database: hash(DIRECTIVE_DATABASE.as_bytes()),
+            settings: hash(settings_to_json(settings).to_string().as_bytes()),
+            content: hash(contents),
+        }
+    }
+
+    /// The fields of the entry which have to match
+    fn fields(&self) -> [(&'static str, &str); 6] {
+        [
+            ("file", &self.file),
+            ("version", &self.version),
+            ("build", &self.build),
+            ("database", &self.database),
+            ("settings", &self.settings),
+            ("content", &self.content),
+        ]
+    }
+}
+
+/// Every field of the settings, so that a new one can't be left out of the key
+fn settings_to_json(settings: &Settings) -> Json {
+
+    let Settings { ref context, ref severities, ref selected, ref rules, ref plugins, allow_plugins } = *settings;
+    let LintContext { ref allowed_categories, ref allowed_directives, systemd_version } = *context;
+
+    let strings = |values: &[String]| Json::Array(values.iter().cloned().map(Json::String).collect());
+    let id = |id: &LintId| match *id {
+        LintId::Code(code) => Json::U64(code as u64),
+        LintId::Rule(ref rule) => Json::String(rule.clone()),
+    };
+    let rule = |rule: &CustomRule| {
+        let CustomRule { ref id, ref message, severity, ref section, ref check } = *rule;
+        let check = match *check {
+            RuleCheck::Require(ref key) => vec![Json::String("require".into()), Json::String(key.clone())],
+            RuleCheck::Match(ref key, ref regex) => {
+                vec![Json::String("match".into()), Json::String(key.clone()), Json::String(regex.as_str().into())]
+            }
+            RuleCheck::Forbid { ref key, ref when } => vec![
+                Json::String("forbid".into()),
+                Json::String(key.clone()),
+                when.clone().map_or(Json::Null, Json::String),
+            ],
+        };
+        Json::Array(vec![
+            Json::String(id.clone()),
+            Json::String(message.clone()),
+            Json::String(severity.name().into()),
+            section.clone().map_or(Json::Null, Json::String),
+            Json::Array(check),
+        ])
+    };
+    let plugin = |plugin: &Plugin| {
+        let Plugin { ref name, ref command, timeout } = *plugin;
+        Json::Array(vec![Json::String(name.clone()), strings(command), Json::U64(timeout.as_millis() as u64)])
+    };
+
+    Json::Array(vec![
+        strings(allowed_categories),
+        strings(allowed_directives),
+        systemd_version.map_or(Json::Null, |version| Json::U64(u64::from(version))),
+        Json::Array(severities
+            .iter()
+            .map(|&(ref lint, severity)| Json::Array(vec![id(lint), Json::String(severity.name().into())]))
+            .collect()),
+        selected.as_ref().map_or(Json::Null, |selected| Json::Array(selected.iter().map(id).collect())),
+        Json::Array(rules.iter().map(rule).collect()),
+        Json::Array(plugins.iter().map(plugin).collect()),
+        Json::Boolean(allow_plugins),
+    ])
+}
+
+impl Cache {
+
+    pub fn new(dir: &Path) -> Cache {
+        Cache { dir: dir.to_path_buf() }
+    }
+
+    /// `$XDG_CACHE_HOME/systemd-linter`, or `~/.cache/systemd-linter`
+    pub fn default_dir() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").filter(|dir| !dir.is_empty()).map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("systemd-linter"))
+    }
+
+    fn entry_path(&self, key: &Key) -> PathBuf {
+        self.dir.join(format!("{}.json", hash(key.file.as_bytes())))
+    }
+
+    /// The findings recorded for the key, `None` when they are missing, out of date
+    /// or unreadable
+    pub fn get(&self, key: &Key, registry: &Registry) -> Option<Vec<LintResult>> {
+
+        let entry = Json::from_str(&fs::read_to_string(self.entry_path(key)).ok()?).ok()?;
+        if !key.fields().iter().all(|&(name, value)| entry.find(name).and_then(Json::as_string) == Some(value)) {
+            return None;
+        }
+
+        entry
+            .find("findings")?
+            .as_array()?
+            .iter()
+            .map(|finding| LintResult::from_json(finding, registry).ok())
+            .collect()
+    }
+
+    pub fn put(&self, key: &Key, findings: &[LintResult]) -> Result<(), String> {
+
+        let mut entry: json::Object = key.fields()
+            .iter()
+            .map(|&(name, value)| (name.to_string(), Json::String(value.into())))
+            .collect();
+        entry.insert("findings".into(), Json::Array(findings.iter().map(LintResult::to_json).collect()));
+
+        let path = self.entry_path(key);
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, Json::Object(entry).to_string()))
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+    use lint::{LintCode, LintId, LintSeverity};
+    use systemd_parser;
+
+    /// A directory of each test and process, so that concurrent runs don't share it
+    fn cache(test: &str) -> Cache {
+        let dir = env::temp_dir().join(format!("systemd-linter-cache-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(&dir)
+    }
+
+    fn findings(input: &str) -> Vec<LintResult> {
+        let unit = systemd_parser::parse_string(input).unwrap();
+        let mut findings = vec!();
+        Registry::new().check(&unit, &Settings::default(), &mut findings);
+        findings
+    }
+
+    #[test]
+    fn it_should_return_the_findings_of_an_unchanged_unit() {
+        let cache = cache("hit");
+        let source = "[Service]\nType=Simple\nExecStart=/bin/true\n";
+        let key = Key::new(Path::new("foo.service"), source.as_bytes(), &Settings::default());
+
+        cache.put(&key, &findings(source)).unwrap();
+
+        assert_eq!(Some(findings(source)), cache.get(&key, &Registry::new()))
+    }
+
+    #[test]
+    fn it_should_miss_when_the_unit_or_the_settings_change() {
+        let cache = cache("miss");
+        let source = "[Service]\nExecStart=/bin/true\n";
+        let mut settings = Settings::default();
+        cache.put(&Key::new(Path::new("foo.service"), source.as_bytes(), &settings), &findings(source)).unwrap();
+
+        let changed = Key::new(Path::new("foo.service"), b"[Service]\nExecStart=/bin/false\n", &settings);
+        assert_eq!(None, cache.get(&changed, &Registry::new()));
+        let changed = Key::new(Path::new("foo.service"), b"[Service]\nExecStart=/bin/true\n\0", &settings);
+        assert_eq!(None, cache.get(&changed, &Registry::new()));
+
+        settings.severities.push((LintId::Code(LintCode::LintMissingDescription), LintSeverity::Error));
+        assert_eq!(None, cache.get(&Key::new(Path::new("foo.service"), source.as_bytes(), &settings), &Registry::new()));
+        let rule = |pattern: &str| CustomRule {
+            id: "restart".into(),
+            message: "Restart the service".into(),
+            severity: LintSeverity::Error,
+            section: None,
+            check: RuleCheck::matching("Restart", pattern).unwrap(),
+        };
+        let with_rule = |pattern: &str| Settings { rules: vec![rule(pattern)], ..Settings::default() };
+        cache.put(&Key::new(Path::new("foo.service"), source.as_bytes(), &with_rule("always")), &findings(source)).unwrap();
+        assert_eq!(None, cache.get(&Key::new(Path::new("foo.service"), source.as_bytes(), &with_rule("on-failure")), &Registry::new()));
+        assert_eq!(None, cache.get(&Key::new(Path::new("bar.service"), source.as_bytes(), &Settings::default()), &Registry::new()));
+    }
+
+    #[test]
+    fn it_should_miss_on_unreadable_entries() {
+        let cache = cache("unreadable");
+        let key = Key::new(Path::new("foo.service"), b"", &Settings::default());
+        cache.put(&key, &[]).unwrap();
+
+        fs::write(cache.entry_path(&key), "{\"findings\": ").unwrap();
+
+        assert_eq!(None, cache.get(&key, &Registry::new()))
+    }
+}
@@ -0,0 +1,683 @@
+
+use glob::{MatchOptions, Pattern};
+use lint::{CustomRule, LintSeverity, Plugin, Registry, RuleCheck, Settings, DEFAULT_PLUGIN_TIMEOUT};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml;
+
+pub static CONFIG_FILE_NAME: &str = ".systemd-lint.toml";
+
+/// The project configuration, read from a `.systemd-lint.toml` file:
+///
+/// ```toml
+/// systemd-version = 219
+/// select = ["correctness", "portability"]
+/// disable = ["missing-description"]
+/// allowed-categories = ["Internal"]
+/// allowed-directives = ["InternalOwner"]
+///
+/// [severity]
+/// ErrorUnknownDirective = "warning"
+///
+/// [[override]]
+/// paths = ["legacy/**/*.service"]
+/// disable = ["unknown-directive"]
+///
+/// [[plugin]]
+/// name = "house-rules"
+/// command = ["./tools/house-rules", "--strict"]
+/// timeout = 5
+///
+/// [[rule]]
+/// id = "deployed-services-user"
+/// paths = ["deploy/**/*.service"]
+/// section = "Service"
+/// require = "User"
+/// message = "Deployed services should not run as root"
+/// severity = "error"
+/// ```
+///
+/// Lints are referred to by name, code name or code number, and `select` and
+/// `disable` (or `ignore`) take groups, code prefixes such as `40` and `all` too.
+/// Only the selected lints run, all of them by default. The paths of the
+/// overrides are globs relative to the directory of the configuration file; the
+/// matching overrides apply on top of the top-level settings, in order. Plugins
+/// run on every unit, once allowed by `--allow-plugins`; a program given as a
+/// relative path is relative to the directory of the configuration file too, and
+/// the timeout is in seconds. The diagnostics of a plugin are referred to as
+/// `house-rules/x-owner`, or all at once by the name of the plugin.
+///
+/// A rule either requires a directive (`require = "User"`), checks its values
+/// against a regex (`key = "Restart"` and `match = "on-failure|always"`) or
+/// forbids it (`forbid = "PrivateNetwork"`), maybe only when another directive is
+/// set (`when = "JoinsNamespaceOf"`). It looks at a single section when `section`
+/// is set, and at the units matching `paths` when set. The severity defaults to
+/// warning. A rule is selected, ignored or suppressed by its id, and all the rules
+/// at once as `custom-rule`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    root: PathBuf,
+    settings: Settings,
+    overrides: Vec<Override>,
+    rules: Vec<Rule>,
+}
+
+/// A custom rule, with the globs of the units it applies to, all of them when empty
+#[derive(Clone, Debug)]
+struct Rule {
+    paths: Vec<Pattern>,
+    rule: CustomRule,
+}
+
+#[derive(Clone, Debug)]
+struct Override {
+    paths: Vec<Pattern>,
+    settings: Settings,
+}
+
+impl Config {
+
+    /// Look for a configuration file in the directory of `file`, then in its parents
+    pub fn discover(file: &Path, registry: &Registry) -> Result<Option<Config>, String> {
+
+        let file = file.canonicalize()
+            .map_err(|err| format!("{}: {}", file.display(), err))?;
+
+        for dir in file.ancestors().skip(1) {
+            let candidate = dir.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Config::load(&candidate, registry).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn load(path: &Path, registry: &Registry) -> Result<Config, String> {
+
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        let root = path.parent().unwrap_or_else(|| Path::new("."));
+        Config::parse(&contents, root, registry)
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Parse a configuration, whose override paths are relative to `root`
+    pub fn parse(input: &str, root: &Path, registry: &Registry) -> Result<Config, String> {
+
+        let mut parser = toml::Parser::new(input);
+        let table = match parser.parse() {
+            Some(table) => table,
+            None => {
+                let err = &parser.errors[0];
+                let (line, col) = parser.to_linecol(err.lo);
+                return Err(format!("line {}, column {}: {}", line + 1, col + 1, err.desc));
+            }
+        };
+
+        let mut settings = Settings::default();
+        let mut overrides = vec!();
+        let mut rules: Vec<Rule> = vec!();
+
+        // the plugins and the rules first, as the other settings may refer to them
+        for (key, value) in &table {
+            match &key[..] {
+                "plugin" => {
+                    for value in as_array(key, value)? {
+                        settings.plugins.push(parse_plugin(value, root)?);
+                    }
+                }
+                "rule" => {
+                    for value in as_array(key, value)? {
+                        let rule = parse_rule(value, registry)?;
+                        if rules.iter().any(|other| other.rule.id == rule.rule.id) {
+                            return Err(format!("rule {} is defined twice", rule.rule.id));
+                        }
+                        rules.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut rule_ids = settings.rule_ids();
+        rule_ids.extend(rules.iter().map(|rule| rule.rule.id.clone()));
+        for (key, value) in &table {
+            match &key[..] {
+                "plugin" | "rule" => {}
+                "override" => {
+                    for value in as_array(key, value)? {
+                        overrides.push(parse_override(value, registry, &rule_ids)?);
+                    }
+                }
+                _ => parse_setting(&mut settings, key, value, registry, &rule_ids)?,
+            }
+        }
+        // `disable` is read before `severity` whatever the key order
+        settings.severities.sort_by_key(|&(_, severity)| severity != LintSeverity::Ignore);
+
+        Ok(Config {
+            root: root.canonicalize().unwrap_or_else(|_| root.into()),
+            settings,
+            overrides,
+            rules,
+        })
+    }
+
+    /// The ids of the rules of the configuration, which the arguments may refer to
+    /// along with the built-in lints, see `Registry::select_ids`
+    pub fn rule_ids(&self) -> Vec<String> {
+        let mut rule_ids = self.settings.rule_ids();
+        rule_ids.extend(self.rules.iter().map(|rule| rule.rule.id.clone()));
+        rule_ids
+    }
+
+    /// The settings for the unit at `path`
+    pub fn settings_for(&self, path: &Path) -> Settings {
+
+        let mut settings = self.settings.clone();
+
+        let path = path.canonicalize().unwrap_or_else(|_| path.into());
+        let relative = path.strip_prefix(&self.root).ok();
+
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        let matches = |paths: &[Pattern]| {
+            relative.is_some_and(|relative| paths.iter().any(|pattern| pattern.matches_path_with(relative, &options)))
+        };
+
+        for o in &self.overrides {
+            if matches(&o.paths) {
+                merge(&mut settings, &o.settings);
+            }
+        }
+
+        for rule in &self.rules {
+            if rule.paths.is_empty() || matches(&rule.paths) {
+                settings.rules.push(rule.rule.clone());
+            }
+        }
+
+        settings
+    }
+}
+
+/// Apply the settings of an override on top of `settings`
+fn merge(settings: &mut Settings, other: &Settings) {
+    settings.severities.extend(other.severities.iter().cloned());
+    if other.selected.is_some() {
+        settings.selected = other.selected.clone();
+    }
+    settings.context.allowed_categories.extend(other.context.allowed_categories.iter().cloned());
+    settings.context.allowed_directives.extend(other.context.allowed_directives.iter().cloned());
+    if other.context.systemd_version.is_some() {
+        settings.context.systemd_version = other.context.systemd_version;
+    }
+}
+
+fn parse_override(value: &toml::Value, registry: &Registry, rule_ids: &[String]) -> Result<Override, String> {
+
+    let table = match *value {
+        toml::Value::Table(ref table) => table,
+        _ => return Err("override should be a table".into()),
+    };
+
+    let mut paths = vec!();
+    let mut settings = Settings::default();
+
+    for (key, value) in table {
+        match &key[..] {
+            "paths" => paths = as_globs(key, value)?,
+            _ => parse_setting(&mut settings, key, value, registry, rule_ids)
+                .map_err(|err| format!("override: {}", err))?,
+        }
+    }
+    settings.severities.sort_by_key(|&(_, severity)| severity != LintSeverity::Ignore);
+
+    if paths.is_empty() {
+        return Err("override without paths".into());
+    }
+
+    Ok(Override { paths, settings })
+}
+
+fn parse_plugin(value: &toml::Value, root: &Path) -> Result<Plugin, String> {
+
+    let table = match *value {
+        toml::Value::Table(ref table) => table,
+        _ => return Err("plugin should be a table".into()),
+    };
+
+    let mut name = None;
+    let mut command: Vec<String> = vec!();
+    let mut timeout = DEFAULT_PLUGIN_TIMEOUT;
+
+    for (key, value) in table {
+        match &key[..] {
+            "name" => name = Some(value.as_str().ok_or("plugin: name should be a string")?.to_string()),
+            "command" => command = as_strings(key, value).map_err(|err| format!("plugin: {}", err))?
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            "timeout" => match *value {
+                toml::Value::Integer(secs) if secs > 0 => timeout = Duration::from_secs(secs as u64),
+                toml::Value::Float(secs) if secs > 0.0 => timeout = Duration::from_millis((secs * 1000.0) as u64),
+                _ => return Err("plugin: timeout should be a positive number of seconds".into()),
+            },
+            _ => return Err(format!("plugin: unknown key: {}", key)),
+        }
+    }
+
+    if command.is_empty() {
+        return Err("plugin without command".into());
+    }
+    // `./tools/foo` is relative to the configuration, `foo` is looked up in the PATH
+    if command[0].contains('/') && Path::new(&command[0]).is_relative() {
+        command[0] = root.join(command[0].trim_start_matches("./")).to_string_lossy().into_owned();
+    }
+    let name = name.unwrap_or_else(|| {
+        Path::new(&command[0]).file_name().map_or(command[0].clone(), |name| name.to_string_lossy().into_owned())
+    });
+
+    Ok(Plugin { name, command, timeout })
+}
+
+fn parse_rule(value: &toml::Value, registry: &Registry) -> Result<Rule, String> {
+
+    let table = match *value {
+        toml::Value::Table(ref table) => table,
+        _ => return Err("rule should be a table".into()),
+    };
+
+    let string = |key: &str| -> Result<Option<&str>, String> {
+        match table.get(key) {
+            None => Ok(None),
+            Some(value) => value.as_str().map(Some).ok_or_else(|| format!("rule: {} should be a string", key)),
+        }
+    };
+
+    let mut paths = vec!();
+    for (key, value) in table {
+        match &key[..] {
+            "paths" => paths = as_globs(key, value).map_err(|err| format!("rule: {}", err))?,
+            "id" | "message" | "severity" | "section" | "require" | "key" | "match" | "forbid" | "when" => {}
+            _ => return Err(format!("rule: unknown key: {}", key)),
+        }
+    }
+
+    let id = string("id")?.ok_or("rule without id")?;
+    if registry.find(id).is_some() {
+        return Err(format!("rule {}: the id of a built-in lint", id));
+    }
+    let message = string("message")?.ok_or_else(|| format!("rule {}: missing message", id))?;
+    let severity = match string("severity")? {
+        None => LintSeverity::Warning,
+        Some(severity) => LintSeverity::from_name(severity)
+            .ok_or_else(|| format!("rule {}: severity should be one of: ignore, lint, warning, error", id))?,
+    };
+
+    let check = match (string("require")?, string("key")?, string("match")?, string("forbid")?, string("when")?) {
+        (Some(key), None, None, None, None) => RuleCheck::Require(key.into()),
+        (None, Some(key), Some(pattern), None, None) => RuleCheck::matching(key, pattern)
+            .map_err(|err| format!("rule {}: {}", id, err))?,
+        (None, None, None, Some(key), when) => RuleCheck::Forbid { key: key.into(), when: when.map(String::from) },
+        _ => return Err(format!("rule {}: expected either require, key and match, or forbid and an optional when", id)),
+    };
+
+    Ok(Rule {
+        paths,
+        rule: CustomRule {
+            id: id.into(),
+            message: message.into(),
+            severity,
+            section: string("section")?.map(String::from),
+            check,
+        },
+    })
+}
+
+fn parse_setting(settings: &mut Settings, key: &str, value: &toml::Value, registry: &Registry, rule_ids: &[String])
+    -> Result<(), String> {
+
+    match key {
+        "systemd-version" => {
+            match *value {
+                toml::Value::Integer(version) if version > 0 => {
+                    settings.context.systemd_version = Some(version as u32);
+                }
+                _ => return Err(format!("{} should be a positive integer", key)),
+            }
+        }
+        "select" => {
+            let mut selected = vec!();
+            for selector in as_lint_ids(key, value)? {
+                selected.extend(registry.select_ids(&selector, rule_ids)?);
+            }
+            settings.selected = Some(selected);
+        }
+        "disable" | "ignore" => {
+            for selector in as_lint_ids(key, value)? {
+                for id in registry.select_ids(&selector, rule_ids)? {
+                    settings.severities.push((id, LintSeverity::Ignore));
+                }
+            }
+        }
+        "severity" => {
+            let table = match *value {
+                toml::Value::Table(ref table) => table,
+                _ => return Err(format!("{} should be a table", key)),
+            };
+            for (id, severity) in table {
+                let lint_id = registry.find_id(id, rule_ids).ok_or_else(|| format!("unknown lint: {}", id))?;
+                let severity = severity
+                    .as_str()
+                    .and_then(LintSeverity::from_name)
+                    .ok_or_else(|| format!("severity of {} should be one of: ignore, lint, warning, error", id))?;
+                settings.severities.push((lint_id, severity));
+            }
+        }
+        "allowed-categories" => {
+            let categories = as_strings(key, value)?;
+            settings.context.allowed_categories.extend(categories.into_iter().map(String::from));
+        }
+        "allowed-directives" => {
+            let directives = as_strings(key, value)?;
+            settings.context.allowed_directives.extend(directives.into_iter().map(String::from));
+        }
+        _ => return Err(format!("unknown key: {}", key)),
+    }
+
+    Ok(())
+}
+
+fn as_array<'a>(key: &str, value: &'a toml::Value) -> Result<&'a [toml::Value], String> {
+    value.as_slice().ok_or_else(|| format!("{} should be an array", key))
+}
+
+fn as_strings<'a>(key: &str, value: &'a toml::Value) -> Result<Vec<&'a str>, String> {
+    as_array(key, value)?
+        .iter()
+        .map(|value| value.as_str().ok_or_else(|| format!("{} should be an array of strings", key)))
+        .collect()
+}
+
+fn as_globs(key: &str, value: &toml::Value) -> Result<Vec<Pattern>, String> {
+    as_strings(key, value)?
+        .into_iter()
+        .map(|glob| Pattern::new(glob).map_err(|err| format!("invalid glob {}: {}", glob, err.msg)))
+        .collect()
+}
+
+/// Lints may be given by name, code or selector, and codes may be written as integers
+fn as_lint_ids(key: &str, value: &toml::Value) -> Result<Vec<String>, String> {
+    as_array(key, value)?
+        .iter()
+        .map(|value| match *value {
+            toml::Value::String(ref id) => Ok(id.clone()),
+            toml::Value::Integer(code) => Ok(code.to_string()),
+            _ => Err(format!("{} should be an array of lint names or codes", key)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+    use lint::{LintCode, LintId};
+
+    fn parse(input: &str) -> Result<Config, String> {
+        Config::parse(input, Path::new("/etc/systemd"), &Registry::new())
+    }
+
+    #[test]
+    fn it_should_disable_lints_by_name_and_code() {
+        let config = parse("disable = [\"missing-description\", \"40001\", \"ErrorUnknownCategory\"]").unwrap();
+
+        assert_eq!(vec![
+            (LintId::Code(LintCode::LintMissingDescription), LintSeverity::Ignore),
+            (LintId::Code(LintCode::ErrorUnknownDirective), LintSeverity::Ignore),
+            (LintId::Code(LintCode::ErrorUnknownCategory), LintSeverity::Ignore),
+        ], config.settings.severities);
+
+        let config = parse("disable = [40001]").unwrap();
+
+        assert_eq!(vec![(LintId::Code(LintCode::ErrorUnknownDirective), LintSeverity::Ignore)], config.settings.severities);
+    }
+
+    #[test]
+    fn it_should_override_severities() {
+        let config = parse("
+            [severity]
+            LintMissingDescription = \"error\"
+        ").unwrap();
+
+        assert_eq!(vec![(LintId::Code(LintCode::LintMissingDescription), LintSeverity::Error)], config.settings.severities);
+    }
+
+    #[test]
+    fn severities_should_win_over_disabled_lints() {
+        let config = parse("
+            disable = [\"missing-description\"]
+            [severity]
+            missing-description = \"warning\"
+        ").unwrap();
+        let settings = config.settings_for(Path::new("/etc/systemd/foo.service"));
+        let registry = Registry::new();
+
+        let severity = settings.severity(registry.find("missing-description").unwrap());
+        assert_eq!(LintSeverity::Warning, severity);
+    }
+
+    #[test]
+    fn it_should_select_and_ignore_groups_and_code_prefixes() {
+        let config = parse("
+            select = [\"portability\", \"40001\"]
+            ignore = [\"20\"]
+            [[override]]
+            paths = [\"legacy/*.service\"]
+            select = [\"all\"]
+        ").unwrap();
+        let registry = Registry::new();
+        let severity = |settings: &Settings, id: &str| settings.severity(registry.find(id).unwrap());
+
+        let settings = config.settings_for(Path::new("/etc/systemd/foo.service"));
+        assert_eq!(LintSeverity::Error, severity(&settings, "unsupported-directive"));
+        assert_eq!(LintSeverity::Error, severity(&settings, "unknown-directive"));
+        assert_eq!(LintSeverity::Ignore, severity(&settings, "unknown-category"));
+        assert_eq!(vec![(LintId::Code(LintCode::LintMissingDescription), LintSeverity::Ignore)], settings.severities);
+
+        let legacy = config.settings_for(Path::new("/etc/systemd/legacy/foo.service"));
+        assert_eq!(LintSeverity::Error, severity(&legacy, "unknown-category"));
+        assert_eq!(LintSeverity::Ignore, severity(&legacy, "missing-description"));
+    }
+
+    #[test]
+    fn it_should_read_the_context() {
+        let config = parse("
+            systemd-version = 219
+            allowed-categories = [\"Internal\"]
+            allowed-directives = [\"InternalOwner\"]
+        ").unwrap();
+
+        assert_eq!(Some(219), config.settings.context.systemd_version);
+        assert_eq!(vec!["Internal"], config.settings.context.allowed_categories);
+        assert_eq!(vec!["InternalOwner"], config.settings.context.allowed_directives);
+    }
+
+    #[test]
+    fn overrides_should_apply_to_matching_paths_only() {
+        let config = parse("
+            [[override]]
+            paths = [\"legacy/**/*.service\"]
+            disable = [\"unknown-directive\"]
+            systemd-version = 209
+        ").unwrap();
+
+        let legacy = config.settings_for(Path::new("/etc/systemd/legacy/old/foo.service"));
+        assert_eq!(vec![(LintId::Code(LintCode::ErrorUnknownDirective), LintSeverity::Ignore)], legacy.severities);
+        assert_eq!(Some(209), legacy.context.systemd_version);
+
+        let other = config.settings_for(Path::new("/etc/systemd/foo.service"));
+        assert!(other.severities.is_empty());
+        assert_eq!(None, other.context.systemd_version);
+    }
+
+    #[test]
+    fn override_globs_should_not_cross_directories() {
+        let config = parse("
+            [[override]]
+            paths = [\"*.service\"]
+            disable = [\"unknown-directive\"]
+        ").unwrap();
+
+        assert!(!config.settings_for(Path::new("/etc/systemd/foo.service")).severities.is_empty());
+        assert!(config.settings_for(Path::new("/etc/systemd/system/foo.service")).severities.is_empty());
+    }
+
+    #[test]
+    fn it_should_read_the_plugins() {
+        let config = parse("
+            [[plugin]]
+            name = \"house-rules\"
+            command = [\"./tools/house-rules\", \"--strict\"]
+            timeout = 2
+
+            [[plugin]]
+            command = [\"check-owner\"]
+        ").unwrap();
+
+        assert_eq!(vec![
+            Plugin {
+                name: "house-rules".into(),
+                command: vec!["/etc/systemd/tools/house-rules".into(), "--strict".into()],
+                timeout: Duration::from_secs(2),
+            },
+            Plugin {
+                name: "check-owner".into(),
+                command: vec!["check-owner".into()],
+                timeout: DEFAULT_PLUGIN_TIMEOUT,
+            },
+        ], config.settings_for(Path::new("/etc/systemd/foo.service")).plugins);
+
+        assert_eq!(Err("plugin without command".into()), parse("[[plugin]]\nname = \"foo\"").map(|_| ()));
+    }
+
+    #[test]
+    fn it_should_refer_to_the_diagnostics_of_the_plugins_by_id() {
+        let config = parse("
+            disable = [\"house-rules/x-owner\"]
+
+            [[plugin]]
+            name = \"house-rules\"
+            command = [\"house-rules\"]
+
+            [severity]
+            house-rules = \"error\"
+        ").unwrap();
+
+        assert_eq!(vec![
+            (LintId::Rule("house-rules/x-owner".into()), LintSeverity::Ignore),
+            (LintId::Rule("house-rules".into()), LintSeverity::Error),
+        ], config.settings.severities);
+        assert_eq!(
+            Err("unknown lint: other-rules/x-owner".into()),
+            parse("disable = [\"other-rules/x-owner\"]").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn it_should_refer_to_the_custom_rules_by_id() {
+        let config = parse("
+            select = [\"no-private-tmp\", \"unknown-directive\"]
+
+            [[rule]]
+            id = \"no-private-tmp\"
+            forbid = \"PrivateTmp\"
+            message = \"Use a directory of the service\"
+
+            [severity]
+            no-private-tmp = \"lint\"
+        ").unwrap();
+
+        assert_eq!(
+            Some(vec![LintId::Rule("no-private-tmp".into()), LintId::Code(LintCode::ErrorUnknownDirective)]),
+            config.settings.selected
+        );
+        assert_eq!(vec![(LintId::Rule("no-private-tmp".into()), LintSeverity::Lint)], config.settings.severities);
+        assert_eq!(vec!["no-private-tmp".to_string()], config.rule_ids());
+    }
+
+    #[test]
+    fn rules_should_apply_to_matching_paths_only() {
+        let config = parse("
+            [[rule]]
+            id = \"deployed-services-user\"
+            paths = [\"deploy/**/*.service\"]
+            section = \"Service\"
+            require = \"User\"
+            message = \"Deployed services should not run as root\"
+            severity = \"error\"
+
+            [[rule]]
+            id = \"restart\"
+            key = \"Restart\"
+            match = \"on-failure|always\"
+            message = \"Services should restart on failure\"
+        ").unwrap();
+
+        let deployed = config.settings_for(Path::new("/etc/systemd/deploy/web/foo.service")).rules;
+        assert_eq!(vec!["deployed-services-user", "restart"], deployed.iter().map(|rule| &rule.id[..]).collect::<Vec<_>>());
+        assert_eq!(CustomRule {
+            id: "deployed-services-user".into(),
+            message: "Deployed services should not run as root".into(),
+            severity: LintSeverity::Error,
+            section: Some("Service".into()),
+            check: RuleCheck::Require("User".into()),
+        }, deployed[0]);
+        assert_eq!(RuleCheck::matching("Restart", "on-failure|always").unwrap(), deployed[1].check);
+        assert_eq!(LintSeverity::Warning, deployed[1].severity);
+
+        let other = config.settings_for(Path::new("/etc/systemd/foo.service")).rules;
+        assert_eq!(vec!["restart"], other.iter().map(|rule| &rule.id[..]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_should_reject_ambiguous_and_malformed_rules() {
+        let err = |input: &str| parse(input).map(|_| ()).unwrap_err();
+
+        assert_eq!(
+            "rule foo: expected either require, key and match, or forbid and an optional when",
+            err("[[rule]]\nid = \"foo\"\nmessage = \"m\"\nrequire = \"User\"\nforbid = \"Group\"")
+        );
+        assert!(err("[[rule]]\nid = \"foo\"\nmessage = \"m\"\nkey = \"Restart\"\nmatch = \"(\"").starts_with("rule foo: invalid regex"));
+        assert_eq!("rule unknown-directive: the id of a built-in lint",
+                   err("[[rule]]\nid = \"unknown-directive\"\nmessage = \"m\"\nrequire = \"User\""));
+        assert_eq!("rule foo is defined twice",
+                   err("[[rule]]\nid = \"foo\"\nmessage = \"m\"\nrequire = \"A\"\n[[rule]]\nid = \"foo\"\nmessage = \"m\"\nrequire = \"B\""));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_lints_and_keys() {
+        assert_eq!(Err("unknown lint: plop".into()), parse("disable = [\"plop\"]").map(|_| ()));
+        assert_eq!(Err("unknown key: disabled".into()), parse("disabled = [\"plop\"]").map(|_| ()));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_severities() {
+        assert!(parse("[severity]\nmissing-description = \"fatal\"").is_err());
+    }
+
+    #[test]
+    fn it_should_report_syntax_errors_with_their_position() {
+        let err = parse("disable = [").unwrap_err();
+        assert!(err.starts_with("line 1"), "{}", err);
+    }
+}
@@ -0,0 +1,132 @@
+
+use std::cmp;
+
+/// Lines of context around the changes
+const CONTEXT: usize = 3;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A unified diff between two versions of the file at `path`, empty when they are equal
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+
+    let lines = diff_lines(old, new);
+
+    // hunks, as ranges of `lines`, merged when their contexts touch
+    let mut hunks: Vec<(usize, usize)> = vec!();
+    for (idx, _) in lines.iter().enumerate().filter(|&(_, line)| !is_same(line)) {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = cmp::min(idx + CONTEXT + 1, lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let path = path.trim_start_matches('/');
+    let mut res = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for (start, end) in hunks {
+        let old_before = lines[..start].iter().filter(|line| !is_added(line)).count();
+        let new_before = lines[..start].iter().filter(|line| !is_removed(line)).count();
+        let old_len = lines[start..end].iter().filter(|line| !is_added(line)).count();
+        let new_len = lines[start..end].iter().filter(|line| !is_removed(line)).count();
+
+        res.push_str(&format!("@@ -{} +{} @@\n", hunk_range(old_before, old_len), hunk_range(new_before, new_len)));
+        for line in &lines[start..end] {
+            match *line {
+                Line::Same(text) => res.push_str(&format!(" {}\n", text)),
+                Line::Removed(text) => res.push_str(&format!("-{}\n", text)),
+                Line::Added(text) => res.push_str(&format!("+{}\n", text)),
+            }
+        }
+    }
+
+    res
+}
+
+/// An empty range starts at the line before it
+fn hunk_range(before: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", before),
+        1 => format!("{}", before + 1),
+        _ => format!("{},{}", before + 1, len),
+    }
+}
+
+fn is_same(line: &Line) -> bool { matches!(*line, Line::Same(_)) }
+fn is_removed(line: &Line) -> bool { matches!(*line, Line::Removed(_)) }
+fn is_added(line: &Line) -> bool { matches!(*line, Line::Added(_)) }
+
+/// Line by line diff, from the longest common subsequence. Unit files are small
+/// enough for the quadratic table.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                cmp::max(lcs[i + 1][j], lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut res = vec!();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            res.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            res.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            res.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn it_should_be_empty_for_equal_texts() {
+        assert_eq!("", unified_diff("[Unit]\n", "[Unit]\n", "foo.service"))
+    }
+
+    #[test]
+    fn it_should_show_the_changes_with_their_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\n";
+
+        let expected = "--- a/foo.service\n+++ b/foo.service\n@@ -2,7 +2,8 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n+i\n";
+        assert_eq!(expected, unified_diff(old, new, "foo.service"))
+    }
+
+    #[test]
+    fn it_should_split_distant_changes_in_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+
+        let expected = "--- a/f\n+++ b/f\n@@ -1,3 +1,4 @@\n+0\n 1\n 2\n 3\n@@ -7,4 +8,3 @@\n 7\n 8\n 9\n-10\n";
+        assert_eq!(expected, unified_diff(old, new, "f"))
+    }
+}
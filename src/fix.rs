@@ -0,0 +1,230 @@
+
+use lint::{Edit, Registry, Settings};
+use std::path::Path;
+use systemd_parser::incremental::{Document, TextEdit};
+
+/// How many times the lints are run again on the fixed unit, at most
+const MAX_PASSES: usize = 10;
+
+pub struct Fixed {
+    pub source: String,
+    /// The description of every applied fix
+    pub applied: Vec<String>,
+}
+
+/// Apply the fixes of the findings, then lint the result and apply the new fixes,
+/// until there is nothing left to fix. Fixes which overlap an already accepted one
+/// wait for the next pass. The source may have CRLF line endings, which are kept:
+/// the inserted lines end like the others.
+pub fn fix(source: &str, path: &Path, registry: &Registry, settings: &Settings, include_unsafe: bool) -> Fixed {
+
+    let mut doc = Document::parse(source);
+    let mut applied = vec!();
+
+    for _ in 0..MAX_PASSES {
+        let mut unit = match doc.unit() {
+            Ok(unit) => unit,
+            Err(_) => break,
+        };
+        unit.set_file_name(path);
+
+        let mut findings = vec!();
+        registry.check_source(doc.source(), &[], Some(path), &unit, settings, &mut findings);
+
+        let mut edits: Vec<TextEdit> = vec!();
+        let fixes = findings
+            .iter()
+            .flat_map(|finding| finding.fixes())
+            .filter(|fix| fix.safe || include_unsafe);
+
+        for fix in fixes {
+            let fix_edits: Vec<TextEdit> = fix.edits.iter().flat_map(|edit| resolve(&doc, edit)).collect();
+            let conflicts = fix_edits.iter().any(|edit| edits.iter().any(|other| overlaps(edit, other)));
+            if fix_edits.is_empty() || conflicts {
+                continue;
+            }
+
+            edits.extend(fix_edits);
+            applied.push(fix.description.clone());
+        }
+
+        if edits.is_empty() {
+            break;
+        }
+
+        // from the end, so that the offsets of the next edits stay valid
+        edits.sort_by_key(|edit| edit.range.start);
+        for edit in edits.iter().rev() {
+            doc.apply_edit(edit).expect("resolved edits are within the source");
+        }
+    }
+
+    Fixed {
+        source: doc.source().into(),
+        applied,
+    }
+}
+
+/// Two insertions at the same place overlap too, as their order would be arbitrary
+fn overlaps(edit: &TextEdit, other: &TextEdit) -> bool {
+    edit.range.start == other.range.start ||
+        (edit.range.start < other.range.end && other.range.start < edit.range.end)
+}
+
+/// The text edits doing `edit` in the document
+fn resolve(doc: &Document, edit: &Edit) -> Vec<TextEdit> {
+
+    let source = doc.source();
+    let directives = |category: &str, key: &str| {
+        doc.directive_spans()
+            .into_iter()
+            .filter(|span| span.category == category && span.key == key)
+            .collect::<Vec<_>>()
+    };
+
+    match *edit {
+        Edit::ReplaceValue { ref category, ref key, ref old_value, ref new_value } => {
+            directives(category, key)
+                .into_iter()
+                .filter(|span| source[span.value.clone()] == **old_value)
+                .map(|span| TextEdit::new(span.value, new_value))
+                .collect()
+        }
+        Edit::RenameKey { ref category, ref key, ref new_key } => {
+            directives(category, key)
+                .into_iter()
+                .map(|span| {
+                    let start = span.line.start + source[span.line].find(&key[..]).expect("the line holds its key");
+                    TextEdit::new(start..start + key.len(), new_key)
+                })
+                .collect()
+        }
+        Edit::RenameCategory { ref category, ref new_category } => {
+            doc.category_spans()
+                .into_iter()
+                .filter(|span| span.category == *category)
+                .map(|span| {
+                    let start = span.span.start + source[span.span].find(&category[..]).expect("the header holds its category");
+                    TextEdit::new(start..start + category.len(), new_category)
+                })
+                .collect()
+        }
+        Edit::Remove { ref category, ref key } => {
+            directives(category, key)
+                .into_iter()
+                .map(|span| TextEdit::new(span.line, ""))
+                .collect()
+        }
+        Edit::Insert { ref category, ref key, ref value } => {
+            let eol = if source.contains("\r\n") { "\r\n" } else { "\n" };
+            let line = format!("{}={}{}", key, value, eol);
+            let last_span = doc.category_spans()
+                .into_iter()
+                .rev()
+                .find(|span| span.category == *category);
+
+            let (at, text) = match last_span {
+                Some(span) => (span.span.end, line),
+                None => (source.len(), format!("{}[{}]{}{}", eol, category, eol, line)),
+            };
+            let text = if at == source.len() && !source.is_empty() && !source.ends_with('\n') {
+                format!("{}{}", eol, text)
+            } else {
+                text
+            };
+
+            vec![TextEdit::new(at..at, &text)]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+    use lint::{LintCode, LintId, LintSeverity};
+
+    fn fix_str(input: &str, path: &str) -> Fixed {
+        fix(input, Path::new(path), &Registry::new(), &Settings::default(), false)
+    }
+
+    #[test]
+    fn it_should_apply_fixes_until_stable() {
+        let input = "[Unit]\nDescription=foo\n\n[Service]\nExecStart=/bin/true\n\n[Install]\nWantedBy=multi-user.target\n";
+
+        let res = fix_str(input, "foo.service");
+
+        assert_eq!("[Unit]\nDescription=foo\n\n[Service]\nExecStart=/bin/true\nType=simple\n\n[Install]\nWantedBy=multi-user.target\n", res.source);
+        assert_eq!(1, res.applied.len());
+    }
+
+    #[test]
+    fn it_should_add_the_missing_category() {
+        let input = "[Unit]\nDescription=foo";
+
+        let res = fix_str(input, "foo.service");
+
+        assert_eq!("[Unit]\nDescription=foo\n\n[Service]\nType=oneshot\n", res.source);
+    }
+
+    #[test]
+    fn it_should_keep_crlf_line_endings() {
+        let input = "[Unit]\r\nDescription=foo\r\n\r\n[Service]\r\nType=Simple\r\nExecStart=/bin/true\r\n";
+
+        let res = fix(input, Path::new("foo.service"), &Registry::new(), &Settings::default(), true);
+
+        assert_eq!("[Unit]\r\nDescription=foo\r\n\r\n[Service]\r\nType=simple\r\nExecStart=/bin/true\r\n", res.source);
+        assert_eq!("[Unit]\r\nDescription=foo\r\n\r\n[Service]\r\nType=oneshot\r\n", fix_str("[Unit]\r\nDescription=foo\r\n", "foo.service").source);
+    }
+
+    #[test]
+    fn it_should_not_apply_fixes_of_disabled_lints() {
+        let input = "[Service]\nExecStart=/bin/true\n";
+        let settings = Settings {
+            severities: vec![(LintId::Code(LintCode::WarnServiceTypeShouldAlwaysBeExplicit), LintSeverity::Ignore)],
+            ..Settings::default()
+        };
+
+        let res = fix(input, Path::new("foo.service"), &Registry::new(), &settings, false);
+
+        assert_eq!(input, res.source);
+        assert!(res.applied.is_empty());
+    }
+
+    #[test]
+    fn it_should_leave_unparsable_units_alone() {
+        let input = "[Service\nExecStart=/bin/true\n";
+
+        assert_eq!(input, fix_str(input, "foo.service").source);
+    }
+
+    #[test]
+    fn it_should_resolve_edits_of_every_occurrence() {
+        let doc = Document::parse("[Socket]\n  ListenStream = 80 \nListenStream=\\\n 443\n");
+
+        let rename = Edit::RenameKey { category: "Socket".into(), key: "ListenStream".into(), new_key: "ListenDatagram".into() };
+        let replace = Edit::ReplaceValue {
+            category: "Socket".into(),
+            key: "ListenStream".into(),
+            old_value: "80".into(),
+            new_value: "22".into(),
+        };
+        let remove = Edit::Remove { category: "Socket".into(), key: "ListenStream".into() };
+
+        assert_eq!(vec![TextEdit::new(11..23, "ListenDatagram"), TextEdit::new(30..42, "ListenDatagram")], resolve(&doc, &rename));
+        assert_eq!(vec![TextEdit::new(26..28, "22")], resolve(&doc, &replace));
+        assert_eq!(vec![TextEdit::new(9..30, ""), TextEdit::new(30..50, "")], resolve(&doc, &remove));
+    }
+
+    #[test]
+    fn it_should_rename_every_header_of_a_category() {
+        let doc = Document::parse("[Services]
+ExecStart=/bin/true
+[ Services ]
+Type=simple
+");
+
+        let rename = Edit::RenameCategory { category: "Services".into(), new_category: "Service".into() };
+
+        assert_eq!(vec![TextEdit::new(1..9, "Service"), TextEdit::new(33..41, "Service")], resolve(&doc, &rename));
+    }
+}
@@ -0,0 +1,299 @@
+//! Lint systemd unit files.
+//!
+//! The quickest way in is `lint_file`, which lints a unit the way the
+//! `systemd-linter` command does, with the `.systemd-lint.toml` configuration found
+//! next to it or in its parents:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let diagnostics = systemd_linter::lint_file(Path::new("/etc/systemd/system/foo.service")).unwrap();
+//! for diagnostic in diagnostics {
+//!     println!("{}[{}]: {}", diagnostic.severity().name(), diagnostic.lint(), diagnostic.message());
+//! }
+//! ```
+//!
+//! `lint_str` lints a unit which is not in a file, with explicit `Settings`:
+//!
+//! ```
+//! use systemd_linter::{LintCode, Settings};
+//!
+//! let unit = "[Service]\nType=Simple\nExecStart=/bin/true\n";
+//! let diagnostics = systemd_linter::lint_str(unit, &Settings::default()).unwrap();
+//!
+//! assert!(diagnostics.iter().any(|diagnostic| diagnostic.code() == LintCode::ErrorInvalidValue));
+//! ```
+#![cfg_attr(feature="clippy", feature(plugin))]
+#![cfg_attr(feature="clippy", plugin(clippy))]
+
+extern crate glob;
+extern crate regex;
+extern crate rustc_serialize;
+extern crate systemd_parser;
+extern crate toml;
+
+// used by the binary, not part of the stable API
+#[doc(hidden)]
+pub mod baseline;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod diff;
+#[doc(hidden)]
+pub mod fix;
+#[doc(hidden)]
+pub mod stats;
+
+pub mod config;
+pub mod lint;
+pub mod support;
+mod suggest;
+
+pub use config::Config;
+pub use lint::{Edit, Fix, Lint, LintCode, LintId, LintResult, LintSeverity, LintSink, Location, Registry, Settings};
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use systemd_parser::decode::{DecodeDiagnostic, DecodeIssue, DecodedInput};
+use systemd_parser::errors::ParserError;
+use systemd_parser::items::{SystemdUnit, UnitType};
+
+/// A finding of a lint, with the name of the lint
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Diagnostic {
+    lint: String,
+    result: LintResult,
+}
+
+impl Diagnostic {
+    /// The name of the lint, e.g. `unknown-directive`, or the id of the custom rule or
+    /// of the diagnostic of a plugin, e.g. `house-rules/x-owner`
+    pub fn lint(&self) -> &str {
+        &self.lint
+    }
+
+    pub fn code(&self) -> LintCode {
+        self.result.code()
+    }
+
+    /// The severity set by the settings, never `Ignore`
+    pub fn severity(&self) -> LintSeverity {
+        self.result.severity()
+    }
+
+    pub fn message(&self) -> &str {
+        self.result.message()
+    }
+
+    pub fn location(&self) -> &Location {
+        self.result.location()
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        self.result.fixes()
+    }
+}
+
+/// Why a unit could not be linted
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    /// The configuration file of the unit is invalid
+    Config(String),
+    Parse(ParserError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            Error::Config(ref err) => write!(f, "invalid configuration: {}", err),
+            Error::Parse(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Lint the source of a unit. Its type is told by its type-specific section, e.g.
+/// `[Service]`: the lints of a given unit type skip it when there is none.
+pub fn lint_str(source: &str, settings: &Settings) -> Result<Vec<Diagnostic>, Error> {
+
+    let unit = systemd_parser::parse_string(source).map_err(Error::Parse)?;
+    Ok(lint_unit(&Registry::new(), source, &[], None, &unit, settings))
+}
+
+/// Lint the unit at `path`, with the configuration found in its directory or in its
+/// parents. Its type is told by its file name. The plugins of the configuration are
+/// not run, but reported by the `plugin-failure` lint.
+pub fn lint_file(path: &Path) -> Result<Vec<Diagnostic>, Error> {
+    lint_file_with(&Registry::new(), path)
+}
+
+/// Lint the units at the paths. The directories are walked: the files whose name
+/// tells a unit type are linted, e.g. `foo.service`, and the `.conf` files of the
+/// drop-in directories, e.g. `foo.service.d/override.conf`, but not the configuration
+/// of the daemons, e.g. `journald.conf`. The results are in the order of the paths,
+/// then of the file names.
+pub fn lint_paths<P: AsRef<Path>>(paths: &[P]) -> Vec<(PathBuf, Result<Vec<Diagnostic>, Error>)> {
+
+    let registry = Registry::new();
+    let mut res = vec!();
+
+    for path in paths {
+        match unit_files(path.as_ref()) {
+            Ok(files) => for file in files {
+                let diagnostics = lint_file_with(&registry, &file);
+                res.push((file, diagnostics));
+            },
+            Err(err) => res.push((path.as_ref().to_path_buf(), Err(err))),
+        }
+    }
+
+    res
+}
+
+fn lint_file_with(registry: &Registry, path: &Path) -> Result<Vec<Diagnostic>, Error> {
+    let file = UnitFile::read(registry, path)?;
+    let unit = file.parse()?;
+    Ok(lint_unit(registry, file.source(), file.decode_diagnostics(), Some(path), &unit, &file.settings))
+}
+
+/// A unit file, read and decoded, with the settings of the configuration found in
+/// its directory or in its parents. `lint_file` lints it as it is, while the command
+/// line changes its settings, or fixes it, first.
+pub struct UnitFile {
+    path: PathBuf,
+    contents: Vec<u8>,
+    decoded: DecodedInput,
+    /// The default settings when there is no configuration
+    pub settings: Settings,
+    /// The ids of the custom rules and of the plugins of the configuration, see
+    /// `Registry::select_ids`
+    pub rule_ids: Vec<String>,
+}
+
+impl UnitFile {
+
+    pub fn read(registry: &Registry, path: &Path) -> Result<UnitFile, Error> {
+
+        let contents = fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
+        let config = Config::discover(path, registry).map_err(Error::Config)?;
+
+        Ok(UnitFile {
+            path: path.to_path_buf(),
+            decoded: systemd_parser::decode::decode(&contents),
+            contents,
+            settings: config.as_ref().map(|config| config.settings_for(path)).unwrap_or_default(),
+            rule_ids: config.as_ref().map(|config| config.rule_ids()).unwrap_or_default(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The bytes of the file
+    pub fn contents(&self) -> &[u8] {
+        &self.contents
+    }
+
+    /// The decoded text of the file, which is linted
+    pub fn source(&self) -> &str {
+        self.decoded.text()
+    }
+
+    /// What was wrong with the bytes of the file, e.g. invalid UTF-8
+    pub fn decode_diagnostics(&self) -> &[DecodeDiagnostic] {
+        self.decoded.diagnostics()
+    }
+
+    /// Tells if the file is text which can be written back: valid UTF-8, without NUL
+    /// bytes. Lone carriage returns are kept by the decoding.
+    pub fn is_text(&self) -> bool {
+        self.decode_diagnostics().iter().all(|diagnostic| diagnostic.issue == DecodeIssue::StrayCarriageReturn)
+    }
+
+    /// The text to fix, along with the BOM to write back: the file as it is, so that
+    /// the lines no fix touches keep their line endings. The decoded text when the
+    /// file is not valid UTF-8, which can't be written back.
+    pub fn raw_source(&self) -> (&'static str, String) {
+        match std::str::from_utf8(&self.contents) {
+            Ok(raw) if self.is_text() => match raw.strip_prefix('\u{FEFF}') {
+                Some(raw) => ("\u{FEFF}", String::from(raw)),
+                None => ("", String::from(raw)),
+            },
+            _ => ("", String::from(self.source())),
+        }
+    }
+
+    /// Replace the text of the file, e.g. by its fixed version
+    pub fn set_source(&mut self, source: &str) {
+        self.contents = source.as_bytes().to_vec();
+        self.decoded = systemd_parser::decode::decode(&self.contents);
+    }
+
+    /// Parse the text, as a unit of the type its file name tells
+    pub fn parse(&self) -> Result<SystemdUnit, Error> {
+        systemd_parser::parse_unit_file(&self.path, self.source()).map_err(Error::Parse)
+    }
+
+    /// Run the lints, and the plugins of the settings, on the unit parsed from the text,
+    /// and report the problems of its bytes
+    pub fn check(&self, registry: &Registry, unit: &SystemdUnit, sink: &mut dyn LintSink) {
+        registry.check_source(self.source(), self.decode_diagnostics(), Some(&self.path), unit, &self.settings, sink)
+    }
+}
+
+fn lint_unit(registry: &Registry, source: &str, decoding: &[DecodeDiagnostic], path: Option<&Path>, unit: &SystemdUnit,
+             settings: &Settings) -> Vec<Diagnostic> {
+
+    let mut findings = vec!();
+    registry.check_source(source, decoding, path, unit, settings, &mut findings);
+
+    findings
+        .into_iter()
+        .map(|result| Diagnostic {
+            lint: result.rule()
+                .or_else(|| registry.find(&(result.code() as u32).to_string()).map(|lint| lint.name()))
+                .unwrap_or("")
+                .into(),
+            result,
+        })
+        .collect()
+}
+
+/// The unit files below `path`, walking the subdirectories, sorted: the files whose
+/// name tells a unit type. `path` itself when it is not a directory. The symbolic
+/// links below `path` are not followed: the ones of `.wants/` directories point at
+/// units linted on their own, and a link to a parent directory would never end.
+pub fn unit_files(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec!();
+    if path.is_dir() {
+        walk(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(files)
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+
+    let mut entries: Vec<(PathBuf, fs::FileType)> = fs::read_dir(dir)
+        .and_then(|entries| entries.map(|entry| entry.and_then(|entry| Ok((entry.path(), entry.file_type()?)))).collect())
+        .map_err(|err| Error::Io(dir.to_path_buf(), err))?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (entry, file_type) in entries {
+        if file_type.is_dir() {
+            walk(&entry, files)?;
+        } else if file_type.is_file() && UnitType::from_path(&entry).is_some() {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+
+use lint::*;
+use regex::Regex;
+use support::*;
+use systemd_parser::items::*;
+
+/// A house rule declared in a `[[rule]]` table of the configuration, run by the
+/// registry along with the built-in lints
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CustomRule {
+    /// Used as the name of the lint, and by the settings and the suppression comments
+    /// to refer to the rule
+    pub id: String,
+    pub message: String,
+    pub severity: LintSeverity,
+    /// The category the rule looks at, all of them when `None`
+    pub section: Option<String>,
+    pub check: RuleCheck,
+}
+
+#[derive(Clone, Debug)]
+pub enum RuleCheck {
+    /// The directive must be set
+    Require(String),
+    /// Every value of the directive must match the regex as a whole
+    Match(String, Regex),
+    /// The directive must not be set, or only when the other one is not
+    Forbid { key: String, when: Option<String> },
+}
+
+impl RuleCheck {
+    /// The regex is anchored, so that `on-failure|always` does not accept `no-always`
+    pub fn matching(key: &str, pattern: &str) -> Result<RuleCheck, String> {
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(|regex| RuleCheck::Match(key.into(), regex))
+            .map_err(|err| format!("invalid regex {}: {}", pattern, err))
+    }
+}
+
+impl PartialEq for RuleCheck {
+    fn eq(&self, other: &RuleCheck) -> bool {
+        match (self, other) {
+            (RuleCheck::Require(a), RuleCheck::Require(b)) => a == b,
+            (RuleCheck::Match(a, re_a), RuleCheck::Match(b, re_b)) => a == b && re_a.as_str() == re_b.as_str(),
+            (RuleCheck::Forbid { key: a, when: when_a }, RuleCheck::Forbid { key: b, when: when_b }) => {
+                a == b && when_a == when_b
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for RuleCheck {}
+
+impl CustomRule {
+    fn in_scope<'a>(&self, unit: &'a SystemdUnit, key: &str) -> Vec<&'a DirectiveEntry> {
+        unit.keys()
+            .into_iter()
+            .filter(|entry| entry.key() == key && self.section.as_ref().is_none_or(|section| *section == entry.category()))
+            .collect()
+    }
+
+    fn report(&self, location: Location, detail: &str, fixes: Vec<Fix>, sink: &mut dyn LintSink) {
+        sink.report(LintResult {
+            severity: self.severity,
+            message: format!("{}{}", self.message, detail),
+            code: LintCode::CustomRule,
+            rule: Some(self.id.clone()),
+            location,
+            fixes,
+        });
+    }
+}
+
+impl Lint for CustomRule {
+    fn code(&self) -> LintCode { LintCode::CustomRule }
+    fn name(&self) -> &str { &self.id }
+    fn rationale(&self) -> &'static str { CustomRules.rationale() }
+    // documented by the configuration which declares it
+    fn docs_url(&self) -> Option<String> { None }
+    fn default_severity(&self) -> LintSeverity { self.severity }
+    fn groups(&self) -> &'static [LintGroup] { CustomRules.groups() }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        match self.check {
+            RuleCheck::Require(ref key) => {
+                if self.in_scope(unit, key).is_empty() {
+                    let location = self.section.clone().map_or(Location::Unit, Location::Category);
+                    self.report(location, "", vec!(), sink);
+                }
+            }
+            RuleCheck::Match(ref key, ref regex) => {
+                for entry in self.in_scope(unit, key) {
+                    for value in entry.values().into_iter().filter(|value| !regex.is_match(value)) {
+                        self.report(Location::of(entry), &format!(" (found {}={})", key, value), vec!(), sink);
+                    }
+                }
+            }
+            RuleCheck::Forbid { ref key, ref when } => {
+                if when.as_ref().is_some_and(|when| self.in_scope(unit, when).is_empty()) {
+                    return;
+                }
+                for entry in self.in_scope(unit, key) {
+                    let fix = Fix {
+                        description: format!("Remove {}= from [{}]", key, entry.category()),
+                        safe: false,
+                        edits: vec![Edit::Remove { category: entry.category(), key: key.clone() }],
+                    };
+                    self.report(Location::of(entry), "", vec![fix], sink);
+                }
+            }
+        }
+    }
+}
+
+/// Stands for every custom rule in the registry, to select or ignore them at once
+pub struct CustomRules;
+
+impl Lint for CustomRules {
+    fn code(&self) -> LintCode { LintCode::CustomRule }
+    fn name(&self) -> &'static str { "custom-rule" }
+    fn rationale(&self) -> &'static str {
+        "The house rules declared in `[[rule]]` tables of the configuration: a directive \
+         which must be set, whose values must match a regex, or which is forbidden, maybe \
+         only when another one is set. Each rule has its own id, message and severity; \
+         it is selected, ignored or suppressed by its id, and warning is used when it has \
+         no severity."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    // the registry runs the rules of the settings
+    fn check(&self, _unit: &SystemdUnit, _ctx: &LintContext, _sink: &mut dyn LintSink) {}
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[cfg(test)]
+fn rule(section: Option<&str>, check: RuleCheck) -> CustomRule {
+    CustomRule {
+        id: "house-rule".into(),
+        message: "breaks the house rules".into(),
+        severity: LintSeverity::Error,
+        section: section.map(String::from),
+        check,
+    }
+}
+
+#[cfg(test)]
+fn messages(findings: &[LintResult]) -> Vec<&str> {
+    findings.iter().map(|finding| &finding.message[..]).collect()
+}
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        [Service]
+        User=nobody
+        Restart=on-failure
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let rules = [
+        rule(Some("Service"), RuleCheck::Require("User".into())),
+        rule(None, RuleCheck::matching("Restart", "on-failure|always").unwrap()),
+        rule(None, RuleCheck::Forbid { key: "PrivateNetwork".into(), when: None }),
+        rule(None, RuleCheck::Forbid { key: "ExecStart".into(), when: Some("BusName".into()) }),
+    ];
+    // act
+    let res: Vec<LintResult> = rules.iter().flat_map(|rule| rule.run(&unit)).collect();
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_required_directive() {
+    // arrange
+    let input = "
+        [Unit]
+        User=nobody
+        [Service]
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = rule(Some("Service"), RuleCheck::Require("User".into())).run(&unit).remove(0);
+    // assert
+    assert_eq!("breaks the house rules", res.message);
+    assert_eq!(Some("house-rule"), res.rule());
+    assert_eq!(Location::Category("Service".into()), res.location)
+}
+
+#[test]
+fn error_case_values_must_match_as_a_whole() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        Restart=no-always
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = rule(None, RuleCheck::matching("Restart", "on-failure|always").unwrap()).run(&unit);
+    // assert
+    assert_eq!(vec!["breaks the house rules (found Restart=no-always)"], messages(&res))
+}
+
+#[test]
+fn error_case_forbidden_directive_when_another_is_set() {
+    // arrange
+    let input = "
+        [Service]
+        Type=dbus
+        BusName=org.example.Foo
+        RemainAfterExit=yes
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = rule(None, RuleCheck::Forbid { key: "RemainAfterExit".into(), when: Some("BusName".into()) })
+        .run(&unit)
+        .remove(0);
+    // assert
+    assert_eq!(Location::Directive { category: "Service".into(), key: "RemainAfterExit".into() }, res.location);
+    assert_eq!(vec![Edit::Remove { category: "Service".into(), key: "RemainAfterExit".into() }], res.fixes()[0].edits)
+}
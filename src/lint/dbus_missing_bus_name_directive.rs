@@ -3,21 +3,37 @@ use lint::*;
 use support::*;
 use systemd_parser::items::*;
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
+pub struct DBusMissingBusNameDirective;
 
-    // Skip the lint if Type is not dbus
-    if !unit.key_have_solo_value("Type", "dbus") {
-        return Ok(());
+impl Lint for DBusMissingBusNameDirective {
+    fn code(&self) -> LintCode { LintCode::ErrorMissingBusNameDirectiveInDBusService }
+    fn name(&self) -> &'static str { "dbus-missing-bus-name" }
+    fn rationale(&self) -> &'static str {
+        "A Type=dbus service is considered started once it has acquired its BusName= on the \
+         bus. Without it, systemd can't start the service."
     }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+    fn categories(&self) -> &'static [&'static str] { &["Service"] }
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(&[UnitType::Service]) }
 
-    if false == unit.has_key("BusName") {
-        Err(LintResult {
-            severity: LintSeverity::Error,
-            message: "You must fill the BusName= directive in a dbus service".into(),
-            code: LintCode::ErrorMissingBusNameDirectiveInDBusService,
-        })
-    } else {
-        Ok(())
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        // Skip the lint if Type is not dbus
+        if !unit.key_have_solo_value("Type", "dbus") {
+            return;
+        }
+
+        if !unit.has_key("BusName") {
+            sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message: "You must fill the BusName= directive in a dbus service".into(),
+                code: LintCode::ErrorMissingBusNameDirectiveInDBusService,
+                rule: None,
+                location: Location::Category("Service".into()),
+                fixes: vec!(),
+            });
+        }
     }
 }
 
@@ -34,9 +50,9 @@ fn success_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = DBusMissingBusNameDirective.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
 }
 
 #[test]
@@ -49,9 +65,9 @@ fn success_case_not_dbus_service() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = DBusMissingBusNameDirective.run(&unit);
     // assert
-    assert!(res.is_ok());
+    assert!(res.is_empty());
 }
 
 #[test]
@@ -63,9 +79,9 @@ fn success_case_not_service() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = DBusMissingBusNameDirective.run(&unit);
     // assert
-    assert!(res.is_ok());
+    assert!(res.is_empty());
 }
 
 #[test]
@@ -77,7 +93,7 @@ fn error_case_missing_directive() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = DBusMissingBusNameDirective.run(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res.is_empty());
 }
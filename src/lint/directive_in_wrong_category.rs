@@ -0,0 +1,163 @@
+
+use lint::*;
+use lint::directives::DirectiveDatabase;
+use support::*;
+use systemd_parser::items::*;
+
+/// The category the directive should be moved to: the one of the unit type when it
+/// accepts the directive, or the only one accepting it
+fn target_category<'a>(unit: &SystemdUnit, sections: &[&'a str]) -> Option<&'a str> {
+
+    let unit_category = unit.unit_type().and_then(|unit_type| unit_type.category());
+
+    match unit_category.and_then(|cat| sections.iter().find(|&&section| section == cat)) {
+        Some(&section) => Some(section),
+        None if sections.len() == 1 => Some(sections[0]),
+        None => None,
+    }
+}
+
+fn move_fix(entry: &DirectiveEntry, target: &str) -> Fix {
+
+    let mut edits = vec![Edit::Remove { category: entry.category(), key: entry.key() }];
+    for value in entry.values() {
+        edits.push(Edit::Insert {
+            category: target.into(),
+            key: entry.key(),
+            value: value.into(),
+        });
+    }
+
+    Fix {
+        description: format!("Move {}= to [{}]", entry.key(), target),
+        safe: false,
+        edits,
+    }
+}
+
+pub struct DirectiveInWrongCategory;
+
+impl Lint for DirectiveInWrongCategory {
+    fn code(&self) -> LintCode { LintCode::ErrorDirectiveInWrongCategory }
+    fn name(&self) -> &'static str { "directive-in-wrong-category" }
+    fn rationale(&self) -> &'static str {
+        "systemd only reads a directive in the categories documented for it, e.g. \
+         ExecStart= is ignored in [Unit] and WantedBy= in [Service]."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        for entry in unit.keys() {
+            let key = entry.key();
+            let cat = entry.category();
+
+            // unknown directives and categories are reported by their own lints
+            if ctx.is_extension(entry) || !directives.contains(&key) || !directives.is_known_section(&cat) {
+                continue;
+            }
+
+            let sections = directives.sections(&key);
+            if sections.contains(&&cat[..]) {
+                continue;
+            }
+
+            sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message: format!("{}= is ignored in [{}], it belongs in [{}]", key, cat, sections.join("], [")),
+                code: LintCode::ErrorDirectiveInWrongCategory,
+                rule: None,
+                location: Location::of(entry),
+                fixes: target_category(unit, &sections).map(|target| move_fix(entry, target)).into_iter().collect(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+#[cfg(test)]
+use std::path::Path;
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        [Service]
+        Type=simple
+        ExecStart=/bin/true
+        User=nobody
+        [Install]
+        WantedBy=multi-user.target
+        [X-Fleet]
+        MachineMetadata=location=chicago
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DirectiveInWrongCategory.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_directive_of_another_category() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        ExecStart=/bin/true
+        [Service]
+        WantedBy=multi-user.target
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("dummy.service"), input).unwrap();
+    // act
+    let res = DirectiveInWrongCategory.run(&unit);
+    // assert
+    let messages: Vec<&str> = res.iter().map(|finding| &finding.message[..]).collect();
+    assert_eq!(vec![
+        "ExecStart= is ignored in [Unit], it belongs in [Service]",
+        "WantedBy= is ignored in [Service], it belongs in [Install]",
+    ], messages)
+}
+
+#[test]
+fn error_case_fix_moves_the_directive_to_the_category_of_the_unit_type() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        User=nobody
+        [Socket]
+        ListenStream=80
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("dummy.socket"), input).unwrap();
+    // act
+    let res = DirectiveInWrongCategory.run(&unit).remove(0);
+    // assert
+    assert_eq!(vec![
+        Edit::Remove { category: "Unit".into(), key: "User".into() },
+        Edit::Insert { category: "Socket".into(), key: "User".into(), value: "nobody".into() },
+    ], res.fixes()[0].edits);
+    assert!(!res.fixes()[0].safe)
+}
+
+#[test]
+fn success_case_unknown_categories_are_left_to_unknown_category() {
+    // arrange
+    let input = "
+        [Services]
+        ExecStart=/bin/true
+        [BusName]
+        Name=org.example.Foo
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DirectiveInWrongCategory.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
@@ -0,0 +1,378 @@
+
+use rustc_serialize::{json, Decodable, Decoder};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The database as embedded in the binary, generated by `crates/directives-generator`
+pub static DIRECTIVES: &str = include_str!("./directives.json");
+
+/// A directive, as documented in a man page
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DocumentedDirective {
+    pub url: String,
+    pub field: String,
+    /// The categories accepting the directive, from the man page documenting it: e.g.
+    /// the directives of systemd.exec go in [Service], [Socket], [Mount] and [Swap]
+    pub sections: Vec<String>,
+    /// Each occurrence adds to the list, instead of replacing the previous value
+    pub list: bool,
+    pub value_type: ValueType,
+    /// The values of an enumeration, or the words a boolean accepts too, e.g.
+    /// `ProtectSystem=strict`
+    pub allowed: Vec<String>,
+    pub versions: Versions,
+    /// The values which appeared or disappeared in some systemd version
+    pub values: Vec<DocumentedValue>,
+    /// The `config_parse_*` function systemd parses the value with, for the entries
+    /// generated from the systemd sources by `crates/directives-generator`
+    pub parser: Option<String>,
+    /// The first sentence of the man page, for the generated entries too
+    pub description: Option<String>,
+}
+
+/// What the values of a directive look like
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ValueType {
+    /// Free-form, or not described yet
+    String,
+    Boolean,
+    /// One of the allowed values
+    Enum,
+    /// e.g. `1min 30s`
+    TimeSpan,
+    /// e.g. `512M`, `50%` or `infinity`
+    Size,
+    Integer,
+    /// A resource limit: a soft and an optional hard value, e.g. `LimitNOFILE=1024:4096`
+    Limit,
+    /// An absolute path
+    Path,
+    /// Space-separated absolute paths
+    Paths,
+    /// A unit name, e.g. the slice of `Slice=`
+    Unit,
+    /// Space-separated unit names
+    Units,
+    /// A command line, with its optional prefixes
+    Command,
+}
+
+impl ValueType {
+    pub fn from_name(name: &str) -> Option<ValueType> {
+        match name {
+            "string" => Some(ValueType::String),
+            "boolean" => Some(ValueType::Boolean),
+            "enum" => Some(ValueType::Enum),
+            "timespan" => Some(ValueType::TimeSpan),
+            "size" => Some(ValueType::Size),
+            "integer" => Some(ValueType::Integer),
+            "limit" => Some(ValueType::Limit),
+            "path" => Some(ValueType::Path),
+            "paths" => Some(ValueType::Paths),
+            "unit" => Some(ValueType::Unit),
+            "units" => Some(ValueType::Units),
+            "command" => Some(ValueType::Command),
+            _ => None,
+        }
+    }
+}
+
+/// A value of a directive, whose support depends on the systemd version
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DocumentedValue {
+    pub value: String,
+    pub versions: Versions,
+}
+
+/// The systemd versions in which something appeared, got deprecated or was removed
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Versions {
+    pub introduced: Option<u32>,
+    pub deprecated: Option<u32>,
+    pub removed: Option<u32>,
+    /// What should be used instead, once deprecated or removed
+    pub replaced_by: Option<String>,
+}
+
+/// How a systemd version handles a directive or a value
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Support {
+    Supported,
+    /// Introduced in a later version
+    TooNew(u32),
+    Deprecated(u32),
+    Removed(u32),
+}
+
+impl Versions {
+    /// The support by the target version, the latest one when unknown: a directive
+    /// cannot be too new for it
+    pub fn support(&self, target: Option<u32>) -> Support {
+        let target = target.unwrap_or(u32::MAX);
+
+        match *self {
+            Versions { introduced: Some(version), .. } if version > target => Support::TooNew(version),
+            Versions { removed: Some(version), .. } if version <= target => Support::Removed(version),
+            Versions { deprecated: Some(version), .. } if version <= target => Support::Deprecated(version),
+            _ => Support::Supported,
+        }
+    }
+}
+
+// rustc does not provide `#[derive(RustcDecodable)]` anymore
+impl Decodable for DocumentedDirective {
+    fn decode<D: Decoder>(d: &mut D) -> Result<DocumentedDirective, D::Error> {
+        d.read_struct("DocumentedDirective", 13, |d| {
+            let value_type: String = d.read_struct_field("type", 4, Decodable::decode)?;
+            Ok(DocumentedDirective {
+                url: d.read_struct_field("url", 0, Decodable::decode)?,
+                field: d.read_struct_field("field", 1, Decodable::decode)?,
+                sections: d.read_struct_field("sections", 2, Decodable::decode)?,
+                list: d.read_struct_field::<Option<_>, _>("list", 3, Decodable::decode)?.unwrap_or(false),
+                value_type: ValueType::from_name(&value_type)
+                    .ok_or_else(|| d.error(&format!("unknown value type: {}", value_type)))?,
+                allowed: d.read_struct_field::<Option<_>, _>("allowed", 5, Decodable::decode)?.unwrap_or_default(),
+                versions: decode_versions(d, 6)?,
+                values: d.read_struct_field::<Option<_>, _>("values", 10, Decodable::decode)?.unwrap_or_default(),
+                parser: d.read_struct_field("parser", 11, Decodable::decode)?,
+                description: d.read_struct_field("description", 12, Decodable::decode)?,
+            })
+        })
+    }
+}
+
+impl Decodable for DocumentedValue {
+    fn decode<D: Decoder>(d: &mut D) -> Result<DocumentedValue, D::Error> {
+        d.read_struct("DocumentedValue", 5, |d| {
+            Ok(DocumentedValue {
+                value: d.read_struct_field("value", 0, Decodable::decode)?,
+                versions: decode_versions(d, 1)?,
+            })
+        })
+    }
+}
+
+/// The versions are optional fields, flattened in the directive or the value
+fn decode_versions<D: Decoder>(d: &mut D, first_idx: usize) -> Result<Versions, D::Error> {
+    Ok(Versions {
+        introduced: d.read_struct_field("introduced", first_idx, Decodable::decode)?,
+        deprecated: d.read_struct_field("deprecated", first_idx + 1, Decodable::decode)?,
+        removed: d.read_struct_field("removed", first_idx + 2, Decodable::decode)?,
+        replaced_by: d.read_struct_field("replaced_by", first_idx + 3, Decodable::decode)?,
+    })
+}
+
+/// The documented directives, indexed by name, by lowercase name and by section. A
+/// name may be documented by several man pages.
+pub struct DirectiveDatabase {
+    directives: HashMap<String, Vec<DocumentedDirective>>,
+    /// Every name, sorted
+    keys: Vec<String>,
+    by_lowercase_key: HashMap<String, Vec<String>>,
+    /// The names accepted by each category, sorted
+    by_section: HashMap<String, Vec<String>>,
+}
+
+impl DirectiveDatabase {
+    /// The database embedded in the binary, decoded on first use and shared by the
+    /// lints. Prefer it to `load`, which decodes the database again.
+    pub fn shared() -> &'static DirectiveDatabase {
+        static DATABASE: OnceLock<DirectiveDatabase> = OnceLock::new();
+        DATABASE.get_or_init(DirectiveDatabase::load)
+    }
+
+    pub fn load() -> DirectiveDatabase {
+        let vec: Vec<DocumentedDirective> = json::decode(DIRECTIVES).expect("json file should be ok");
+
+        let mut directives: HashMap<String, Vec<DocumentedDirective>> = HashMap::with_capacity(vec.len());
+        let mut by_lowercase_key: HashMap<String, Vec<String>> = HashMap::with_capacity(vec.len());
+        let mut by_section: HashMap<String, Vec<String>> = HashMap::new();
+
+        for directive in vec {
+            for section in &directive.sections {
+                by_section.entry(section.clone()).or_default().push(directive.field.clone());
+            }
+            let same_key = directives.entry(directive.field.clone()).or_default();
+            if same_key.is_empty() {
+                by_lowercase_key.entry(directive.field.to_lowercase()).or_default().push(directive.field.clone());
+            }
+            same_key.push(directive);
+        }
+
+        let mut keys: Vec<String> = directives.keys().cloned().collect();
+        keys.sort();
+        for keys in by_section.values_mut() {
+            keys.sort();
+            keys.dedup();
+        }
+
+        DirectiveDatabase { directives, keys, by_lowercase_key, by_section }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.directives.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> &[DocumentedDirective] {
+        self.directives.get(key).map_or(&[], |documented| &documented[..])
+    }
+
+    /// The names equal to `key` but for the case, e.g. `ExecStart` for `execstart`
+    pub fn find_ignore_case(&self, key: &str) -> Vec<&str> {
+        self.by_lowercase_key
+            .get(&key.to_lowercase())
+            .map_or(vec!(), |keys| keys.iter().map(|key| &key[..]).collect())
+    }
+
+    /// How the directive is documented for the category, if it is accepted there
+    pub fn documented_in(&self, key: &str, category: &str) -> Option<&DocumentedDirective> {
+        self.get(key).iter().find(|directive| directive.sections.iter().any(|section| section == category))
+    }
+
+    /// The categories accepting the directive, in the database order
+    pub fn sections(&self, key: &str) -> Vec<&str> {
+        let mut res: Vec<&str> = vec!();
+        for section in self.get(key).iter().flat_map(|documented| &documented.sections) {
+            if !res.contains(&&section[..]) {
+                res.push(section);
+            }
+        }
+        res
+    }
+
+    /// Tells if some directives are documented for the category
+    pub fn is_known_section(&self, category: &str) -> bool {
+        self.by_section.contains_key(category)
+    }
+
+    /// Every directive name, sorted
+    pub fn keys(&self) -> Vec<&str> {
+        self.keys.iter().map(|key| &key[..]).collect()
+    }
+
+    /// The directives accepted by the category, sorted
+    pub fn keys_of_section(&self, category: &str) -> Vec<&str> {
+        self.by_section
+            .get(category)
+            .map_or(vec!(), |keys| keys.iter().map(|key| &key[..]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn it_should_know_the_sections_of_a_directive() {
+        let db = DirectiveDatabase::shared();
+
+        assert_eq!(vec!["Unit"], db.sections("Description"));
+        assert_eq!(vec!["Install"], db.sections("WantedBy"));
+        assert_eq!(vec!["Service"], db.sections("ExecStart"));
+        assert_eq!(vec!["Service", "Socket", "Mount", "Swap"], db.sections("User"));
+    }
+
+    #[test]
+    fn it_should_only_know_the_directives_of_unit_files() {
+        let db = DirectiveDatabase::shared();
+
+        assert!(db.keys().iter().all(|key| !db.sections(key).is_empty()));
+        assert!(!db.contains("OWNER"));
+        assert!(!db.contains("$MAINPID"));
+    }
+
+    #[test]
+    fn it_should_merge_the_man_pages_documenting_a_directive() {
+        let db = DirectiveDatabase::shared();
+
+        assert_eq!(2, db.get("Type").len());
+        assert_eq!(vec!["Mount", "Service"], db.sections("Type"));
+    }
+
+    #[test]
+    fn it_should_know_the_sections() {
+        let db = DirectiveDatabase::shared();
+
+        assert!(db.is_known_section("Service"));
+        assert!(db.keys_of_section("Install").contains(&"WantedBy"));
+        assert!(!db.is_known_section("Match"));
+    }
+
+    #[test]
+    fn it_should_know_the_versions_of_directives_and_values() {
+        let db = DirectiveDatabase::shared();
+
+        let exec_condition = db.documented_in("ExecCondition", "Service").unwrap();
+        assert_eq!(Some(243), exec_condition.versions.introduced);
+
+        let memory_limit = db.documented_in("MemoryLimit", "Service").unwrap();
+        assert_eq!(Some(231), memory_limit.versions.deprecated);
+        assert_eq!(Some("MemoryMax".into()), memory_limit.versions.replaced_by);
+
+        let service_type = db.documented_in("Type", "Service").unwrap();
+        assert!(service_type.values.iter().any(|value| value.value == "exec" && value.versions.introduced == Some(240)));
+        assert!(db.documented_in("Type", "Mount").unwrap().values.is_empty());
+    }
+
+    #[test]
+    fn it_should_know_the_value_types() {
+        let db = DirectiveDatabase::shared();
+
+        let service_type = db.documented_in("Type", "Service").unwrap();
+        assert_eq!(ValueType::Enum, service_type.value_type);
+        assert!(service_type.allowed.contains(&"simple".into()));
+        assert_eq!(ValueType::String, db.documented_in("Type", "Mount").unwrap().value_type);
+        assert_eq!(ValueType::Boolean, db.documented_in("PrivateTmp", "Service").unwrap().value_type);
+        assert_eq!(ValueType::TimeSpan, db.documented_in("RestartSec", "Service").unwrap().value_type);
+        assert_eq!(ValueType::Units, db.documented_in("After", "Unit").unwrap().value_type);
+        assert_eq!(ValueType::Unit, db.documented_in("Slice", "Service").unwrap().value_type);
+        assert_eq!(ValueType::Unit, db.documented_in("Unit", "Timer").unwrap().value_type);
+    }
+
+    #[test]
+    fn it_should_know_the_list_directives() {
+        let db = DirectiveDatabase::shared();
+
+        assert!(db.documented_in("ExecStartPre", "Service").unwrap().list);
+        assert!(db.documented_in("ConditionPathExists", "Unit").unwrap().list);
+        assert!(!db.documented_in("User", "Service").unwrap().list);
+    }
+
+    #[test]
+    fn it_should_decode_the_fields_of_the_generated_entries() {
+        let input = r#"[{"url":"https://www.freedesktop.org/software/systemd/man/systemd.service.html#ExecStart=",
+            "field":"ExecStart","sections":["Service"],"list":true,"type":"command",
+            "parser":"config_parse_exec","description":"Commands that are executed when this service is started."}]"#;
+
+        let directive: DocumentedDirective = json::decode::<Vec<_>>(input).unwrap().remove(0);
+
+        assert_eq!(Some("config_parse_exec".into()), directive.parser);
+        assert_eq!(Some("Commands that are executed when this service is started.".into()), directive.description);
+    }
+
+    #[test]
+    fn it_should_look_up_keys_whatever_their_case() {
+        let db = DirectiveDatabase::shared();
+
+        assert_eq!(vec!["ExecStart"], db.find_ignore_case("execstart"));
+        assert_eq!(vec!["ExecStart"], db.find_ignore_case("ExecStart"));
+        assert!(db.find_ignore_case("ExecStarts").is_empty());
+    }
+
+    #[test]
+    fn the_shared_database_should_be_decoded_once() {
+        assert!(::std::ptr::eq(DirectiveDatabase::shared(), DirectiveDatabase::shared()));
+    }
+
+    #[test]
+    fn support_should_depend_on_the_target_version() {
+        let versions = Versions { introduced: Some(220), deprecated: Some(230), removed: Some(240), replaced_by: None };
+
+        assert_eq!(Support::TooNew(220), versions.support(Some(219)));
+        assert_eq!(Support::Supported, versions.support(Some(220)));
+        assert_eq!(Support::Deprecated(230), versions.support(Some(235)));
+        assert_eq!(Support::Removed(240), versions.support(Some(240)));
+        assert_eq!(Support::Removed(240), versions.support(None));
+        assert_eq!(Support::Supported, Versions::default().support(Some(1)));
+    }
+}
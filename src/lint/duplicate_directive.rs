@@ -0,0 +1,154 @@
+
+use lint::*;
+use lint::directives::DirectiveDatabase;
+use systemd_parser::items::*;
+
+/// The assignments which compete for the directive: the ones after the last empty
+/// assignment, which resets the directive
+fn competing(entries: &[UnitDirective]) -> &[UnitDirective] {
+
+    let after_reset = entries
+        .iter()
+        .rposition(|entry| entry.value().is_none())
+        .map_or(0, |idx| idx + 1);
+
+    &entries[after_reset..]
+}
+
+/// `Type=simple (line 3)`, without the line when the unit was not parsed from a source
+fn occurrence(entry: &UnitDirective) -> String {
+    let value = entry.value().unwrap_or_default();
+    match entry.line() {
+        Some(line) => format!("{}={} (line {})", entry.key(), value, line),
+        None => format!("{}={}", entry.key(), value),
+    }
+}
+
+pub struct DuplicateDirective;
+
+impl Lint for DuplicateDirective {
+    fn code(&self) -> LintCode { LintCode::WarnDuplicateDirective }
+    fn name(&self) -> &'static str { "duplicate-directive" }
+    fn rationale(&self) -> &'static str {
+        "When a directive which holds a single value is set several times, systemd silently \
+         keeps the last value. This often hides a copy-paste mistake. Directives holding a \
+         list, such as After= or ExecStartPre=, may be repeated; an empty assignment resets \
+         a directive and is not a duplicate."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        for entry in unit.keys() {
+            let entries = match *entry {
+                DirectiveEntry::Many(ref entries) => entries,
+                DirectiveEntry::Solo(_) => continue,
+            };
+            let key = entry.key();
+            let cat = entry.category();
+
+            // unknown directives and directives in the wrong category have their own lints
+            match directives.documented_in(&key, &cat) {
+                Some(documented) if !documented.list && !ctx.is_extension(entry) => {}
+                _ => continue,
+            }
+
+            let competing = competing(entries);
+            let (winner, others) = match competing.split_last() {
+                Some((winner, others)) if !others.is_empty() => (winner.value().unwrap_or_default(), others),
+                _ => continue,
+            };
+
+            let occurrences: Vec<String> = competing.iter().map(occurrence).collect();
+            sink.report(LintResult {
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "{}= is set {} times in [{}]: {}. systemd keeps the last one, {}",
+                    key, competing.len(), cat, occurrences.join(", then "), occurrences[others.len()],
+                ),
+                code: LintCode::WarnDuplicateDirective,
+                rule: None,
+                location: Location::of(entry),
+                fixes: vec![Fix {
+                    description: format!("Keep only {}={}, remove {}", key, winner, occurrences[..others.len()].join(", ")),
+                    safe: false,
+                    edits: vec![
+                        Edit::Remove { category: cat.clone(), key: key.clone() },
+                        Edit::Insert { category: cat.clone(), key: key.clone(), value: winner.to_string() },
+                    ],
+                }],
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        After=network.target
+        After=local-fs.target
+        ConditionPathExists=/etc/foo
+        ConditionPathExists=/etc/bar
+        [Service]
+        Type=simple
+        ExecStartPre=/bin/true
+        ExecStartPre=/bin/false
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DuplicateDirective.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case() {
+    // arrange
+    let input = "
+        [Service]
+        Type=simple
+        User=nobody
+        ExecStart=/bin/true
+        Type=forking
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DuplicateDirective.run(&unit).remove(0);
+    // assert
+    assert_eq!(
+        "Type= is set 2 times in [Service]: Type=simple (line 3), then Type=forking (line 6). systemd keeps the last one, Type=forking (line 6)",
+        res.message
+    );
+    assert_eq!(vec![
+        Edit::Remove { category: "Service".into(), key: "Type".into() },
+        Edit::Insert { category: "Service".into(), key: "Type".into(), value: "forking".into() },
+    ], res.fixes()[0].edits);
+    assert!(!res.fixes()[0].safe)
+}
+
+#[test]
+fn success_case_empty_assignment_resets_the_directive() {
+    // arrange
+    let input = "
+        [Service]
+        User=root
+        User=
+        User=nobody
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DuplicateDirective.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
@@ -0,0 +1,183 @@
+
+use lint::*;
+use suggest::{closest, did_you_mean};
+use support::*;
+use systemd_parser::condition::*;
+use systemd_parser::items::*;
+
+/// The arguments a kind of condition accepts, when they are a closed list
+fn known_arguments(kind: ConditionKind) -> Option<&'static [&'static str]> {
+    match kind {
+        ConditionKind::Architecture => Some(ARCHITECTURES),
+        ConditionKind::Virtualization => Some(VIRTUALIZATIONS),
+        ConditionKind::Security => Some(SECURITY_FRAMEWORKS),
+        _ => None,
+    }
+}
+
+fn fixes(entry: &DirectiveEntry, value: &str, condition: &Condition, suggestions: &[&str]) -> Vec<Fix> {
+
+    // keep the `|` and `!` prefixes
+    let value = value.trim();
+    let prefix = &value[..value.len() - condition.argument.len()];
+
+    suggestions
+        .iter()
+        .map(|suggestion| Fix {
+            description: format!("Replace {} by {}", condition.argument, suggestion),
+            // the condition fails on the misspelled argument: fixing it may start the unit
+            safe: false,
+            edits: vec![Edit::ReplaceValue {
+                category: entry.category(),
+                key: entry.key(),
+                old_value: value.into(),
+                new_value: format!("{}{}", prefix, suggestion),
+            }],
+        })
+        .collect()
+}
+
+pub struct InvalidCondition;
+
+impl Lint for InvalidCondition {
+    fn code(&self) -> LintCode { LintCode::ErrorInvalidCondition }
+    fn name(&self) -> &'static str { "invalid-condition" }
+    fn rationale(&self) -> &'static str {
+        "A condition with a meaningless argument never holds, so the unit is silently \
+         skipped, and such an assertion makes the unit fail."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+    fn categories(&self) -> &'static [&'static str] { &["Unit"] }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        for entry in unit.keys() {
+            let key = entry.key();
+            if !Condition::is_condition_key(&key) {
+                continue;
+            }
+
+            for value in entry.values() {
+                // unknown conditions are reported as unknown directives
+                let condition = match Condition::parse(&key, value) {
+                    Ok(condition) => condition,
+                    Err(_) => continue,
+                };
+
+                if let Err(err) = condition.validate() {
+                    let suggestions = known_arguments(condition.kind)
+                        .map_or(vec!(), |known| closest(&condition.argument, known.iter().cloned()));
+
+                    sink.report(LintResult {
+                        severity: LintSeverity::Error,
+                        message: format!("Invalid {}={}: {}{}", key, value, err, did_you_mean(&suggestions)),
+                        code: LintCode::ErrorInvalidCondition,
+                        rule: None,
+                        location: Location::of(entry),
+                        fixes: fixes(entry, value, &condition, &suggestions),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Unit]
+        ConditionVirtualization=!container
+        ConditionPathExists=|!/lib/udev/hwdb.bin
+        ConditionPathExists=|/etc/udev/hwdb.bin
+        AssertKernelVersion=>=4.10
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn success_case_empty_value_resets_the_conditions() {
+    // arrange
+    let input = "
+        [Unit]
+        ConditionPathExists=
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit);
+    // assert
+    assert!(res.is_empty())
+}
+
+#[test]
+fn error_case_relative_path() {
+    // arrange
+    let input = "
+        [Unit]
+        ConditionPathExists=!etc/fstab
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit).remove(0);
+    // assert
+    assert!(res.message.contains("ConditionPathExists"))
+}
+
+#[test]
+fn error_case_unknown_virtualization() {
+    // arrange
+    let input = "
+        [Unit]
+        AssertVirtualization=virtualbox
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit);
+    // assert
+    assert!(!res.is_empty())
+}
+
+#[test]
+fn error_case_every_invalid_condition_is_reported() {
+    // arrange
+    let input = "
+        [Unit]
+        ConditionPathExists=etc/fstab
+        ConditionPathExists=/etc/hosts
+        ConditionPathExists=etc/hosts
+        ConditionArchitecture=amd64
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit);
+    // assert
+    assert_eq!(3, res.len())
+}
+
+#[test]
+fn error_case_suggests_the_closest_argument() {
+    // arrange
+    let input = "
+        [Unit]
+        ConditionArchitecture=|!x86_64
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidCondition.run(&unit).remove(0);
+    // assert
+    assert!(res.message.ends_with("(did you mean x86-64?)"), "{}", res.message);
+    assert_eq!(vec![Edit::ReplaceValue {
+        category: "Unit".into(),
+        key: "ConditionArchitecture".into(),
+        old_value: "|!x86_64".into(),
+        new_value: "|!x86-64".into(),
+    }], res.fixes()[0].edits);
+}
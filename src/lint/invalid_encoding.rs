@@ -0,0 +1,65 @@
+
+use lint::*;
+use systemd_parser::decode::{DecodeDiagnostic, DecodeIssue};
+use systemd_parser::items::*;
+
+pub struct InvalidEncoding;
+
+impl Lint for InvalidEncoding {
+    fn code(&self) -> LintCode { LintCode::WarnInvalidEncoding }
+    fn name(&self) -> &'static str { "invalid-encoding" }
+    fn rationale(&self) -> &'static str {
+        "systemd reads units as UTF-8 text and ignores the assignments which are not valid \
+         UTF-8. NUL bytes and carriage returns out of CRLF line endings do not show in most \
+         editors, yet change what systemd reads. Such bytes are usually left by a copy from \
+         another system or by a broken tool."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    // the registry reports the decoding problems, as the lints only see the decoded text
+    fn check(&self, _unit: &SystemdUnit, _ctx: &LintContext, _sink: &mut dyn LintSink) {}
+}
+
+/// Report what was wrong with the bytes of the unit, see `systemd_parser::decode`
+pub fn report_diagnostics(diagnostics: &[DecodeDiagnostic], sink: &mut dyn LintSink) {
+
+    for diagnostic in diagnostics {
+        let message = match diagnostic.issue {
+            DecodeIssue::InvalidUtf8 { len } => {
+                format!("Invalid UTF-8 sequence of {} byte(s) at byte {}", len, diagnostic.offset)
+            }
+            DecodeIssue::NulByte => format!("NUL byte at byte {}", diagnostic.offset),
+            DecodeIssue::StrayCarriageReturn => {
+                format!("Carriage return out of a CRLF line ending at byte {}", diagnostic.offset)
+            }
+        };
+
+        sink.report(LintResult {
+            severity: LintSeverity::Warning,
+            message,
+            code: LintCode::WarnInvalidEncoding,
+            rule: None,
+            location: Location::Unit,
+            fixes: vec!(),
+        });
+    }
+}
+
+#[test]
+fn it_should_report_each_decoding_problem() {
+    // arrange
+    let input = b"[Unit]\nDescription=caf\xE9\0\r\n[Service]\rExecStart=/bin/true\n";
+    let decoded = ::systemd_parser::decode::decode(input);
+    // act
+    let mut findings = vec!();
+    report_diagnostics(decoded.diagnostics(), &mut findings);
+    // assert
+    let messages: Vec<&str> = findings.iter().map(|finding| finding.message()).collect();
+    assert_eq!(vec![
+        "Invalid UTF-8 sequence of 1 byte(s) at byte 22",
+        "NUL byte at byte 23",
+        "Carriage return out of a CRLF line ending at byte 35",
+    ], messages);
+    assert!(findings.iter().all(|finding| finding.code() == LintCode::WarnInvalidEncoding));
+}
@@ -0,0 +1,222 @@
+
+use lint::*;
+use support::*;
+use systemd_parser::items::*;
+use systemd_parser::socket_address::SocketAddress;
+
+static ADDRESS_DIRECTIVES: &[&str] = &["ListenStream", "ListenDatagram", "ListenSequentialPacket", "ListenNetlink"];
+
+fn addresses(entry: &DirectiveEntry) -> Vec<(&str, Result<SocketAddress, String>)> {
+    entry.values()
+        .into_iter()
+        .map(|value| match &entry.key()[..] {
+            "ListenNetlink" => (value, SocketAddress::parse_netlink(value)),
+            // a FIFO takes a path in the file system, as a unix socket does
+            "ListenFIFO" => (value, Ok(SocketAddress::Unix(value.trim().into()))),
+            _ => (value, SocketAddress::parse(value)),
+        })
+        .collect()
+}
+
+/// The socket type an address is bound for: the paths and the abstract names are
+/// shared by every socket type and by the FIFOs, while a TCP and a UDP socket may
+/// listen on the same port
+fn socket_type<'a>(key: &'a str, address: &SocketAddress) -> &'a str {
+    match *address {
+        SocketAddress::Unix(_) | SocketAddress::Abstract(_) => "unix",
+        _ => key,
+    }
+}
+
+pub struct InvalidListenAddress;
+
+impl Lint for InvalidListenAddress {
+    fn code(&self) -> LintCode { LintCode::ErrorInvalidListenAddress }
+    fn name(&self) -> &'static str { "invalid-listen-address" }
+    fn rationale(&self) -> &'static str {
+        "systemd refuses to start a socket unit with an address it can't parse."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+    fn categories(&self) -> &'static [&'static str] { &["Socket"] }
+    // drop-ins may extend a socket
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(&[UnitType::Socket, UnitType::DropIn]) }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        for key in ADDRESS_DIRECTIVES {
+            let entry = match unit.lookup_by_key(key) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            for (value, parsed) in addresses(entry) {
+                if let Err(err) = parsed {
+                    sink.report(LintResult {
+                        severity: LintSeverity::Error,
+                        message: format!("Invalid address in {}={}: {}", key, value, err),
+                        code: LintCode::ErrorInvalidListenAddress,
+                        rule: None,
+                        location: Location::of(entry),
+                        fixes: vec!(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+pub struct DuplicateListenAddress;
+
+impl Lint for DuplicateListenAddress {
+    fn code(&self) -> LintCode { LintCode::ErrorDuplicateListenAddress }
+    fn name(&self) -> &'static str { "duplicate-listen-address" }
+    fn rationale(&self) -> &'static str {
+        "Listening twice on the same address makes the socket unit fail with \"Address \
+         already in use\". A path can't be bound by two directives either, even for \
+         different socket types, or by a socket and a FIFO."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+    fn categories(&self) -> &'static [&'static str] { &["Socket"] }
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(&[UnitType::Socket, UnitType::DropIn]) }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let mut seen: Vec<(&str, &str, SocketAddress)> = vec!();
+        for key in ADDRESS_DIRECTIVES.iter().chain(&["ListenFIFO"]) {
+            let entry = match unit.lookup_by_key(key) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            for (value, parsed) in addresses(entry) {
+                // invalid addresses are reported by InvalidListenAddress
+                let address = match parsed {
+                    Ok(address) => address,
+                    Err(_) => continue,
+                };
+
+                let bound = seen.iter().find(|&&(other_key, _, ref other)| {
+                    socket_type(other_key, other) == socket_type(key, &address) && other.conflicts_with(&address)
+                });
+                if let Some(&(other_key, other_value, _)) = bound {
+                    let message = if other_key == *key {
+                        format!("{}={} is bound more than once", key, value)
+                    } else {
+                        format!("{}={} is already bound by {}={}", key, value, other_key, other_value)
+                    };
+                    sink.report(LintResult {
+                        severity: LintSeverity::Error,
+                        message,
+                        code: LintCode::ErrorDuplicateListenAddress,
+                        rule: None,
+                        location: Location::of(entry),
+                        fixes: vec!(),
+                    });
+                }
+                seen.push((key, value, address));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenStream=/var/run/docker.sock
+        ListenStream=127.0.0.1:2375
+        ListenDatagram=2375
+        ListenNetlink=kobject-uevent 1
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidListenAddress.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn success_case_not_a_socket() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidListenAddress.applies_to(&unit);
+    // assert
+    assert!(!res)
+}
+
+#[test]
+fn error_case_invalid_address() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenStream=localhost:22
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidListenAddress.run(&unit).remove(0);
+    // assert
+    assert_eq!(LintCode::ErrorInvalidListenAddress, res.code);
+    assert!(res.message.contains("localhost:22"))
+}
+
+#[test]
+fn error_case_invalid_netlink_family() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenNetlink=kobject 1
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidListenAddress.run(&unit).remove(0);
+    // assert
+    assert_eq!(LintCode::ErrorInvalidListenAddress, res.code)
+}
+
+#[test]
+fn error_case_same_address_twice() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenStream=22
+        ListenStream=0.0.0.0:22
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DuplicateListenAddress.run(&unit).remove(0);
+    // assert
+    assert_eq!(LintCode::ErrorDuplicateListenAddress, res.code)
+}
+
+#[test]
+fn error_case_same_path_for_two_socket_types() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenStream=/run/foo.sock
+        ListenStream=2375
+        ListenDatagram=2375
+        ListenSequentialPacket=/run/foo.sock
+        ListenFIFO=/run/foo.sock
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = DuplicateListenAddress.run(&unit);
+    // assert
+    let messages: Vec<&str> = res.iter().map(|finding| finding.message()).collect();
+    assert_eq!(vec![
+        "ListenSequentialPacket=/run/foo.sock is already bound by ListenStream=/run/foo.sock",
+        "ListenFIFO=/run/foo.sock is already bound by ListenStream=/run/foo.sock",
+    ], messages)
+}
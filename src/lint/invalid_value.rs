@@ -0,0 +1,290 @@
+
+use lint::*;
+use lint::directives::{DirectiveDatabase, DocumentedDirective, ValueType};
+use suggest::{closest, did_you_mean};
+use support::*;
+use systemd_parser::items::*;
+use systemd_parser::values::*;
+
+/// Prefixes of the command lines, e.g. `-` ignores the exit code
+static COMMAND_PREFIXES: &[char] = &['@', '-', ':', '+', '!', '|'];
+
+/// Check the value against the type of the directive, and describe what is wrong
+fn validate(value: &str, documented: &DocumentedDirective) -> Result<(), String> {
+
+    let allowed = documented.allowed.iter().any(|allowed| allowed == value);
+
+    match documented.value_type {
+        ValueType::String => Ok(()),
+        ValueType::Boolean if allowed => Ok(()),
+        ValueType::Boolean => parse_boolean(value).map(|_| ()).map_err(|_| {
+            let mut expected: Vec<&str> = vec!["yes", "no", "true", "false", "on", "off", "1", "0"];
+            expected.extend(documented.allowed.iter().map(|allowed| &allowed[..]));
+            format!("expected a boolean ({})", join_alternatives(&expected))
+        }),
+        ValueType::Enum if allowed => Ok(()),
+        ValueType::Enum => {
+            let expected: Vec<&str> = documented.allowed.iter().map(|allowed| &allowed[..]).collect();
+            Err(format!("expected {}", join_alternatives(&expected)))
+        }
+        ValueType::TimeSpan => parse_time_span(value).map(|_| ()),
+        ValueType::Size => parse_size(value).map(|_| ()),
+        ValueType::Integer => value.parse::<i64>().map(|_| ()).map_err(|_| "expected an integer".into()),
+        ValueType::Limit => validate_limit(value),
+        ValueType::Path => validate_path(value),
+        ValueType::Paths => value.split_whitespace().try_for_each(|path| validate_path(path.trim_start_matches(&['-', '+'][..]))),
+        ValueType::Unit => validate_unit_name(value),
+        ValueType::Units => value.split_whitespace().try_for_each(validate_unit_name),
+        ValueType::Command => validate_command(value),
+    }
+}
+
+/// `a, b or c`
+fn join_alternatives(words: &[&str]) -> String {
+    match words.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.to_string(),
+        Some((last, others)) => format!("{} or {}", others.join(", "), last),
+    }
+}
+
+/// A soft limit and an optional hard limit, each a number, a size, a time span or `infinity`
+fn validate_limit(value: &str) -> Result<(), String> {
+
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() > 2 {
+        return Err("expected a soft limit and an optional hard limit, as soft:hard".into());
+    }
+
+    for part in parts {
+        if parse_size(part).is_err() && parse_time_span(part).is_err() {
+            return Err(format!("\"{}\" is not a number, a size, a time span or infinity", part));
+        }
+    }
+
+    Ok(())
+}
+
+/// An absolute path, maybe starting with a specifier such as `%h`, or `~` for the
+/// home directory. `-` ignores a missing file.
+fn validate_path(value: &str) -> Result<(), String> {
+
+    let path = value.trim_start_matches('-');
+    if path.starts_with('/') || path.starts_with('%') || path == "~" || path.starts_with("~/") {
+        Ok(())
+    } else {
+        Err(format!("\"{}\" is not an absolute path", path))
+    }
+}
+
+fn validate_unit_name(name: &str) -> Result<(), String> {
+    if is_unit_name(name) {
+        Ok(())
+    } else {
+        Err(format!("\"{}\" is not a unit name, such as foo.service", name))
+    }
+}
+
+/// A command, after its prefixes, with balanced quotes
+fn validate_command(value: &str) -> Result<(), String> {
+
+    let command = value.trim_start_matches(COMMAND_PREFIXES).trim_start();
+    if command.is_empty() {
+        return Err("missing command".into());
+    }
+
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => { chars.next(); }
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            _ => {}
+        }
+    }
+
+    match quote {
+        Some(open) => Err(format!("unbalanced {} quote", open)),
+        None => Ok(()),
+    }
+}
+
+/// Enumerations only: replace the value by the closest allowed one, when there is
+/// no doubt. Not safe, even when only the case changes: systemd ignores the
+/// misspelled value, so the fix changes how the unit runs.
+fn fixes(entry: &DirectiveEntry, value: &str, suggestions: &[&str]) -> Vec<Fix> {
+
+    match *suggestions {
+        [suggestion] => vec![Fix {
+            description: format!("Replace {}={} by {}={}", entry.key(), value, entry.key(), suggestion),
+            safe: false,
+            edits: vec![Edit::ReplaceValue {
+                category: entry.category(),
+                key: entry.key(),
+                old_value: value.into(),
+                new_value: suggestion.into(),
+            }],
+        }],
+        _ => vec!(),
+    }
+}
+
+pub struct InvalidValue;
+
+impl Lint for InvalidValue {
+    fn code(&self) -> LintCode { LintCode::ErrorInvalidValue }
+    fn name(&self) -> &'static str { "invalid-value" }
+    fn rationale(&self) -> &'static str {
+        "systemd ignores a directive whose value it can't parse, with a mere warning in the \
+         journal, e.g. Type=Simple (values are case-sensitive), Restart=sometimes or \
+         PrivateTmp=maybe. The values are checked against the type of the directive: \
+         enumeration, boolean, time span, size, path, unit names, command line, ..."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        for entry in unit.keys() {
+            // directives in the wrong category are reported by DirectiveInWrongCategory
+            let documented = match directives.documented_in(&entry.key(), &entry.category()) {
+                Some(documented) if !ctx.is_extension(entry) => documented,
+                _ => continue,
+            };
+
+            for value in entry.values() {
+                if let Err(err) = validate(value, documented) {
+                    let suggestions = match documented.value_type {
+                        ValueType::Enum | ValueType::Boolean => {
+                            closest(value, documented.allowed.iter().map(|allowed| &allowed[..]))
+                        }
+                        _ => vec!(),
+                    };
+
+                    sink.report(LintResult {
+                        severity: LintSeverity::Error,
+                        message: format!("Invalid value in {}={}: {}{}", entry.key(), value, err, did_you_mean(&suggestions)),
+                        code: LintCode::ErrorInvalidValue,
+                        rule: None,
+                        location: Location::of(entry),
+                        fixes: fixes(entry, value, &suggestions),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[cfg(test)]
+fn messages(findings: &[LintResult]) -> Vec<&str> {
+    findings.iter().map(|finding| &finding.message[..]).collect()
+}
+
+#[test]
+fn success_case() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        After=network-online.target getty@tty1.service
+        [Service]
+        Type=simple
+        ExecStart=-/usr/bin/foo --name \"a b\"
+        Restart=on-failure
+        RestartSec=1min 30s
+        PrivateTmp=Yes
+        ProtectSystem=strict
+        MemoryMax=1G 512M
+        TasksMax=50%
+        Nice=-5
+        LimitNOFILE=1024:infinity
+        LimitSTACK=8192B
+        WorkingDirectory=~
+        EnvironmentFile=-/etc/default/foo
+        ReadWritePaths=-/var/lib/foo +/run/foo
+        Slice=system-foo.slice
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidValue.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_enumerations_are_case_sensitive() {
+    // arrange
+    let input = "
+        [Service]
+        Type=Simple
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidValue.run(&unit).remove(0);
+    // assert
+    assert_eq!(
+        "Invalid value in Type=Simple: expected simple, exec, forking, oneshot, dbus, notify, notify-reload or idle (did you mean simple?)",
+        res.message
+    );
+    assert!(!res.fixes()[0].safe);
+    assert_eq!(vec![Edit::ReplaceValue {
+        category: "Service".into(),
+        key: "Type".into(),
+        old_value: "Simple".into(),
+        new_value: "simple".into(),
+    }], res.fixes()[0].edits)
+}
+
+#[test]
+fn error_case_every_type_has_a_precise_message() {
+    // arrange
+    let input = "
+        [Unit]
+        After=network
+        [Service]
+        ExecStart=/bin/echo \"unterminated
+        MemoryMax=1GB
+        PIDFile=run/foo.pid
+        PrivateTmp=maybe
+        Restart=sometimes
+        RestartSec=5 minuts
+        Slice=system foo.slice
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidValue.run(&unit);
+    // assert
+    assert_eq!(vec![
+        "Invalid value in After=network: \"network\" is not a unit name, such as foo.service",
+        "Invalid value in ExecStart=/bin/echo \"unterminated: unbalanced \" quote",
+        "Invalid value in MemoryMax=1GB: unknown size suffix \"GB\", expected B, K, M, G, T, P or E",
+        "Invalid value in PIDFile=run/foo.pid: \"run/foo.pid\" is not an absolute path",
+        "Invalid value in PrivateTmp=maybe: expected a boolean (yes, no, true, false, on, off, 1 or 0)",
+        "Invalid value in Restart=sometimes: expected no, on-success, on-failure, on-abnormal, on-watchdog, on-abort or always",
+        "Invalid value in RestartSec=5 minuts: unknown time unit \"minuts\"",
+        "Invalid value in Slice=system foo.slice: \"system foo.slice\" is not a unit name, such as foo.service",
+    ], messages(&res))
+}
+
+#[test]
+fn success_case_extensions_and_other_categories_are_not_checked() {
+    // arrange
+    let input = "
+        [Mount]
+        Type=ext4
+        [X-Foo]
+        Restart=sometimes
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = InvalidValue.run(&unit);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
@@ -2,23 +2,46 @@
 use lint::*;
 use systemd_parser::items::*;
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
+pub struct MissingDescription;
 
-    let error = Err(LintResult {
-        severity: LintSeverity::Lint,
-        message: "Consider filling the Description= field".into(),
-        code: LintCode::LintMissingDescription,
-    });
+impl Lint for MissingDescription {
+    fn code(&self) -> LintCode { LintCode::LintMissingDescription }
+    fn name(&self) -> &'static str { "missing-description" }
+    fn rationale(&self) -> &'static str {
+        "Description= is what systemctl and the journal show about the unit. Without it, \
+         the unit is only known by its file name."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Lint }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Style] }
+    fn categories(&self) -> &'static [&'static str] { &["Unit"] }
+    // drop-ins get their description from the unit they extend
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(COMPLETE_UNIT_TYPES) }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
 
-    match unit.lookup_by_key("Description") {
-        None => error,
-        Some(&DirectiveEntry::Solo(ref entry)) if entry.value().is_none() => error,
-        _ => Ok(()),
+        let is_missing = match unit.lookup_by_key("Description") {
+            None => true,
+            Some(DirectiveEntry::Solo(entry)) => entry.value().is_none(),
+            _ => false,
+        };
+
+        if is_missing {
+            sink.report(LintResult {
+                severity: LintSeverity::Lint,
+                message: "Consider filling the Description= field".into(),
+                code: LintCode::LintMissingDescription,
+                rule: None,
+                location: Location::Category("Unit".into()),
+                fixes: vec!(),
+            });
+        }
     }
 }
 
 #[cfg(test)]
 use systemd_parser;
+#[cfg(test)]
+use std::path::Path;
 
 #[test]
 fn success_case() {
@@ -31,9 +54,9 @@ fn success_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = MissingDescription.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
 }
 
 #[test]
@@ -45,9 +68,9 @@ fn error_case_missing_directive() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = MissingDescription.run(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res.is_empty());
 }
 
 #[test]
@@ -61,7 +84,21 @@ fn error_case_missing_value() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = MissingDescription.run(&unit);
+    // assert
+    assert!(!res.is_empty());
+}
+
+#[test]
+fn success_case_drop_in() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("sshd.service.d/override.conf"), input).unwrap();
+    // act
+    let res = MissingDescription.applies_to(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res)
 }
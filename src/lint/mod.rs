@@ -1,6 +1,9 @@
+use std::fmt;
+use std::time::{Duration, Instant};
 
-#[allow(dead_code)]
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+
+/// Ordered from the least to the most severe
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
 pub enum LintSeverity {
     Ignore,
     Lint,
@@ -14,32 +17,845 @@ pub struct LintResult {
     severity: LintSeverity,
     message: String,
     code: LintCode,
+    /// The custom rule or the diagnostic of a plugin which reported the finding, e.g.
+    /// `deployed-services-user` or `house-rules/x-owner`
+    rule: Option<String>,
+    location: Location,
+    fixes: Vec<Fix>,
+}
+
+/// A change to the unit which addresses a finding
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Fix {
+    pub description: String,
+    /// A safe fix keeps the behaviour of the unit, the others have to be reviewed.
+    /// Fixing a misspelling is not safe: systemd ignores or rejects what is
+    /// misspelled, so the fix turns on a setting which had no effect.
+    pub safe: bool,
+    pub edits: Vec<Edit>,
 }
 
+/// An edit of the unit, in terms of its directives. Edits of a directive apply to
+/// every occurrence of its key in the category.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Edit {
+    /// Replace the value of the occurrences of the key which have the old value
+    ReplaceValue { category: String, key: String, old_value: String, new_value: String },
+    RenameKey { category: String, key: String, new_key: String },
+    /// Rename every header of the category
+    RenameCategory { category: String, new_category: String },
+    Remove { category: String, key: String },
+    /// Append a directive at the end of the category, which is added when missing
+    Insert { category: String, key: String, value: String },
+}
+
+/// What a finding is about, used to match the suppression comments
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Location {
+    Unit,
+    Category(String),
+    Directive { category: String, key: String },
+}
+
+impl Location {
+    pub fn of(entry: &DirectiveEntry) -> Location {
+        Location::Directive {
+            category: entry.category(),
+            key: entry.key(),
+        }
+    }
+
+    pub fn category(&self) -> Option<&str> {
+        match *self {
+            Location::Unit => None,
+            Location::Category(ref category) => Some(category),
+            Location::Directive { ref category, .. } => Some(category),
+        }
+    }
+}
+
+/// `[Service] ExecStart=`, `[Service]`, or nothing for the whole unit
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Location::Unit => Ok(()),
+            Location::Category(ref category) => write!(f, "[{}]", category),
+            Location::Directive { ref category, ref key } => write!(f, "[{}] {}=", category, key),
+        }
+    }
+}
+
+/// Where the lints report their findings
+pub trait LintSink {
+    fn report(&mut self, result: LintResult);
+    /// Told how long each lint took on the unit, once it ran, for `--stats`
+    fn timed(&mut self, _lint: &dyn Lint, _elapsed: Duration) {}
+}
+
+/// What a lint is concerned with, to select lints by concern
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LintGroup {
+    /// The unit does not do what it says
+    Correctness,
+    Security,
+    /// The unit does not work on every systemd version
+    Portability,
+    Style,
+    Performance,
+    /// Opinionated, may be noisy
+    Pedantic,
+}
+
+pub static LINT_GROUPS: &[LintGroup] = &[
+    LintGroup::Correctness, LintGroup::Security, LintGroup::Portability, LintGroup::Style,
+    LintGroup::Performance, LintGroup::Pedantic,
+];
+
+impl LintGroup {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LintGroup::Correctness => "correctness",
+            LintGroup::Security => "security",
+            LintGroup::Portability => "portability",
+            LintGroup::Style => "style",
+            LintGroup::Performance => "performance",
+            LintGroup::Pedantic => "pedantic",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<LintGroup> {
+        LINT_GROUPS.iter().find(|group| group.name() == name).cloned()
+    }
+}
+
+impl LintSeverity {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LintSeverity::Ignore => "ignore",
+            LintSeverity::Lint => "lint",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<LintSeverity> {
+        match name {
+            "ignore" => Some(LintSeverity::Ignore),
+            "lint" => Some(LintSeverity::Lint),
+            "warning" => Some(LintSeverity::Warning),
+            "error" => Some(LintSeverity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl LintResult {
+    pub fn severity(&self) -> LintSeverity {
+        self.severity
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn code(&self) -> LintCode {
+        self.code
+    }
+
+    /// The id the settings and the suppression comments refer to the finding by,
+    /// along with its code, e.g. `deployed-services-user` or `house-rules/x-owner`.
+    /// `None` for the built-in lints.
+    pub fn rule(&self) -> Option<&str> {
+        self.rule.as_ref().map(|rule| &rule[..])
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+}
+
+impl LintSink for Vec<LintResult> {
+    fn report(&mut self, result: LintResult) {
+        self.push(result)
+    }
+}
+
+/// The codes are numbered by hand rather than in declaration order: they are written
+/// in baselines, suppression comments and configurations, so a code never changes
+/// once released. The first digit tells the default severity: 2 for lints, 3 for
+/// warnings and 4 for errors. The custom rules, whose severity is the one of each
+/// rule, start with 5.
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum LintCode {
     LintMissingDescription                              = 20_000,
     WarnServiceTypeShouldAlwaysBeExplicit               = 30_000,
+    WarnUnusedSuppression                               = 30_001,
+    WarnDeprecatedDirective                             = 30_002,
+    WarnDuplicateDirective                              = 30_003,
+    WarnPluginFinding                                   = 30_004,
+    WarnInvalidEncoding                                 = 30_005,
     ErrorServiceSimpleMustHaveExecstart                 = 40_000,
     ErrorUnknownDirective                               = 40_001,
     ErrorUnknownCategory                                = 40_002,
     ErrorMissingBusNameDirectiveInDBusService           = 40_003,
+    ErrorInvalidListenAddress                           = 40_004,
+    ErrorDuplicateListenAddress                         = 40_005,
+    ErrorInvalidCondition                               = 40_006,
+    ErrorDirectiveInWrongCategory                       = 40_007,
+    ErrorUnsupportedDirective                           = 40_008,
+    ErrorInvalidValue                                   = 40_009,
+    ErrorPluginFailure                                  = 40_010,
+    CustomRule                                          = 50_000,
 }
 
+mod directives;
+
 mod lint_missing_description;
 mod service_type_always_explicit;
 mod service_execstart_not_set;
 mod unknown_directive;
 mod unknown_category;
 mod dbus_missing_bus_name_directive;
+mod invalid_listen_address;
+mod invalid_condition;
+mod unused_suppression;
+mod directive_in_wrong_category;
+mod systemd_version;
+mod invalid_value;
+mod duplicate_directive;
+mod invalid_encoding;
+mod plugin;
+mod custom_rule;
+mod result_json;
+
+pub use self::custom_rule::{CustomRule, RuleCheck};
+pub use self::plugin::Plugin;
+pub use self::plugin::DEFAULT_TIMEOUT as DEFAULT_PLUGIN_TIMEOUT;
+pub use self::directives::DIRECTIVES as DIRECTIVE_DATABASE;
+
+use std::path::Path;
+use systemd_parser::decode::DecodeDiagnostic;
+use systemd_parser::items::{DirectiveEntry, SystemdUnit, UnitType};
+
+/// Where the built-in lints are documented, one section per lint
+pub static LINTS_DOCS_URL: &str = "https://github.com/mackwic/systemd-linter/blob/master/docs/lints.md";
+
+/// Decode the directive database the lints share, which happens on first use
+/// otherwise, and is then accounted to the first lint which uses it
+pub fn preload_directives() {
+    directives::DirectiveDatabase::shared();
+}
+
+/// What the project configuration tells the lints about the units
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct LintContext {
+    /// Categories which are not known by systemd but are used on purpose
+    pub allowed_categories: Vec<String>,
+    /// Directives which are not known by systemd but are used on purpose
+    pub allowed_directives: Vec<String>,
+    /// The systemd version the units are written for, the latest one when unknown
+    pub systemd_version: Option<u32>,
+}
+
+impl LintContext {
+    /// Tells if the directive is an extension: in a X- category, in an allowed
+    /// category, or allowed itself. Extensions are not checked against systemd.
+    pub fn is_extension(&self, entry: &DirectiveEntry) -> bool {
+        let cat = entry.category();
+
+        cat.starts_with("X-") || self.allowed_categories.contains(&cat) ||
+            self.allowed_directives.contains(&entry.key())
+    }
+}
+
+/// What the settings refer to: a lint of the registry, or a custom rule by its id,
+/// or the diagnostics of a plugin by their id, e.g. `house-rules/x-owner`, or by the
+/// name of the plugin
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum LintId {
+    Code(LintCode),
+    Rule(String),
+}
+
+impl LintId {
+    /// Tells if the findings of the lint, reported as the rule when given, have this id
+    pub fn matches(&self, code: LintCode, rule: Option<&str>) -> bool {
+        match *self {
+            LintId::Code(own) => own == code,
+            LintId::Rule(ref id) => rule.is_some_and(|rule| names(id, rule)),
+        }
+    }
+}
+
+impl From<LintCode> for LintId {
+    fn from(code: LintCode) -> LintId {
+        LintId::Code(code)
+    }
+}
+
+/// `house-rules` names `house-rules/x-owner`, and so does `house-rules/x-owner`
+fn names(id: &str, rule: &str) -> bool {
+    rule.strip_prefix(id).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// How the lints are run on a unit
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Settings {
+    pub context: LintContext,
+    /// Severity overrides, the last one wins. `Ignore` disables the lint.
+    pub severities: Vec<(LintId, LintSeverity)>,
+    /// The only lints to run, all of them when `None`
+    pub selected: Option<Vec<LintId>>,
+    /// Rules of the configuration, run after the built-in lints
+    pub rules: Vec<CustomRule>,
+    /// External lints, run after the built-in ones
+    pub plugins: Vec<Plugin>,
+    /// The plugins are programs named by the configuration, which may be found in a
+    /// parent directory: they only run when allowed
+    pub allow_plugins: bool,
+}
+
+impl Settings {
+    /// Lints which are not selected are ignored, whatever their severity overrides
+    pub fn severity(&self, lint: &dyn Lint) -> LintSeverity {
+        self.severity_of(lint.code(), None, lint.default_severity())
+    }
+
+    /// The severity of the findings of the lint reported as the rule when given,
+    /// `default` unless overridden
+    fn severity_of(&self, code: LintCode, rule: Option<&str>, default: LintSeverity) -> LintSeverity {
+        if !self.is_selected(code, rule) {
+            return LintSeverity::Ignore;
+        }
+
+        self.severities
+            .iter()
+            .rev()
+            .find(|&(id, _)| id.matches(code, rule))
+            .map_or(default, |&(_, severity)| severity)
+    }
+
+    /// The severity of a diagnostic of a plugin, which has its own: the overrides of
+    /// the diagnostic apply, while the ones of the plugin lint are the default
+    fn diagnostic_severity(&self, finding: &LintResult) -> LintSeverity {
+        if !self.is_selected(finding.code, finding.rule()) {
+            return LintSeverity::Ignore;
+        }
+
+        self.severities
+            .iter()
+            .rev()
+            .find(|&(id, _)| id != &LintId::Code(finding.code) && id.matches(finding.code, finding.rule()))
+            .map_or(finding.severity, |&(_, severity)| severity)
+    }
+
+    fn is_selected(&self, code: LintCode, rule: Option<&str>) -> bool {
+        self.selected.as_ref().is_none_or(|selected| selected.iter().any(|id| id.matches(code, rule)))
+    }
 
-use systemd_parser::items::SystemdUnit;
+    /// Tells if the findings the lint reports as the rule are ignored, whatever their
+    /// own severity, e.g. as the plugins are not allowed to run
+    fn ignores_rule(&self, lint: &dyn Lint, rule: &str) -> bool {
+        if lint.code() == LintCode::WarnPluginFinding && !self.allow_plugins {
+            return true;
+        }
 
-type LintFunction = fn(&SystemdUnit) -> Result<(), LintResult>;
+        self.severity_of(lint.code(), Some(rule), lint.default_severity()) == LintSeverity::Ignore
+    }
 
-pub const ALL_LINTS: &'static [LintFunction] = &[lint_missing_description::lint,
-                                                 service_type_always_explicit::lint,
-                                                 service_execstart_not_set::lint,
-                                                 dbus_missing_bus_name_directive::lint,
-                                                 unknown_directive::lint,
-                                                 unknown_category::lint];
+    /// Tells if a rule of the lint may be selected, even when the lint is not
+    fn may_select(&self, code: LintCode) -> bool {
+        self.selected
+            .as_ref()
+            .is_none_or(|selected| selected.iter().any(|id| *id == LintId::Code(code) || matches!(*id, LintId::Rule(_))))
+    }
+
+    /// The ids of the rules the settings and the suppression comments may refer to:
+    /// the ids of the custom rules and the names of the plugins
+    pub fn rule_ids(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .map(|rule| rule.id.clone())
+            .chain(self.plugins.iter().map(|plugin| plugin.name.clone()))
+            .collect()
+    }
+}
+
+pub trait Lint {
+    fn code(&self) -> LintCode;
+    /// Short kebab-case name, used to refer to the lint on the command line
+    fn name(&self) -> &str;
+    /// Why the lint exists, shown by --explain
+    fn rationale(&self) -> &'static str;
+    /// Where the lint is documented, shown with its findings
+    fn docs_url(&self) -> Option<String> {
+        Some(format!("{}#{}", LINTS_DOCS_URL, self.name()))
+    }
+    fn default_severity(&self) -> LintSeverity;
+    fn groups(&self) -> &'static [LintGroup];
+    /// The categories (sections) the lint looks at, empty when it looks at all of them
+    fn categories(&self) -> &'static [&'static str] { &[] }
+    /// The unit types the lint applies to, `None` when it applies to every unit,
+    /// even the ones whose type is unknown
+    fn unit_types(&self) -> Option<&'static [UnitType]> { None }
+    /// Report every problem found in the unit into the sink
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink);
+
+    /// Check the unit with the default context
+    fn run(&self, unit: &SystemdUnit) -> Vec<LintResult> {
+        let mut findings = vec!();
+        self.check(unit, &LintContext::default(), &mut findings);
+        findings
+    }
+
+    fn applies_to(&self, unit: &SystemdUnit) -> bool {
+        match (self.unit_types(), unit.unit_type()) {
+            (None, _) => true,
+            (Some(types), Some(unit_type)) => types.contains(&unit_type),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Every unit type but drop-ins, for the lints checking a unit as a whole
+pub static COMPLETE_UNIT_TYPES: &[UnitType] = &[
+    UnitType::Service, UnitType::Socket, UnitType::Target, UnitType::Device, UnitType::Mount,
+    UnitType::Automount, UnitType::Swap, UnitType::Timer, UnitType::Path, UnitType::Slice,
+    UnitType::Scope,
+];
+
+pub struct Registry {
+    lints: Vec<Box<dyn Lint>>,
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+impl Registry {
+    /// A registry holding all the built-in lints
+    pub fn new() -> Registry {
+        Registry {
+            lints: vec![
+                Box::new(lint_missing_description::MissingDescription),
+                Box::new(service_type_always_explicit::ServiceTypeAlwaysExplicit),
+                Box::new(service_execstart_not_set::ServiceExecStartNotSet),
+                Box::new(dbus_missing_bus_name_directive::DBusMissingBusNameDirective),
+                Box::new(unknown_directive::UnknownDirective),
+                Box::new(unknown_category::UnknownCategory),
+                Box::new(directive_in_wrong_category::DirectiveInWrongCategory),
+                Box::new(invalid_listen_address::InvalidListenAddress),
+                Box::new(invalid_listen_address::DuplicateListenAddress),
+                Box::new(invalid_condition::InvalidCondition),
+                Box::new(systemd_version::UnsupportedDirective),
+                Box::new(systemd_version::DeprecatedDirective),
+                Box::new(invalid_value::InvalidValue),
+                Box::new(duplicate_directive::DuplicateDirective),
+                Box::new(invalid_encoding::InvalidEncoding),
+                Box::new(custom_rule::CustomRules),
+                Box::new(plugin::PluginFinding),
+                Box::new(plugin::PluginFailure),
+                Box::new(unused_suppression::UnusedSuppression),
+            ],
+        }
+    }
+
+    pub fn lints(&self) -> &[Box<dyn Lint>] {
+        &self.lints
+    }
+
+    /// Find a lint by its name, its code name or its code number
+    pub fn find(&self, id: &str) -> Option<&dyn Lint> {
+        self.lints
+            .iter()
+            .find(|lint| {
+                let code = lint.code();
+                lint.name() == id || format!("{:?}", code) == id || (code as u32).to_string() == id
+            })
+            .map(|lint| &**lint)
+    }
+
+    /// Find a lint like `find`, or the rules of `rule_ids` (see `Settings::rule_ids`):
+    /// the id of a custom rule, or `house-rules` and `house-rules/x-owner` for the
+    /// diagnostics of the plugin `house-rules`
+    pub fn find_id(&self, id: &str, rule_ids: &[String]) -> Option<LintId> {
+        if rule_ids.iter().any(|rule| names(rule, id)) {
+            Some(LintId::Rule(id.into()))
+        } else {
+            self.find(id).map(|lint| LintId::Code(lint.code()))
+        }
+    }
+
+    /// Like `select`, with the rules `find_id` accepts
+    pub fn select_ids(&self, selector: &str, rule_ids: &[String]) -> Result<Vec<LintId>, String> {
+        match self.find_id(selector, rule_ids) {
+            Some(LintId::Rule(id)) => Ok(vec![LintId::Rule(id)]),
+            _ => self.select(selector).map(|lints| lints.iter().map(|lint| LintId::Code(lint.code())).collect()),
+        }
+    }
+
+    /// The lints matching a selector: `all`, a group name, a code prefix such as `40`,
+    /// or whatever `find` accepts
+    pub fn select(&self, selector: &str) -> Result<Vec<&dyn Lint>, String> {
+
+        let matching: Vec<&dyn Lint> = if selector == "all" {
+            self.lints.iter().map(|lint| &**lint).collect()
+        } else if let Some(group) = LintGroup::from_name(selector) {
+            // a group may be empty
+            return Ok(self.lints.iter().filter(|lint| lint.groups().contains(&group)).map(|lint| &**lint).collect());
+        } else if !selector.is_empty() && selector.bytes().all(|b| b.is_ascii_digit()) {
+            self.lints
+                .iter()
+                .filter(|lint| (lint.code() as u32).to_string().starts_with(selector))
+                .map(|lint| &**lint)
+                .collect()
+        } else {
+            self.find(selector).into_iter().collect()
+        };
+
+        if matching.is_empty() {
+            Err(format!("unknown lint: {}", selector))
+        } else {
+            Ok(matching)
+        }
+    }
+
+    /// Run every enabled lint which applies to the unit, with the severity set
+    /// by the settings, and drop the findings silenced by suppression comments
+    pub fn check(&self, unit: &SystemdUnit, settings: &Settings, sink: &mut dyn LintSink) {
+        self.check_with(unit, settings, vec!(), sink)
+    }
+
+    /// Like `check`, running the plugins of the settings too when they are allowed.
+    /// They are given the source and the path of the unit. The problems found when
+    /// decoding the source are reported by the `invalid-encoding` lint.
+    pub fn check_source(&self, source: &str, decoding: &[DecodeDiagnostic], path: Option<&Path>, unit: &SystemdUnit,
+                        settings: &Settings, sink: &mut dyn LintSink) {
+
+        let mut external = vec!();
+        let encoding_severity = settings.severity(&invalid_encoding::InvalidEncoding);
+        if encoding_severity != LintSeverity::Ignore {
+            let start = Instant::now();
+            invalid_encoding::report_diagnostics(decoding, &mut external);
+            sink.timed(&invalid_encoding::InvalidEncoding, start.elapsed());
+        }
+
+        // the plugins which are not selected as a whole may have selected diagnostics
+        let severity = settings.severities
+            .iter()
+            .rev()
+            .find(|&(id, _)| *id == LintId::Code(LintCode::WarnPluginFinding))
+            .map_or(plugin::PluginFinding.default_severity(), |&(_, severity)| severity);
+        if severity != LintSeverity::Ignore && settings.may_select(LintCode::WarnPluginFinding) && !settings.plugins.is_empty() {
+            let start = Instant::now();
+            plugin::run_plugins(source, path, unit, settings, severity, &mut external);
+            sink.timed(&plugin::PluginFinding, start.elapsed());
+        }
+
+        let failure_severity = settings.severity(&plugin::PluginFailure);
+        let external = external
+            .into_iter()
+            .filter_map(|mut finding| {
+                finding.severity = match finding.code {
+                    LintCode::WarnInvalidEncoding => encoding_severity,
+                    LintCode::ErrorPluginFailure => failure_severity,
+                    _ => settings.diagnostic_severity(&finding),
+                };
+                if finding.severity == LintSeverity::Ignore { None } else { Some(finding) }
+            })
+            .collect();
+
+        self.check_with(unit, settings, external, sink)
+    }
+
+    /// The external findings already have their severity, but may be suppressed too
+    fn check_with(&self, unit: &SystemdUnit, settings: &Settings, external: Vec<LintResult>, sink: &mut dyn LintSink) {
+
+        let mut suppressions = unused_suppression::Suppressions::new(unit, self, settings);
+
+        let rules = settings.rules
+            .iter()
+            .map(|rule| (rule as &dyn Lint, settings.severity_of(rule.code(), Some(&rule.id), rule.severity)));
+        let lints = self.lints
+            .iter()
+            .map(|lint| (&**lint, settings.severity(&**lint)))
+            .chain(rules);
+
+        for (lint, severity) in lints.filter(|&(lint, _)| lint.applies_to(unit)) {
+            if severity == LintSeverity::Ignore {
+                continue;
+            }
+
+            let mut findings = vec!();
+            let start = Instant::now();
+            lint.check(unit, &settings.context, &mut findings);
+            sink.timed(lint, start.elapsed());
+            for mut finding in findings {
+                if !suppressions.suppress(&finding) {
+                    finding.severity = severity;
+                    sink.report(finding);
+                }
+            }
+        }
+
+        for finding in external {
+            if !suppressions.suppress(&finding) {
+                sink.report(finding);
+            }
+        }
+
+        let severity = settings.severity(&unused_suppression::UnusedSuppression);
+        if severity != LintSeverity::Ignore {
+            let mut findings = vec!();
+            let start = Instant::now();
+            suppressions.report_unused(settings, &mut findings);
+            sink.timed(&unused_suppression::UnusedSuppression, start.elapsed());
+            for mut finding in findings {
+                finding.severity = severity;
+                sink.report(finding);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lints_should_have_unique_codes_and_names() {
+        let registry = Registry::new();
+        for lint in registry.lints() {
+            let same = registry.lints()
+                .iter()
+                .filter(|other| other.code() == lint.code() || other.name() == lint.name())
+                .count();
+            assert_eq!(1, same, "{} is registered more than once", lint.name());
+        }
+    }
+
+    #[test]
+    fn lints_should_belong_to_a_group() {
+        for lint in Registry::new().lints() {
+            assert!(!lint.groups().is_empty(), "{} has no group", lint.name());
+        }
+    }
+
+    #[test]
+    fn code_ranges_should_match_default_severities() {
+        for lint in Registry::new().lints() {
+            let expected = match lint.code() as u32 {
+                20_000..=29_999 => LintSeverity::Lint,
+                30_000..=39_999 => LintSeverity::Warning,
+                40_000..=49_999 => LintSeverity::Error,
+                // the severity of the rule which was not given one
+                _ => LintSeverity::Warning,
+            };
+            assert_eq!(expected, lint.default_severity(), "{}", lint.name());
+        }
+    }
+
+    #[test]
+    fn it_should_find_lints_by_name_and_code() {
+        let registry = Registry::new();
+
+        assert_eq!(Some(LintCode::ErrorUnknownDirective), registry.find("unknown-directive").map(|l| l.code()));
+        assert_eq!(Some(LintCode::ErrorUnknownDirective), registry.find("ErrorUnknownDirective").map(|l| l.code()));
+        assert_eq!(Some(LintCode::ErrorUnknownDirective), registry.find("40001").map(|l| l.code()));
+        assert!(registry.find("plop").is_none());
+    }
+
+    #[test]
+    fn it_should_select_lints_by_group_and_code_prefix() {
+        let registry = Registry::new();
+        let codes = |selector: &str| -> Vec<LintCode> {
+            registry.select(selector).unwrap().iter().map(|lint| lint.code()).collect()
+        };
+
+        assert_eq!(registry.lints().len(), codes("all").len());
+        assert!(codes("portability").contains(&LintCode::ErrorUnsupportedDirective));
+        assert!(!codes("portability").contains(&LintCode::ErrorUnknownDirective));
+        assert!(codes("4").iter().all(|&code| code as u32 >= 40_000));
+        assert_eq!(vec![LintCode::ErrorUnknownDirective], codes("40001"));
+        assert_eq!(vec![LintCode::ErrorUnknownDirective], codes("unknown-directive"));
+        assert!(codes("security").is_empty());
+        assert_eq!(vec![LintCode::CustomRule], codes("5"));
+        assert!(registry.select("6").is_err());
+        assert!(registry.select("plop").is_err());
+    }
+
+    #[test]
+    fn settings_should_only_run_the_selected_lints() {
+        let registry = Registry::new();
+        let settings = Settings {
+            selected: Some(vec![LintId::Code(LintCode::ErrorUnknownDirective)]),
+            severities: vec![(LintId::Code(LintCode::LintMissingDescription), LintSeverity::Error)],
+            ..Settings::default()
+        };
+
+        let unknown_directive = registry.find("unknown-directive").unwrap();
+        let missing_description = registry.find("missing-description").unwrap();
+        assert_eq!(LintSeverity::Error, settings.severity(unknown_directive));
+        assert_eq!(LintSeverity::Ignore, settings.severity(missing_description));
+    }
+
+    #[test]
+    fn settings_should_override_severities() {
+        let input = "
+            [Service]
+            ExecStart=/bin/true
+        ";
+        let unit = ::systemd_parser::parse_string(input).unwrap();
+        let settings = Settings {
+            severities: vec![(LintId::Code(LintCode::LintMissingDescription), LintSeverity::Error)],
+            ..Settings::default()
+        };
+
+        let mut findings = vec!();
+        Registry::new().check(&unit, &settings, &mut findings);
+
+        let finding = findings.iter().find(|finding| finding.code == LintCode::LintMissingDescription).unwrap();
+        assert_eq!(LintSeverity::Error, finding.severity);
+    }
+
+    #[test]
+    fn settings_should_run_the_custom_rules_with_their_own_severity() {
+        let input = "
+            [Unit]
+            Description=a dummy unit
+            [Service]
+            Type=simple
+            ExecStart=/bin/true
+        ";
+        let unit = ::systemd_parser::parse_string(input).unwrap();
+        let mut settings = Settings {
+            rules: vec![CustomRule {
+                id: "service-user".into(),
+                message: "set User=".into(),
+                severity: LintSeverity::Error,
+                section: Some("Service".into()),
+                check: RuleCheck::Require("User".into()),
+            }],
+            ..Settings::default()
+        };
+
+        let mut findings = vec!();
+        Registry::new().check(&unit, &settings, &mut findings);
+
+        assert_eq!(1, findings.len(), "{:?}", findings);
+        assert_eq!(LintSeverity::Error, findings[0].severity);
+        assert_eq!(Some("service-user"), findings[0].rule());
+        assert_eq!(LintCode::CustomRule, findings[0].code);
+
+        settings.severities.push((LintId::Code(LintCode::CustomRule), LintSeverity::Ignore));
+        let mut findings = vec!();
+        Registry::new().check(&unit, &settings, &mut findings);
+
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn settings_and_suppressions_should_refer_to_a_custom_rule_by_id() {
+        let input = "
+            [Unit]
+            Description=a dummy unit
+            [Service]
+            ExecStart=/bin/true
+            # systemd-lint: disable=no-private-network
+            PrivateNetwork=yes
+            PrivateTmp=yes
+        ";
+        let unit = ::systemd_parser::parse_string(input).unwrap();
+        let forbid = |id: &str, key: &str| CustomRule {
+            id: id.into(),
+            message: format!("{}= is not allowed", key),
+            severity: LintSeverity::Error,
+            section: None,
+            check: RuleCheck::Forbid { key: key.into(), when: None },
+        };
+        let mut settings = Settings {
+            rules: vec![forbid("no-private-network", "PrivateNetwork"), forbid("no-private-tmp", "PrivateTmp")],
+            severities: vec![(LintCode::WarnServiceTypeShouldAlwaysBeExplicit.into(), LintSeverity::Ignore)],
+            ..Settings::default()
+        };
+        let rules = |settings: &Settings| {
+            let mut findings = vec!();
+            Registry::new().check(&unit, settings, &mut findings);
+            findings.into_iter().map(|finding| finding.rule.unwrap_or_default()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec!["no-private-tmp"], rules(&settings));
+
+        // the suppression of an ignored rule is not reported
+        settings.severities.push((LintId::Rule("no-private-network".into()), LintSeverity::Ignore));
+        assert_eq!(vec!["no-private-tmp"], rules(&settings));
+
+        settings.selected = Some(vec![LintId::Rule("no-private-network".into())]);
+        assert!(rules(&settings).is_empty());
+
+        settings.severities.clear();
+        assert!(rules(&settings).is_empty(), "the suppression silences the selected rule");
+        settings.selected = Some(vec![LintId::Rule("no-private-tmp".into())]);
+        assert_eq!(vec!["no-private-tmp"], rules(&settings));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn settings_should_refer_to_the_diagnostics_of_the_plugins_by_id() {
+        let input = "# systemd-lint: disable-file=house-rules/x-owner\n[Unit]\nDescription=a dummy unit\n";
+        let unit = ::systemd_parser::parse_string(input).unwrap();
+        let answer = r#"cat > /dev/null; echo '{"diagnostics": [{"code": "x-owner", "message": "X-Owner= is required"},
+                                                            {"code": "x-team", "message": "X-Team= is required"}]}'"#;
+        let mut settings = Settings {
+            plugins: vec![Plugin {
+                name: "house-rules".into(),
+                command: vec!["sh".into(), "-c".into(), answer.into()],
+                timeout: DEFAULT_PLUGIN_TIMEOUT,
+            }],
+            allow_plugins: true,
+            ..Settings::default()
+        };
+        let rules = |settings: &Settings| {
+            let mut findings = vec!();
+            Registry::new().check_source(input, &[], None, &unit, settings, &mut findings);
+            findings.into_iter().map(|finding| finding.rule.unwrap_or_default()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec!["house-rules/x-team"], rules(&settings));
+
+        // the suppression of an ignored diagnostic is not reported
+        settings.severities.push((LintId::Rule("house-rules/x-owner".into()), LintSeverity::Ignore));
+        assert_eq!(vec!["house-rules/x-team"], rules(&settings));
+
+        settings.severities.clear();
+        settings.selected = Some(vec![LintId::Rule("house-rules/x-owner".into())]);
+        assert!(rules(&settings).is_empty());
+    }
+
+    #[test]
+    fn settings_should_disable_ignored_lints() {
+        let input = "
+            [Service]
+            ExecStart=/bin/true
+        ";
+        let unit = ::systemd_parser::parse_string(input).unwrap();
+        let settings = Settings {
+            severities: vec![
+                (LintId::Code(LintCode::LintMissingDescription), LintSeverity::Ignore),
+                (LintId::Code(LintCode::WarnServiceTypeShouldAlwaysBeExplicit), LintSeverity::Ignore),
+            ],
+            ..Settings::default()
+        };
+
+        let mut findings = vec!();
+        Registry::new().check(&unit, &settings, &mut findings);
+
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+}
@@ -0,0 +1,419 @@
+
+use lint::*;
+use lint::result_json::{parse_fixes, parse_location, string};
+use rustc_serialize::json::Json;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use systemd_parser::incremental::Document;
+use systemd_parser::items::*;
+
+/// Version of the protocol, sent to the plugins so that they can check it
+const PROTOCOL_VERSION: i64 = 1;
+
+/// How long a plugin may run when its configuration does not say
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An external executable checking the units for rules which don't belong in the
+/// linter, configured in a `[[plugin]]` table of the configuration.
+///
+/// The plugin is spawned once per unit and receives the unit as a JSON object on
+/// its standard input:
+///
+/// ```json
+/// {"protocol": 1, "path": "/etc/systemd/system/foo.service", "unit_type": "service",
+///  "systemd_version": 219, "source": "[Service]\nUser=foo\n",
+///  "sections": [{"name": "Service", "span": {"start": 0, "end": 19},
+///                "directives": [{"key": "User", "value": "foo",
+///                                "span": {"start": 10, "end": 19}, "value_span": {"start": 15, "end": 18}}]}]}
+/// ```
+///
+/// Spans are byte offsets in `source`; a category which appears twice is sent twice.
+/// The plugin answers on its standard output with its diagnostics, shaped as the
+/// findings of the built-in lints:
+///
+/// ```json
+/// {"diagnostics": [{"code": "x-owner", "severity": "error", "message": "X-Owner= is required",
+///                   "location": {"category": "Unit"},
+///                   "fixes": [{"description": "Add X-Owner=", "safe": false,
+///                              "edits": [{"kind": "insert", "category": "Unit", "key": "X-Owner", "value": "TODO"}]}]}]}
+/// ```
+///
+/// `location` is omitted for the whole unit, and takes a `key` for a directive. The
+/// edit kinds are `replace-value` (`old_value`, `new_value`), `rename-key` (`new_key`),
+/// `rename-category` (`new_category`), `remove` and `insert` (`value`). A plugin which
+/// fails, exits with a non-zero status, answers garbage or outlives its timeout is
+/// reported by the `plugin-failure` lint, and the other plugins still run.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Plugin {
+    /// Prefixes the codes of its diagnostics, e.g. `house-rules/x-owner`, which the
+    /// settings and the suppression comments refer to them by
+    pub name: String,
+    /// The program, then its arguments
+    pub command: Vec<String>,
+    pub timeout: Duration,
+}
+
+pub struct PluginFinding;
+
+impl Lint for PluginFinding {
+    fn code(&self) -> LintCode { LintCode::WarnPluginFinding }
+    fn name(&self) -> &'static str { "plugin" }
+    fn rationale(&self) -> &'static str {
+        "The diagnostics of the plugins configured in `[[plugin]]` tables, which check \
+         house rules that don't belong in the linter. A plugin sets the severity of each \
+         of its diagnostics; warning is used when it does not. Each diagnostic is selected, \
+         ignored or suppressed by its code prefixed with the name of the plugin, e.g. \
+         `house-rules/x-owner`, and all the diagnostics of a plugin by its name. Ignoring \
+         this lint does not run the plugins, and neither does a configuration unless the \
+         plugins are allowed with `--allow-plugins`: a configuration found in a parent \
+         directory should not run programs unasked."
+    }
+    // the plugins document their own diagnostics
+    fn docs_url(&self) -> Option<String> { None }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    // the registry runs the plugins, as they need the source of the unit
+    fn check(&self, _unit: &SystemdUnit, _ctx: &LintContext, _sink: &mut dyn LintSink) {}
+}
+
+pub struct PluginFailure;
+
+impl Lint for PluginFailure {
+    fn code(&self) -> LintCode { LintCode::ErrorPluginFailure }
+    fn name(&self) -> &'static str { "plugin-failure" }
+    fn rationale(&self) -> &'static str {
+        "A plugin which is not allowed to run, can't be spawned, exits with a non-zero \
+         status, answers something else than diagnostics or outlives its timeout checks \
+         nothing, and the unit would seem fine."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    // reported by the registry along with the diagnostics of the plugins
+    fn check(&self, _unit: &SystemdUnit, _ctx: &LintContext, _sink: &mut dyn LintSink) {}
+}
+
+/// Run every plugin of the settings on the unit, reporting their diagnostics and
+/// their failures. Diagnostics without a severity get `default_severity`. When the
+/// plugins are not allowed, none is spawned and each is reported as a failure.
+pub fn run_plugins(source: &str, path: Option<&Path>, unit: &SystemdUnit, settings: &Settings,
+                   default_severity: LintSeverity, sink: &mut dyn LintSink) {
+
+    if settings.plugins.is_empty() {
+        return;
+    }
+    if !settings.allow_plugins {
+        for plugin in &settings.plugins {
+            sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message: format!("Plugin {} was not run: the plugins of the configuration only run when allowed, \
+                                  with --allow-plugins", plugin.name),
+                code: LintCode::ErrorPluginFailure,
+                rule: None,
+                location: Location::Unit,
+                fixes: vec!(),
+            });
+        }
+        return;
+    }
+    let request = request(source, path, unit, &settings.context).to_string();
+
+    for plugin in &settings.plugins {
+        let result = spawn(plugin, &request).and_then(|output| parse_response(plugin, &output, default_severity));
+        match result {
+            Ok(findings) => {
+                for finding in findings.into_iter().filter(|finding| finding.severity != LintSeverity::Ignore) {
+                    sink.report(finding);
+                }
+            }
+            Err(err) => sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message: format!("Plugin {} failed: {}", plugin.name, err),
+                code: LintCode::ErrorPluginFailure,
+                rule: None,
+                location: Location::Unit,
+                fixes: vec!(),
+            }),
+        }
+    }
+}
+
+fn span(range: &Range<usize>) -> Json {
+    let mut span = BTreeMap::new();
+    span.insert("start".to_string(), Json::U64(range.start as u64));
+    span.insert("end".to_string(), Json::U64(range.end as u64));
+    Json::Object(span)
+}
+
+fn request(source: &str, path: Option<&Path>, unit: &SystemdUnit, ctx: &LintContext) -> Json {
+
+    let doc = Document::parse(source);
+    let directives = doc.directive_spans();
+
+    let sections = doc.category_spans()
+        .iter()
+        .map(|category| {
+            let directives = directives
+                .iter()
+                .filter(|directive| category.span.start <= directive.line.start && directive.line.end <= category.span.end)
+                .map(|directive| {
+                    let value = &source[directive.value.clone()];
+                    let mut object = BTreeMap::new();
+                    object.insert("key".to_string(), Json::String(directive.key.clone()));
+                    object.insert("value".to_string(), if value.is_empty() { Json::Null } else { Json::String(value.into()) });
+                    object.insert("span".to_string(), span(&directive.line));
+                    object.insert("value_span".to_string(), span(&directive.value));
+                    Json::Object(object)
+                })
+                .collect();
+
+            let mut object = BTreeMap::new();
+            object.insert("name".to_string(), Json::String(category.category.clone()));
+            object.insert("span".to_string(), span(&category.span));
+            object.insert("directives".to_string(), Json::Array(directives));
+            Json::Object(object)
+        })
+        .collect();
+
+    let mut object = BTreeMap::new();
+    object.insert("protocol".to_string(), Json::I64(PROTOCOL_VERSION));
+    object.insert("path".to_string(), path.map_or(Json::Null, |path| Json::String(path.to_string_lossy().into_owned())));
+    object.insert("unit_type".to_string(), unit.unit_type().map_or(Json::Null, |unit_type| {
+        Json::String(format!("{:?}", unit_type).to_lowercase())
+    }));
+    object.insert("systemd_version".to_string(), ctx.systemd_version.map_or(Json::Null, |version| Json::U64(u64::from(version))));
+    object.insert("source".to_string(), Json::String(source.into()));
+    object.insert("sections".to_string(), Json::Array(sections));
+    Json::Object(object)
+}
+
+/// Run the plugin with the request on its standard input, and return its standard
+/// output. The plugin is killed when it outlives its timeout.
+fn spawn(plugin: &Plugin, request: &str) -> Result<String, String> {
+
+    let (program, args) = plugin.command.split_first().ok_or("empty command")?;
+    let mut child = Command::new(PathBuf::from(program))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("can't run {}: {}", program, err))?;
+
+    // in threads, so that a plugin which does not read its input, or writes a lot, can't block us
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let request = request.to_string();
+    thread::spawn(move || {
+        let _ = stdin.write_all(request.as_bytes());
+    });
+    let (sender, receiver) = mpsc::channel();
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    thread::spawn(move || {
+        let mut output = String::new();
+        let mut errors = String::new();
+        let res = stdout.read_to_string(&mut output);
+        let _ = stderr.read_to_string(&mut errors);
+        let _ = sender.send(res.map(|_| (output, errors)));
+    });
+
+    let deadline = Instant::now() + plugin.timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", plugin.timeout.as_secs_f64()));
+            }
+            Err(err) => return Err(err.to_string()),
+        }
+    };
+
+    let (output, errors) = receiver
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()) + Duration::from_secs(1))
+        .map_err(|_| "its output was not closed".to_string())?
+        .map_err(|err| format!("unreadable output: {}", err))?;
+
+    if !status.success() {
+        let reason = errors.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+        return Err(match reason {
+            "" => format!("exited with {}", status),
+            _ => format!("exited with {}: {}", status, reason),
+        });
+    }
+
+    Ok(output)
+}
+
+fn parse_response(plugin: &Plugin, output: &str, default_severity: LintSeverity) -> Result<Vec<LintResult>, String> {
+
+    let response = Json::from_str(output).map_err(|err| format!("invalid response: {}", err))?;
+    let diagnostics = response
+        .find("diagnostics")
+        .and_then(|diagnostics| diagnostics.as_array())
+        .ok_or("invalid response: expected an object with a diagnostics array")?;
+
+    diagnostics
+        .iter()
+        .enumerate()
+        .map(|(idx, diagnostic)| {
+            parse_diagnostic(plugin, diagnostic, default_severity)
+                .map_err(|err| format!("invalid diagnostic #{}: {}", idx + 1, err))
+        })
+        .collect()
+}
+
+fn parse_diagnostic(plugin: &Plugin, diagnostic: &Json, default_severity: LintSeverity) -> Result<LintResult, String> {
+
+    let severity = match diagnostic.find("severity") {
+        None | Some(&Json::Null) => default_severity,
+        Some(severity) => severity
+            .as_string()
+            .and_then(LintSeverity::from_name)
+            .ok_or("severity should be one of: ignore, lint, warning, error")?,
+    };
+
+    Ok(LintResult {
+        severity,
+        message: string(diagnostic, "message")?.into(),
+        code: LintCode::WarnPluginFinding,
+        rule: Some(format!("{}/{}", plugin.name, string(diagnostic, "code")?)),
+        location: parse_location(diagnostic)?,
+        fixes: parse_fixes(diagnostic)?,
+    })
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[cfg(test)]
+fn plugin(script: &str) -> Plugin {
+    Plugin {
+        name: "house-rules".into(),
+        command: vec!["sh".into(), "-c".into(), script.into()],
+        timeout: Duration::from_secs(5),
+    }
+}
+
+#[cfg(test)]
+fn run(input: &str, plugins: Vec<Plugin>) -> Vec<LintResult> {
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let settings = Settings { plugins, allow_plugins: true, ..Settings::default() };
+    let mut findings = vec!();
+    run_plugins(input, None, &unit, &settings, LintSeverity::Warning, &mut findings);
+    findings
+}
+
+#[test]
+fn it_should_send_the_sections_and_directives_with_their_spans() {
+    // arrange
+    let input = "[Unit]\nDescription=foo\n[Service]\nUser=\n";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let request = request(input, Some(Path::new("/foo.service")), &unit, &LintContext::default());
+    // assert
+    assert_eq!(Some(&Json::String("/foo.service".into())), request.find("path"));
+    let sections = request.find("sections").unwrap().as_array().unwrap();
+    assert_eq!(2, sections.len());
+    let description = &sections[0].find("directives").unwrap()[0];
+    assert_eq!(Some("foo"), description.find("value").and_then(|value| value.as_string()));
+    assert_eq!(Some(&Json::U64(19)), description.find_path(&["value_span", "start"]));
+    let user = &sections[1].find("directives").unwrap()[0];
+    assert_eq!(Some(&Json::Null), user.find("value"))
+}
+
+#[test]
+fn it_should_read_the_diagnostics() {
+    // arrange
+    let response = r#"{"diagnostics": [
+        {"code": "x-owner", "message": "X-Owner= is required", "location": {"category": "Unit"}},
+        {"code": "no-root", "severity": "error", "message": "Don't run as root",
+         "location": {"category": "Service", "key": "User"},
+         "fixes": [{"description": "Run as nobody", "edits": [
+            {"kind": "replace-value", "category": "Service", "key": "User", "old_value": "root", "new_value": "nobody"}]}]}
+    ]}"#;
+    // act
+    let res = parse_response(&plugin(""), response, LintSeverity::Warning).unwrap();
+    // assert
+    assert_eq!(LintSeverity::Warning, res[0].severity);
+    assert_eq!(Some("house-rules/x-owner"), res[0].rule());
+    assert_eq!("X-Owner= is required", res[0].message);
+    assert_eq!(Location::Category("Unit".into()), res[0].location);
+    assert_eq!(LintSeverity::Error, res[1].severity);
+    assert_eq!(vec![Edit::ReplaceValue {
+        category: "Service".into(),
+        key: "User".into(),
+        old_value: "root".into(),
+        new_value: "nobody".into(),
+    }], res[1].fixes[0].edits);
+    assert!(!res[1].fixes[0].safe)
+}
+
+#[test]
+fn it_should_reject_malformed_responses() {
+    let parse = |response: &str| parse_response(&plugin(""), response, LintSeverity::Warning).map(|_| ()).unwrap_err();
+
+    assert!(parse("plop").starts_with("invalid response"));
+    assert_eq!("invalid diagnostic #1: code should be a string", parse(r#"{"diagnostics": [{"message": "foo"}]}"#));
+    assert_eq!(
+        "invalid diagnostic #1: severity should be one of: ignore, lint, warning, error",
+        parse(r#"{"diagnostics": [{"code": "a", "message": "b", "severity": "fatal"}]}"#)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn it_should_run_the_plugins() {
+    // arrange
+    let answer = r#"cat > /dev/null; echo '{"diagnostics": [{"code": "x-owner", "message": "X-Owner= is required"}]}'"#;
+    // act
+    let res = run("[Service]\nExecStart=/bin/true\n", vec![plugin(answer)]);
+    // assert
+    assert_eq!(1, res.len(), "{:?}", res);
+    assert_eq!(LintCode::WarnPluginFinding, res[0].code);
+    assert_eq!(Some("house-rules/x-owner"), res[0].rule());
+    assert_eq!("X-Owner= is required", res[0].message)
+}
+
+#[test]
+fn it_should_not_spawn_the_plugins_unless_allowed() {
+    // arrange
+    let input = "[Service]\nExecStart=/bin/true\n";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let settings = Settings { plugins: vec![plugin("exit 3")], ..Settings::default() };
+    let mut res = vec!();
+    // act
+    run_plugins(input, None, &unit, &settings, LintSeverity::Warning, &mut res);
+    // assert
+    assert_eq!(1, res.len(), "{:?}", res);
+    assert_eq!(LintCode::ErrorPluginFailure, res[0].code);
+    assert!(res[0].message.starts_with("Plugin house-rules was not run"), "{}", res[0].message)
+}
+
+#[cfg(unix)]
+#[test]
+fn it_should_isolate_failing_plugins() {
+    // arrange
+    let crashing = plugin("echo 'house-rules: boom' >&2; exit 3");
+    let hanging = Plugin { timeout: Duration::from_millis(100), ..plugin("exec sleep 5") };
+    let missing = Plugin { command: vec!["/no/such/plugin".into()], ..plugin("") };
+    let working = plugin(r#"echo '{"diagnostics": []}'"#);
+    // act
+    let res = run("[Service]\nExecStart=/bin/true\n", vec![crashing, hanging, missing, working]);
+    // assert
+    let messages: Vec<&str> = res.iter().map(|finding| &finding.message[..]).collect();
+    assert_eq!(3, messages.len(), "{:?}", messages);
+    assert!(messages[0].ends_with("exited with exit status: 3: house-rules: boom"), "{}", messages[0]);
+    assert_eq!("Plugin house-rules failed: timed out after 0.1s", messages[1]);
+    assert!(messages[2].starts_with("Plugin house-rules failed: can't run /no/such/plugin"), "{}", messages[2]);
+    assert!(res.iter().all(|finding| finding.code == LintCode::ErrorPluginFailure))
+}
@@ -0,0 +1,197 @@
+
+use lint::*;
+use rustc_serialize::json::{self, Json};
+
+pub fn string<'a>(object: &'a Json, key: &str) -> Result<&'a str, String> {
+    object.find(key).and_then(|value| value.as_string()).ok_or_else(|| format!("{} should be a string", key))
+}
+
+pub fn parse_location(diagnostic: &Json) -> Result<Location, String> {
+    Ok(match diagnostic.find("location") {
+        None | Some(&Json::Null) => Location::Unit,
+        Some(location) => match (string(location, "category"), location.find("key")) {
+            (Ok(category), None) => Location::Category(category.into()),
+            (Ok(category), Some(_)) => Location::Directive { category: category.into(), key: string(location, "key")?.into() },
+            (Err(err), _) => return Err(format!("location: {}", err)),
+        },
+    })
+}
+
+pub fn parse_fixes(diagnostic: &Json) -> Result<Vec<Fix>, String> {
+    match diagnostic.find("fixes") {
+        None | Some(&Json::Null) => Ok(vec!()),
+        Some(fixes) => fixes
+            .as_array()
+            .ok_or("fixes should be an array")?
+            .iter()
+            .map(parse_fix)
+            .collect(),
+    }
+}
+
+fn parse_fix(fix: &Json) -> Result<Fix, String> {
+
+    let edits = fix
+        .find("edits")
+        .and_then(|edits| edits.as_array())
+        .ok_or("fix: edits should be an array")?
+        .iter()
+        .map(parse_edit)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Fix {
+        description: string(fix, "description").map_err(|err| format!("fix: {}", err))?.into(),
+        safe: fix.find("safe").and_then(|safe| safe.as_boolean()).unwrap_or(false),
+        edits,
+    })
+}
+
+fn parse_edit(edit: &Json) -> Result<Edit, String> {
+
+    let field = |key: &str| string(edit, key).map(String::from).map_err(|err| format!("edit: {}", err));
+
+    Ok(match string(edit, "kind").map_err(|err| format!("edit: {}", err))? {
+        "replace-value" => Edit::ReplaceValue {
+            category: field("category")?,
+            key: field("key")?,
+            old_value: field("old_value")?,
+            new_value: field("new_value")?,
+        },
+        "rename-key" => Edit::RenameKey { category: field("category")?, key: field("key")?, new_key: field("new_key")? },
+        "rename-category" => Edit::RenameCategory { category: field("category")?, new_category: field("new_category")? },
+        "remove" => Edit::Remove { category: field("category")?, key: field("key")? },
+        "insert" => Edit::Insert { category: field("category")?, key: field("key")?, value: field("value")? },
+        kind => return Err(format!("edit: unknown kind {}", json::as_json(&kind))),
+    })
+}
+
+pub fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn edit_to_json(edit: &Edit) -> Json {
+    let string = |value: &String| Json::String(value.clone());
+
+    match *edit {
+        Edit::ReplaceValue { ref category, ref key, ref old_value, ref new_value } => object(vec![
+            ("kind", Json::String("replace-value".into())), ("category", string(category)), ("key", string(key)),
+            ("old_value", string(old_value)), ("new_value", string(new_value)),
+        ]),
+        Edit::RenameKey { ref category, ref key, ref new_key } => object(vec![
+            ("kind", Json::String("rename-key".into())), ("category", string(category)), ("key", string(key)),
+            ("new_key", string(new_key)),
+        ]),
+        Edit::RenameCategory { ref category, ref new_category } => object(vec![
+            ("kind", Json::String("rename-category".into())), ("category", string(category)),
+            ("new_category", string(new_category)),
+        ]),
+        Edit::Remove { ref category, ref key } => object(vec![
+            ("kind", Json::String("remove".into())), ("category", string(category)), ("key", string(key)),
+        ]),
+        Edit::Insert { ref category, ref key, ref value } => object(vec![
+            ("kind", Json::String("insert".into())), ("category", string(category)), ("key", string(key)),
+            ("value", string(value)),
+        ]),
+    }
+}
+
+impl LintResult {
+    /// Encode the finding as the plugins report their diagnostics, with the number
+    /// of its code, e.g. to cache it
+    pub fn to_json(&self) -> Json {
+
+        let location = match self.location {
+            Location::Unit => Json::Null,
+            Location::Category(ref category) => object(vec![("category", Json::String(category.clone()))]),
+            Location::Directive { ref category, ref key } => object(vec![
+                ("category", Json::String(category.clone())),
+                ("key", Json::String(key.clone())),
+            ]),
+        };
+        let fixes = self.fixes
+            .iter()
+            .map(|fix| object(vec![
+                ("description", Json::String(fix.description.clone())),
+                ("safe", Json::Boolean(fix.safe)),
+                ("edits", Json::Array(fix.edits.iter().map(edit_to_json).collect())),
+            ]))
+            .collect();
+
+        let rule = self.rule.as_ref().map_or(Json::Null, |rule| Json::String(rule.clone()));
+
+        object(vec![
+            ("code", Json::U64(self.code as u64)),
+            ("rule", rule),
+            ("severity", Json::String(self.severity.name().into())),
+            ("message", Json::String(self.message.clone())),
+            ("location", location),
+            ("fixes", Json::Array(fixes)),
+        ])
+    }
+
+    /// Decode a finding encoded by `to_json`, whose code is one of the registry
+    pub fn from_json(finding: &Json, registry: &Registry) -> Result<LintResult, String> {
+
+        let code = finding.find("code").and_then(Json::as_u64).ok_or("code should be a number")?;
+        let code = registry.find(&code.to_string()).ok_or_else(|| format!("unknown code {}", code))?.code();
+        let severity = string(finding, "severity")?;
+
+        Ok(LintResult {
+            severity: LintSeverity::from_name(severity).ok_or_else(|| format!("unknown severity {}", severity))?,
+            message: string(finding, "message")?.into(),
+            code,
+            rule: match finding.find("rule") {
+                None | Some(&Json::Null) => None,
+                Some(_) => Some(string(finding, "rule")?.into()),
+            },
+            location: parse_location(finding)?,
+            fixes: parse_fixes(finding)?,
+        })
+    }
+}
+
+#[test]
+fn findings_should_read_back_from_json() {
+    // arrange
+    let edits = vec![
+        Edit::ReplaceValue { category: "Service".into(), key: "Type".into(), old_value: "Simple".into(), new_value: "simple".into() },
+        Edit::RenameKey { category: "Service".into(), key: "ExecStrat".into(), new_key: "ExecStart".into() },
+        Edit::RenameCategory { category: "service".into(), new_category: "Service".into() },
+        Edit::Remove { category: "Service".into(), key: "Type".into() },
+        Edit::Insert { category: "Unit".into(), key: "Description".into(), value: "TODO".into() },
+    ];
+    let findings = vec![
+        LintResult {
+            severity: LintSeverity::Error,
+            message: "Invalid value".into(),
+            code: LintCode::ErrorInvalidValue,
+            rule: None,
+            location: Location::Directive { category: "Service".into(), key: "Type".into() },
+            fixes: vec![Fix { description: "Fix it all".into(), safe: true, edits }],
+        },
+        LintResult {
+            severity: LintSeverity::Lint,
+            message: "Missing description".into(),
+            code: LintCode::LintMissingDescription,
+            rule: None,
+            location: Location::Unit,
+            fixes: vec!(),
+        },
+        LintResult {
+            severity: LintSeverity::Warning,
+            message: "X-Owner= is required".into(),
+            code: LintCode::WarnPluginFinding,
+            rule: Some("house-rules/x-owner".into()),
+            location: Location::Category("Unit".into()),
+            fixes: vec!(),
+        },
+    ];
+    let registry = Registry::new();
+    // act
+    let res: Vec<LintResult> = findings
+        .iter()
+        .map(|finding| LintResult::from_json(&finding.to_json(), &registry).unwrap())
+        .collect();
+    // assert
+    assert_eq!(findings, res)
+}
@@ -2,26 +2,40 @@
 use lint::*;
 use systemd_parser::items::*;
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
+pub struct ServiceExecStartNotSet;
 
-    if let Some(&DirectiveEntry::Solo(ref type_entry)) = unit.lookup_by_key("Type") {
+impl Lint for ServiceExecStartNotSet {
+    fn code(&self) -> LintCode { LintCode::ErrorServiceSimpleMustHaveExecstart }
+    fn name(&self) -> &'static str { "service-execstart-not-set" }
+    fn rationale(&self) -> &'static str {
+        "A simple service runs its ExecStart= command. Without it, systemd refuses to start \
+         the service."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+    fn categories(&self) -> &'static [&'static str] { &["Service"] }
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(&[UnitType::Service]) }
 
-        println!("{:?}", type_entry.value());
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
 
-        if type_entry.value() != Some("Simple") {
-            return Ok(());
-        }
+        if let Some(DirectiveEntry::Solo(type_entry)) = unit.lookup_by_key("Type") {
 
-        if !unit.has_key("ExecStart") {
-            return Err(LintResult {
-                severity: LintSeverity::Error,
-                message: "Service with Type==Simple MUST set ExecStart= field".into(),
-                code: LintCode::ErrorServiceSimpleMustHaveExecstart,
-            });
+            if type_entry.value() != Some("simple") {
+                return;
+            }
+
+            if !unit.has_key("ExecStart") {
+                sink.report(LintResult {
+                    severity: LintSeverity::Error,
+                    message: "Service with Type=simple MUST set ExecStart= field".into(),
+                    code: LintCode::ErrorServiceSimpleMustHaveExecstart,
+                    rule: None,
+                    location: Location::Category("Service".into()),
+                    fixes: vec!(),
+                });
+            }
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -32,14 +46,14 @@ fn success_case() {
     // arrange
     let input = "
         [Service]
-        Type=Simple
+        Type=simple
         ExecStart=/bin/true
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = ServiceExecStartNotSet.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
 }
 
 #[test]
@@ -47,11 +61,11 @@ fn error_case() {
     // arrange
     let input = "
         [Service]
-        Type=Simple
+        Type=simple
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = ServiceExecStartNotSet.run(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res.is_empty());
 }
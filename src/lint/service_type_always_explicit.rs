@@ -2,41 +2,74 @@
 use lint::*;
 use systemd_parser::items::*;
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
-
-    // first check it's a service
-    if !unit.has_category("Service") {
-        return Ok(());
+/// The type systemd picks when Type= is not set
+fn default_type(unit: &SystemdUnit) -> &'static str {
+    if unit.has_key("BusName") {
+        "dbus"
+    } else if unit.has_key("ExecStart") {
+        "simple"
+    } else {
+        "oneshot"
     }
+}
 
-    if !unit.has_key("Type") {
+pub struct ServiceTypeAlwaysExplicit;
 
-        return Err(LintResult {
-            severity: LintSeverity::Warning,
-            message: "Service Type= should always be explicit. Fill the Type= field.".into(),
-            code: LintCode::WarnServiceTypeShouldAlwaysBeExplicit,
-        });
+impl Lint for ServiceTypeAlwaysExplicit {
+    fn code(&self) -> LintCode { LintCode::WarnServiceTypeShouldAlwaysBeExplicit }
+    fn name(&self) -> &'static str { "service-type-always-explicit" }
+    fn rationale(&self) -> &'static str {
+        "The default Type= depends on the other directives of the service. Being explicit \
+         avoids surprises about when the service is considered started."
     }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Pedantic] }
+    fn categories(&self) -> &'static [&'static str] { &["Service"] }
+    fn unit_types(&self) -> Option<&'static [UnitType]> { Some(&[UnitType::Service]) }
+
+    fn check(&self, unit: &SystemdUnit, _ctx: &LintContext, sink: &mut dyn LintSink) {
 
-    Ok(())
+        if !unit.has_key("Type") {
+
+            let default_type = default_type(unit);
+            sink.report(LintResult {
+                severity: LintSeverity::Warning,
+                message: "Service Type= should always be explicit. Fill the Type= field.".into(),
+                code: LintCode::WarnServiceTypeShouldAlwaysBeExplicit,
+                rule: None,
+                location: Location::Category("Service".into()),
+                fixes: vec![Fix {
+                    description: format!("Set Type={}, the current default", default_type),
+                    safe: true,
+                    edits: vec![Edit::Insert {
+                        category: "Service".into(),
+                        key: "Type".into(),
+                        value: default_type.into(),
+                    }],
+                }],
+            });
+        }
+    }
 }
 
 #[cfg(test)]
 use systemd_parser;
+#[cfg(test)]
+use std::path::Path;
 
 #[test]
 fn success_case() {
     // arrange
     let input = "
         [Service]
-        Type=Simple
+        Type=simple
         ExecStart=/bin/true
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = ServiceTypeAlwaysExplicit.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
 }
 
 #[test]
@@ -48,7 +81,54 @@ fn error_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = ServiceTypeAlwaysExplicit.run(&unit);
+    // assert
+    assert!(!res.is_empty());
+}
+
+#[test]
+fn success_case_drop_in() {
+    // arrange
+    let input = "
+        [Service]
+        Environment=DEBUG=1
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("sshd.service.d/debug.conf"), input).unwrap();
+    // act
+    let res = ServiceTypeAlwaysExplicit.applies_to(&unit);
+    // assert
+    assert!(!res)
+}
+
+#[test]
+fn error_case_service_file_without_service_section() {
+    // arrange
+    let input = "
+        [Unit]
+        Description= a dummy unit
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("dummy.service"), input).unwrap();
+    // act
+    let res = ServiceTypeAlwaysExplicit.run(&unit);
+    // assert
+    assert!(!res.is_empty())
+}
+
+#[test]
+fn error_case_fix_makes_the_default_type_explicit() {
+    // arrange
+    let input = "
+        [Service]
+        BusName=org.example.Foo
+        ExecStart=/bin/foo
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = ServiceTypeAlwaysExplicit.run(&unit).remove(0);
     // assert
-    assert!(res.is_err());
+    assert_eq!(vec![Edit::Insert {
+        category: "Service".into(),
+        key: "Type".into(),
+        value: "dbus".into(),
+    }], res.fixes()[0].edits)
 }
@@ -0,0 +1,293 @@
+
+use lint::*;
+use lint::directives::{DirectiveDatabase, Support, Versions};
+use support::*;
+use systemd_parser::items::*;
+
+/// A directive, or one of its values, whose support depends on the systemd version
+struct Versioned<'a> {
+    entry: &'a DirectiveEntry,
+    value: Option<&'a str>,
+    versions: Versions,
+}
+
+impl<'a> Versioned<'a> {
+    /// `Key=` or `Key=value`
+    fn subject(&self) -> String {
+        format!("{}={}", self.entry.key(), self.value.unwrap_or(""))
+    }
+
+    fn replacement(&self) -> Option<String> {
+        self.versions.replaced_by.as_ref().map(|replacement| match self.value {
+            Some(_) => format!("{}={}", self.entry.key(), replacement),
+            None => format!("{}=", replacement),
+        })
+    }
+
+    /// ` use Foo= instead`, or nothing without replacement
+    fn use_instead(&self) -> String {
+        self.replacement().map_or(String::new(), |replacement| format!(", use {} instead", replacement))
+    }
+
+    /// Directives are only renamed to a replacement accepted in their category
+    fn fixes(&self, directives: &DirectiveDatabase) -> Vec<Fix> {
+        let replacement = match self.versions.replaced_by {
+            Some(ref replacement) => replacement,
+            None => return vec!(),
+        };
+        let category = self.entry.category();
+
+        let edit = match self.value {
+            Some(value) => Edit::ReplaceValue {
+                category,
+                key: self.entry.key(),
+                old_value: value.into(),
+                new_value: replacement.clone(),
+            },
+            None if directives.sections(replacement).contains(&&category[..]) => Edit::RenameKey {
+                category,
+                key: self.entry.key(),
+                new_key: replacement.clone(),
+            },
+            None => return vec!(),
+        };
+
+        vec![Fix {
+            description: format!("Replace {} by {}", self.subject(), self.replacement().expect("there is a replacement")),
+            safe: false,
+            edits: vec![edit],
+        }]
+    }
+}
+
+/// The directives of the unit known to the database, and their values with versions
+fn versioned<'a>(unit: &'a SystemdUnit, ctx: &LintContext, directives: &DirectiveDatabase) -> Vec<Versioned<'a>> {
+
+    let mut res = vec!();
+    for entry in unit.keys() {
+        // directives in the wrong category are reported by DirectiveInWrongCategory
+        let documented = match directives.documented_in(&entry.key(), &entry.category()) {
+            Some(documented) if !ctx.is_extension(entry) => documented,
+            _ => continue,
+        };
+
+        res.push(Versioned { entry, value: None, versions: documented.versions.clone() });
+        for value in entry.values() {
+            if let Some(documented_value) = documented.values.iter().find(|documented_value| documented_value.value == value) {
+                res.push(Versioned { entry, value: Some(value), versions: documented_value.versions.clone() });
+            }
+        }
+    }
+
+    res
+}
+
+pub struct UnsupportedDirective;
+
+impl Lint for UnsupportedDirective {
+    fn code(&self) -> LintCode { LintCode::ErrorUnsupportedDirective }
+    fn name(&self) -> &'static str { "unsupported-directive" }
+    fn rationale(&self) -> &'static str {
+        "systemd ignores the directives and values it does not know, with a mere warning in \
+         the journal: a directive introduced after the systemd version the units target, \
+         or removed since, silently does nothing. The target version is set by \
+         `systemd-version` in the configuration or by --systemd-version."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Portability] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        for versioned in versioned(unit, ctx, directives) {
+            let message = match versioned.versions.support(ctx.systemd_version) {
+                Support::TooNew(version) => format!(
+                    "{} requires systemd {}, the units target systemd {}",
+                    versioned.subject(),
+                    version,
+                    ctx.systemd_version.expect("only a target version makes a directive too new"),
+                ),
+                Support::Removed(version) => format!(
+                    "{} was removed in systemd {}{}", versioned.subject(), version, versioned.use_instead(),
+                ),
+                Support::Deprecated(_) | Support::Supported => continue,
+            };
+
+            sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message,
+                code: LintCode::ErrorUnsupportedDirective,
+                rule: None,
+                location: Location::of(versioned.entry),
+                fixes: versioned.fixes(directives),
+            });
+        }
+    }
+}
+
+pub struct DeprecatedDirective;
+
+impl Lint for DeprecatedDirective {
+    fn code(&self) -> LintCode { LintCode::WarnDeprecatedDirective }
+    fn name(&self) -> &'static str { "deprecated-directive" }
+    fn rationale(&self) -> &'static str {
+        "Deprecated directives and values still work, but are on their way out and often \
+         have a better replacement, e.g. MemoryMax= for MemoryLimit=. Only the deprecations \
+         which already happened in the target systemd version are reported."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Portability] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        for versioned in versioned(unit, ctx, directives) {
+            if let Support::Deprecated(version) = versioned.versions.support(ctx.systemd_version) {
+                sink.report(LintResult {
+                    severity: LintSeverity::Warning,
+                    message: format!(
+                        "{} is deprecated since systemd {}{}", versioned.subject(), version, versioned.use_instead(),
+                    ),
+                    code: LintCode::WarnDeprecatedDirective,
+                    rule: None,
+                    location: Location::of(versioned.entry),
+                    fixes: versioned.fixes(directives),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[cfg(test)]
+fn check_for_version<L: Lint>(lint: L, input: &str, systemd_version: Option<u32>) -> Vec<LintResult> {
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let ctx = LintContext { systemd_version, ..LintContext::default() };
+    let mut findings = vec!();
+    lint.check(&unit, &ctx, &mut findings);
+    findings
+}
+
+#[cfg(test)]
+fn messages(findings: &[LintResult]) -> Vec<&str> {
+    findings.iter().map(|finding| &finding.message[..]).collect()
+}
+
+#[test]
+fn success_case_supported_by_the_target_version() {
+    // arrange
+    let input = "
+        [Service]
+        Type=exec
+        ExecCondition=/bin/true
+        ExecStart=/bin/true
+        MemoryMax=1G
+    ";
+    // act
+    let res = check_for_version(UnsupportedDirective, input, Some(243));
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_too_new_for_the_target_version() {
+    // arrange
+    let input = "
+        [Service]
+        Type=exec
+        ExecCondition=/bin/true
+        ExecStart=/bin/true
+        MemoryLimit=1G
+    ";
+    // act
+    let res = check_for_version(UnsupportedDirective, input, Some(219));
+    // assert
+    assert_eq!(vec![
+        "ExecCondition= requires systemd 243, the units target systemd 219",
+        "Type=exec requires systemd 240, the units target systemd 219",
+    ], messages(&res));
+    assert!(res[1].fixes().is_empty())
+}
+
+#[test]
+fn success_case_without_target_version_nothing_is_too_new() {
+    // arrange
+    let input = "
+        [Service]
+        Type=exec
+        ExecCondition=/bin/true
+    ";
+    // act
+    let res = check_for_version(UnsupportedDirective, input, None);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_removed_directive() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        SysVStartPriority=99
+    ";
+    // act
+    let res = check_for_version(UnsupportedDirective, input, None);
+    // assert
+    assert_eq!(vec!["SysVStartPriority= was removed in systemd 218"], messages(&res))
+}
+
+#[test]
+fn error_case_deprecated_directive_with_its_replacement() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        MemoryLimit=1G
+        PermissionsStartOnly=true
+    ";
+    // act
+    let res = check_for_version(DeprecatedDirective, input, Some(240));
+    // assert
+    assert_eq!(vec![
+        "MemoryLimit= is deprecated since systemd 231, use MemoryMax= instead",
+        "PermissionsStartOnly= is deprecated since systemd 240",
+    ], messages(&res));
+    assert_eq!(vec![
+        Edit::RenameKey { category: "Service".into(), key: "MemoryLimit".into(), new_key: "MemoryMax".into() },
+    ], res[0].fixes()[0].edits);
+    assert!(res[1].fixes().is_empty())
+}
+
+#[test]
+fn success_case_not_yet_deprecated_for_the_target_version() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        MemoryLimit=1G
+    ";
+    // act
+    let res = check_for_version(DeprecatedDirective, input, Some(219));
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn success_case_replacement_in_another_category_is_not_a_fix() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        StartLimitInterval=10s
+    ";
+    // act
+    let res = check_for_version(DeprecatedDirective, input, None);
+    // assert
+    assert_eq!(vec!["StartLimitInterval= is deprecated since systemd 230, use StartLimitIntervalSec= instead"], messages(&res));
+    assert!(res[0].fixes().is_empty())
+}
@@ -1,30 +1,87 @@
 
 use lint::*;
+use suggest::{closest, did_you_mean};
 use systemd_parser::items::*;
 
-static KNOWN_CATEGORIES: &'static [&'static str] =
-    &["Unit", "Service", "Install", "Mount", "Socket", "Automount", "BusName", "Path", "Timer"];
+/// `[Unit]` and `[Install]`, then the type-specific sections
+fn known_categories() -> Vec<&'static str> {
+    let mut res = vec!["Unit", "Install"];
+    res.extend(UnitType::categories());
+    res
+}
+
+fn belongs_to_another_unit_type(unit: &SystemdUnit, cat: &str) -> bool {
+    match (unit.unit_type(), UnitType::from_categories(&[cat.into()])) {
+        (Some(UnitType::DropIn), _) => false,
+        (Some(unit_type), Some(cat_type)) => unit_type != cat_type,
+        _ => false,
+    }
+}
+
+pub struct UnknownCategory;
+
+impl Lint for UnknownCategory {
+    fn code(&self) -> LintCode { LintCode::ErrorUnknownCategory }
+    fn name(&self) -> &'static str { "unknown-category" }
+    fn rationale(&self) -> &'static str {
+        "systemd ignores unknown categories along with all their directives, and the \
+         categories specific to a unit type in units of another type. X- categories are \
+         left for extensions, and the configuration can allow more categories."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let known_categories = known_categories();
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
+        for cat in unit.categories() {
+            if ctx.allowed_categories.contains(&cat) {
+                continue;
+            }
 
-    for cat in unit.categories() {
-        if &cat[0..2] != "X-" && !KNOWN_CATEGORIES.contains(&cat.as_ref()) {
+            if !cat.starts_with("X-") && !known_categories.contains(&cat.as_ref()) {
 
-            let error = Err(LintResult {
-                severity: LintSeverity::Error,
-                message: format!("Unknown category: {}", cat),
-                code: LintCode::ErrorUnknownCategory,
-            });
+                let suggestions = closest(&cat, known_categories.iter().cloned());
+                let fixes = suggestions
+                    .iter()
+                    .map(|suggestion| Fix {
+                        description: format!("Rename [{}] to [{}]", cat, suggestion),
+                        // systemd ignores the misspelled category: renaming it turns its directives on
+                        safe: false,
+                        edits: vec![Edit::RenameCategory {
+                            category: cat.clone(),
+                            new_category: String::from(*suggestion),
+                        }],
+                    })
+                    .collect();
 
-            return error;
+                sink.report(LintResult {
+                    severity: LintSeverity::Error,
+                    message: format!("Unknown category: {}{}", cat, did_you_mean(&suggestions)),
+                    code: LintCode::ErrorUnknownCategory,
+                    rule: None,
+                    location: Location::Category(cat.clone()),
+                    fixes,
+                });
+            } else if belongs_to_another_unit_type(unit, &cat) {
+                sink.report(LintResult {
+                    severity: LintSeverity::Error,
+                    message: format!("Category {} has no meaning in a {:?} unit", cat, unit.unit_type().unwrap()),
+                    code: LintCode::ErrorUnknownCategory,
+                    rule: None,
+                    location: Location::Category(cat.clone()),
+                    fixes: vec!(),
+                });
+            }
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
 use systemd_parser;
+#[cfg(test)]
+use std::path::Path;
 
 #[test]
 fn success_case() {
@@ -42,8 +99,6 @@ fn success_case() {
         DummyThree=1
         [Automount]
         DummyFour=1
-        [BusName]
-        DummyFive=1
         [Path]
         DummySix=1
         [Timer]
@@ -51,9 +106,39 @@ fn success_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownCategory.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
+}
+
+#[test]
+fn success_case_the_section_of_each_unit_type() {
+    for &(file, category) in &[
+        ("foo.service", "Service"), ("foo.socket", "Socket"), ("foo.mount", "Mount"),
+        ("foo.automount", "Automount"), ("foo.swap", "Swap"), ("foo.timer", "Timer"),
+        ("foo.path", "Path"), ("foo.slice", "Slice"), ("foo.scope", "Scope"),
+    ] {
+        // arrange
+        let input = format!("[Unit]\nDescription=a dummy unit\n[{}]\nDummy=1\n[Install]\nWantedBy=multi-user.target\n", category);
+        let unit = systemd_parser::parse_unit_file(Path::new(file), &input).unwrap();
+        // act
+        let res = UnknownCategory.run(&unit);
+        // assert
+        assert!(res.is_empty(), "{}: {:?}", file, res)
+    }
+}
+
+#[test]
+fn success_case_units_without_a_section_of_their_own() {
+    for file in &["foo.target", "foo.device"] {
+        // arrange
+        let input = "[Unit]\nDescription=a dummy unit\n[Install]\nWantedBy=multi-user.target\n";
+        let unit = systemd_parser::parse_unit_file(Path::new(file), input).unwrap();
+        // act
+        let res = UnknownCategory.run(&unit);
+        // assert
+        assert!(res.is_empty(), "{}: {:?}", file, res)
+    }
 }
 
 #[test]
@@ -65,9 +150,9 @@ fn error_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownCategory.run(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res.is_empty());
 }
 
 #[test]
@@ -79,7 +164,7 @@ fn error_message_should_contains_the_bad_category() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit).unwrap_err();
+    let res = UnknownCategory.run(&unit).remove(0);
     // assert
     assert!(res.message.contains("Services"))
 }
@@ -100,7 +185,127 @@ fn success_lint_should_skip_x_categories() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownCategory.run(&unit);
+    // assert
+    assert!(res.is_empty())
+}
+
+#[test]
+fn error_case_category_of_another_unit_type() {
+    // arrange
+    let input = "
+        [Unit]
+        Description= a dummy unit
+        [Socket]
+        ListenStream=22
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("sshd.service"), input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit).remove(0);
+    // assert
+    assert!(res.message.contains("Socket"))
+}
+
+#[test]
+fn success_case_drop_ins_are_not_checked_against_their_unit_type() {
+    // arrange
+    let input = "
+        [Socket]
+        ListenStream=22
+    ";
+    let unit = systemd_parser::parse_unit_file(Path::new("sshd.socket.d/override.conf"), input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit);
+    // assert
+    assert!(res.is_empty())
+}
+
+#[test]
+fn error_case_every_unknown_category_is_reported() {
+    // arrange
+    let input = "
+        [Units]
+        Description=a dummy unit
+        [Services]
+        ExecStart=/bin/true
+        [Install]
+        WantedBy=multi-user.target
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit);
+    // assert
+    assert_eq!(2, res.len())
+}
+
+#[test]
+fn success_case_allowed_categories_should_be_skipped() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        [Internal]
+        Team=infra
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let ctx = LintContext {
+        allowed_categories: vec!["Internal".into()],
+        ..LintContext::default()
+    };
+    // act
+    let mut res = vec!();
+    UnknownCategory.check(&unit, &ctx, &mut res);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_suggests_the_closest_category() {
+    // arrange
+    let input = "
+        [Services]
+        ExecStart=/bin/true
+        [install]
+        WantedBy=multi-user.target
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit);
+    // assert
+    assert_eq!("Unknown category: Services (did you mean Service?)", res[0].message);
+    assert!(!res[0].fixes()[0].safe);
+    assert_eq!("Unknown category: install (did you mean Install?)", res[1].message);
+    assert_eq!(vec![Edit::RenameCategory {
+        category: "install".into(),
+        new_category: "Install".into(),
+    }], res[1].fixes()[0].edits);
+    assert!(!res[1].fixes()[0].safe);
+}
+
+#[test]
+fn error_case_one_letter_category() {
+    // arrange
+    let input = "
+        [A]
+        Description=a dummy unit
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit);
+    // assert
+    assert_eq!("Unknown category: A", res[0].message)
+}
+
+#[test]
+fn error_case_non_ascii_category() {
+    // arrange
+    let input = "
+        [設定]
+        Description=a dummy unit
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownCategory.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert_eq!("Unknown category: 設定", res[0].message)
 }
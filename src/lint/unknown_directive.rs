@@ -1,52 +1,78 @@
 
 use lint::*;
-use rustc_serialize::json;
+use lint::directives::DirectiveDatabase;
+use suggest::{closest, did_you_mean};
 use systemd_parser::items::*;
-use std::collections::HashMap;
 
-static DIRECTIVES: &'static str = include_str!("./directives.json");
+/// The known directives closest to `key`: the ones equal but for the case, else the
+/// closest ones of its category, else of any category
+fn suggestions<'a>(key: &str, category: &str, directives: &'a DirectiveDatabase) -> Vec<&'a str> {
 
-#[derive(PartialEq, Eq, Clone, Debug, RustcDecodable)]
-struct DocumentedDirective {
-    url: String,
-    field: String,
-}
-
-fn open_and_parse_directive_files() -> HashMap<String, DocumentedDirective> {
-    let vec: Vec<DocumentedDirective> = json::decode(DIRECTIVES).expect("json file should be ok");
-    let mut res = HashMap::with_capacity(vec.len());
-    for directive in vec {
-        res.insert(directive.field.clone(), directive);
+    let same_key = directives.find_ignore_case(key);
+    if !same_key.is_empty() {
+        return same_key;
     }
-    res
-}
-
-fn should_be_skipped(unit_entry: &DirectiveEntry) -> bool {
-    let cat = unit_entry.category();
-    let first_chars = &cat[0..2];
 
-    first_chars == "X-"
+    let res = closest(key, directives.keys_of_section(category));
+    if res.is_empty() {
+        closest(key, directives.keys())
+    } else {
+        res
+    }
 }
 
-pub fn lint(unit: &SystemdUnit) -> Result<(), LintResult> {
-
-    let directives = open_and_parse_directive_files();
+pub struct UnknownDirective;
 
-    let has_unknown = unit.keys()
-        .into_iter()
-        .find(|unit_entry| {
-            !directives.contains_key(&unit_entry.key()) && !should_be_skipped(unit_entry)
-        });
-
-    if let Some(unknown_directive) = has_unknown {
-        return Err(LintResult {
-            severity: LintSeverity::Error,
-            message: format!("Unknown directive found: {}", unknown_directive.key()),
-            code: LintCode::ErrorUnknownDirective,
-        });
+impl Lint for UnknownDirective {
+    fn code(&self) -> LintCode { LintCode::ErrorUnknownDirective }
+    fn name(&self) -> &'static str { "unknown-directive" }
+    fn rationale(&self) -> &'static str {
+        "systemd ignores unknown directives with a mere warning in the journal, so a typo \
+         silently disables a setting. Directives of X- categories, of the allowed categories \
+         and the allowed directives of the configuration are not checked."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Error }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Correctness] }
+
+    fn check(&self, unit: &SystemdUnit, ctx: &LintContext, sink: &mut dyn LintSink) {
+
+        let directives = DirectiveDatabase::shared();
+
+        let unknowns = unit.keys()
+            .into_iter()
+            .filter(|unit_entry| {
+                !directives.contains(&unit_entry.key()) && !ctx.is_extension(unit_entry)
+            });
+
+        for unknown_directive in unknowns {
+            let key = unknown_directive.key();
+            let category = unknown_directive.category();
+            let suggestions = suggestions(&key, &category, directives);
+
+            let fixes = suggestions
+                .iter()
+                .map(|suggestion| Fix {
+                    description: format!("Rename {}= to {}=", key, suggestion),
+                    // systemd ignores the misspelled directive: renaming it turns it on
+                    safe: false,
+                    edits: vec![Edit::RenameKey {
+                        category: category.clone(),
+                        key: key.clone(),
+                        new_key: String::from(*suggestion),
+                    }],
+                })
+                .collect();
+
+            sink.report(LintResult {
+                severity: LintSeverity::Error,
+                message: format!("Unknown directive found: {}{}", key, did_you_mean(&suggestions)),
+                code: LintCode::ErrorUnknownDirective,
+                rule: None,
+                location: Location::of(unknown_directive),
+                fixes,
+            });
+        }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -61,9 +87,9 @@ fn success_case_in_known_category() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownDirective.run(&unit);
     // assert
-    assert!(res.is_ok())
+    assert!(res.is_empty())
 }
 
 #[test]
@@ -75,9 +101,9 @@ fn success_case_x_categories_should_be_skipped() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownDirective.run(&unit);
     // assert
-    assert!(res.is_ok(), "{:?}", res)
+    assert!(res.is_empty(), "{:?}", res)
 }
 
 #[test]
@@ -89,9 +115,9 @@ fn error_case() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit);
+    let res = UnknownDirective.run(&unit);
     // assert
-    assert!(res.is_err());
+    assert!(!res.is_empty());
 }
 
 #[test]
@@ -103,7 +129,109 @@ fn error_case_message_contains_unknown_directive_name() {
     ";
     let unit = systemd_parser::parse_string(input).unwrap();
     // act
-    let res = lint(&unit).unwrap_err();
+    let res = UnknownDirective.run(&unit).remove(0);
     // assert
     assert!(res.message.contains("ExecStrat"))
 }
+
+#[test]
+fn error_case_directive_of_another_configuration_file() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        OWNER=root
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownDirective.run(&unit).remove(0);
+    // assert
+    assert!(res.message.starts_with("Unknown directive found: OWNER"), "{}", res.message)
+}
+
+#[test]
+fn error_case_every_unknown_directive_is_reported() {
+    // arrange
+    let input = "
+        [Unit]
+        Desciption=a dummy unit
+        [Service]
+        ExecStrat=/bin/true
+        Tpye=simple
+        Restart=always
+        Usr=nobody
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownDirective.run(&unit);
+    // assert
+    let messages: Vec<&str> = res.iter().map(|finding| &finding.message[..]).collect();
+    assert_eq!(vec![
+        "Unknown directive found: Desciption (did you mean Description?)",
+        "Unknown directive found: ExecStrat (did you mean ExecStart?)",
+        "Unknown directive found: Tpye (did you mean Type?)",
+        "Unknown directive found: Usr (did you mean User?)",
+    ], messages)
+}
+
+#[test]
+fn error_case_suggestions_are_looked_for_in_the_category_first() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        Wantedby=multi-user.target
+        [Install]
+        wantedBy=multi-user.target
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownDirective.run(&unit);
+    // assert
+    assert!(res[0].message.ends_with("(did you mean WantedBy?)"), "{}", res[0].message);
+    assert!(res[1].message.ends_with("(did you mean WantedBy?)"), "{}", res[1].message);
+}
+
+#[test]
+fn error_case_rename_fixes_are_unsafe() {
+    // arrange
+    let input = "
+        [Service]
+        execstart=/bin/true
+        ExecStrat=/bin/true
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    // act
+    let res = UnknownDirective.run(&unit);
+    // assert
+    let safe: Vec<Vec<bool>> = res.iter().map(|finding| finding.fixes().iter().map(|fix| fix.safe).collect()).collect();
+    assert_eq!(vec![vec![false], vec![false]], safe);
+    assert_eq!(Edit::RenameKey {
+        category: "Service".into(),
+        key: "execstart".into(),
+        new_key: "ExecStart".into(),
+    }, res[1].fixes()[0].edits[0]);
+}
+
+#[test]
+fn success_case_allowed_directives_and_categories_should_be_skipped() {
+    // arrange
+    let input = "
+        [Service]
+        ExecStart=/bin/true
+        InternalOwner=infra
+        [Internal]
+        Team=infra
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let ctx = LintContext {
+        allowed_categories: vec!["Internal".into()],
+        allowed_directives: vec!["InternalOwner".into()],
+        ..LintContext::default()
+    };
+    // act
+    let mut res = vec!();
+    UnknownDirective.check(&unit, &ctx, &mut res);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
@@ -0,0 +1,266 @@
+
+use lint::*;
+use systemd_parser::items::*;
+use systemd_parser::suppression::{Suppression, SuppressionScope};
+
+pub struct UnusedSuppression;
+
+impl Lint for UnusedSuppression {
+    fn code(&self) -> LintCode { LintCode::WarnUnusedSuppression }
+    fn name(&self) -> &'static str { "unused-suppression" }
+    fn rationale(&self) -> &'static str {
+        "A `# systemd-lint: disable=...` comment which silences nothing is either stale, \
+         since the unit has been fixed, or misplaced, and would hide a future problem."
+    }
+    fn default_severity(&self) -> LintSeverity { LintSeverity::Warning }
+    fn groups(&self) -> &'static [LintGroup] { &[LintGroup::Style] }
+
+    // the registry reports the unused suppressions, as it knows what the other lints found
+    fn check(&self, _unit: &SystemdUnit, _ctx: &LintContext, _sink: &mut dyn LintSink) {}
+}
+
+/// A lint named in a suppression comment, or a rule, which is reported by its lint
+struct Silenced<'a> {
+    id: &'a str,
+    lint: Option<&'a dyn Lint>,
+    rule: bool,
+    used: bool,
+}
+
+/// The suppression comments of a unit, along with the lints they silence
+pub struct Suppressions<'a> {
+    entries: Vec<(&'a Suppression, Vec<Silenced<'a>>)>,
+}
+
+impl<'a> Suppressions<'a> {
+    pub fn new(unit: &'a SystemdUnit, registry: &'a Registry, settings: &Settings) -> Suppressions<'a> {
+        let rule_ids = settings.rule_ids();
+        let entries = unit.suppressions()
+            .iter()
+            .map(|suppression| {
+                let silenced = suppression.lints
+                    .iter()
+                    .map(|id| match registry.find_id(id, &rule_ids) {
+                        Some(LintId::Rule(_)) => Silenced { id, lint: Some(rule_lint(settings, id)), rule: true, used: false },
+                        _ => Silenced { id, lint: registry.find(id), rule: false, used: false },
+                    })
+                    .collect();
+                (suppression, silenced)
+            })
+            .collect();
+
+        Suppressions { entries }
+    }
+
+    /// Tells if the finding is silenced, and remember the suppressions which silence it
+    pub fn suppress(&mut self, finding: &LintResult) -> bool {
+        let mut suppressed = false;
+
+        for &mut (suppression, ref mut silenced) in &mut self.entries {
+            if !covers(&suppression.scope, &finding.location) {
+                continue;
+            }
+            let silencing = silenced
+                .iter_mut()
+                .filter(|silenced| match silenced.lint {
+                    Some(_) if silenced.rule => LintId::Rule(silenced.id.into()).matches(finding.code, finding.rule()),
+                    Some(lint) => lint.code() == finding.code,
+                    None => false,
+                });
+            for lint in silencing {
+                lint.used = true;
+                suppressed = true;
+            }
+        }
+
+        suppressed
+    }
+
+    /// Report the suppressions of unknown lints, and the ones which silenced nothing.
+    /// The lints and the rules disabled by the settings are not run, so they are left out.
+    pub fn report_unused(&self, settings: &Settings, sink: &mut dyn LintSink) {
+        for &(suppression, ref silenced) in &self.entries {
+            for lint in silenced.iter().filter(|lint| !lint.used) {
+                let message = match lint.lint {
+                    None => format!("Unknown lint in suppression comment: {}", lint.id),
+                    Some(found) => {
+                        let ignored = if lint.rule {
+                            settings.ignores_rule(found, lint.id)
+                        } else {
+                            settings.severity(found) == LintSeverity::Ignore
+                        };
+                        if ignored {
+                            continue;
+                        }
+                        format!("Suppression of {} {} silences nothing", lint.id, describe(&suppression.scope))
+                    }
+                };
+
+                sink.report(LintResult {
+                    severity: LintSeverity::Warning,
+                    message,
+                    code: LintCode::WarnUnusedSuppression,
+                    rule: None,
+                    location: location(&suppression.scope),
+                    fixes: vec!(),
+                });
+            }
+        }
+    }
+}
+
+/// The lint which reports the findings of a rule
+fn rule_lint(settings: &Settings, id: &str) -> &'static dyn Lint {
+    if settings.rules.iter().any(|rule| rule.id == id) {
+        &custom_rule::CustomRules
+    } else {
+        &plugin::PluginFinding
+    }
+}
+
+fn covers(scope: &SuppressionScope, location: &Location) -> bool {
+    match *scope {
+        SuppressionScope::File => true,
+        SuppressionScope::Section(ref category) => location.category() == Some(category),
+        SuppressionScope::Directive(Some((ref category, ref key))) => {
+            *location == Location::Directive { category: category.clone(), key: key.clone() }
+        }
+        SuppressionScope::Directive(None) => false,
+    }
+}
+
+fn describe(scope: &SuppressionScope) -> String {
+    match *scope {
+        SuppressionScope::File => "in the file".into(),
+        SuppressionScope::Section(ref category) => format!("in [{}]", category),
+        SuppressionScope::Directive(Some((ref category, ref key))) => format!("for {}= in [{}]", key, category),
+        SuppressionScope::Directive(None) => "not followed by a directive".into(),
+    }
+}
+
+fn location(scope: &SuppressionScope) -> Location {
+    match *scope {
+        SuppressionScope::File | SuppressionScope::Directive(None) => Location::Unit,
+        SuppressionScope::Section(ref category) => Location::Category(category.clone()),
+        SuppressionScope::Directive(Some((ref category, ref key))) => {
+            Location::Directive { category: category.clone(), key: key.clone() }
+        }
+    }
+}
+
+#[cfg(test)]
+use systemd_parser;
+
+#[cfg(test)]
+fn check(input: &str) -> Vec<LintResult> {
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let mut res = vec!();
+    Registry::new().check(&unit, &Settings::default(), &mut res);
+    res
+}
+
+#[test]
+fn success_case_disable_silences_the_next_directive() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        [Service]
+        Type=simple
+        ExecStart=/bin/true
+        # systemd-lint: disable=unknown-directive
+        InternalOwner=infra
+    ";
+    // act
+    let res = check(input);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn success_case_disable_section_and_disable_file() {
+    // arrange
+    let input = "
+        # systemd-lint: disable-file=missing-description
+        [Service]
+        # systemd-lint: disable-section=ErrorUnknownDirective,30000
+        ExecStart=/bin/true
+        InternalOwner=infra
+        InternalTeam=infra
+    ";
+    // act
+    let res = check(input);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
+
+#[test]
+fn error_case_disable_does_not_silence_other_directives() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        [Service]
+        Type=simple
+        # systemd-lint: disable=unknown-directive
+        ExecStart=/bin/true
+        InternalOwner=infra
+    ";
+    // act
+    let res = check(input);
+    // assert
+    let codes: Vec<LintCode> = res.iter().map(|finding| finding.code).collect();
+    assert_eq!(vec![LintCode::ErrorUnknownDirective, LintCode::WarnUnusedSuppression], codes)
+}
+
+#[test]
+fn error_case_suppression_which_silences_nothing() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        # systemd-lint: disable=ErrorInvalidCondition
+        ConditionPathExists=/etc/foo
+    ";
+    // act
+    let res = check(input);
+    // assert
+    assert_eq!(1, res.len());
+    assert_eq!(LintCode::WarnUnusedSuppression, res[0].code);
+    assert!(res[0].message.contains("ConditionPathExists"), "{}", res[0].message)
+}
+
+#[test]
+fn error_case_suppression_of_an_unknown_lint() {
+    // arrange
+    let input = "
+        [Unit]
+        Description=a dummy unit
+        # systemd-lint: disable-file=plop
+    ";
+    // act
+    let res = check(input);
+    // assert
+    assert_eq!(1, res.len());
+    assert!(res[0].message.contains("plop"))
+}
+
+#[test]
+fn success_case_suppressions_of_disabled_lints_are_not_reported() {
+    // arrange
+    let input = "
+        [Unit]
+        # systemd-lint: disable-file=missing-description
+        Wants=foo.service
+    ";
+    let unit = systemd_parser::parse_string(input).unwrap();
+    let settings = Settings {
+        severities: vec![(LintCode::LintMissingDescription.into(), LintSeverity::Ignore)],
+        ..Settings::default()
+    };
+    // act
+    let mut res = vec!();
+    Registry::new().check(&unit, &settings, &mut res);
+    // assert
+    assert!(res.is_empty(), "{:?}", res)
+}
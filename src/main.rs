@@ -4,17 +4,20 @@
 
 extern crate clap;
 extern crate colored;
-extern crate rustc_serialize;
-extern crate systemd_parser;
+extern crate systemd_linter;
 
-mod support;
-mod lint;
+use clap::{Arg, App, ArgMatches, ErrorKind};
+use systemd_linter::{baseline, cache, diff, fix, lint, stats, UnitFile};
 
-use clap::{Arg, App};
+/// Exit statuses: 0 when no finding reaches the threshold
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARSE_ERROR: i32 = 3;
 
 pub fn main() {
+    use colored::*;
     use std::fs::File;
-    use std::io::Read;
+    use std::io::Write;
     use std::path::Path;
 
     let matches = App::new("systemd-lint")
@@ -22,37 +25,349 @@ pub fn main() {
         .author("Thomas Wickham <twickham@octo.com>")
         .about("lint systemd unit files")
         .arg(Arg::with_name("INPUT")
-            .help("Sets the input file to use")
-            .required(true))
-        .get_matches();
+            .help("Sets the input file to use, or the files and directories to lint with --stats")
+            .multiple(true)
+            .required_unless_one(&["list-lints", "explain"]))
+        .arg(Arg::with_name("list-lints")
+            .long("list-lints")
+            .help("Lists the available lints"))
+        .arg(Arg::with_name("explain")
+            .long("explain")
+            .value_name("LINT")
+            .takes_value(true)
+            .help("Explains a lint, given its name or its code"))
+        .arg(Arg::with_name("fix")
+            .long("fix")
+            .help("Applies the safe fixes to the file, then lints the result"))
+        .arg(Arg::with_name("diff")
+            .long("diff")
+            .conflicts_with("fix")
+            .help("Prints the fixes as a unified diff, without changing the file"))
+        .arg(Arg::with_name("unsafe-fixes")
+            .long("unsafe-fixes")
+            .help("Applies the fixes which may change the behaviour of the unit too"))
+        .arg(Arg::with_name("systemd-version")
+            .long("systemd-version")
+            .value_name("VERSION")
+            .takes_value(true)
+            .help("Sets the systemd version the units target, overriding the configuration"))
+        .arg(Arg::with_name("select")
+            .long("select")
+            .value_name("LINTS")
+            .takes_value(true)
+            .multiple(true)
+            .require_delimiter(true)
+            .help("Only runs these lints, given by name, code, code prefix or group, separated by commas"))
+        .arg(Arg::with_name("ignore")
+            .long("ignore")
+            .value_name("LINTS")
+            .takes_value(true)
+            .multiple(true)
+            .require_delimiter(true)
+            .help("Does not run these lints, given by name, code, code prefix or group, separated by commas"))
+        .arg(Arg::with_name("deny")
+            .long("deny")
+            .value_name("SEVERITY")
+            .takes_value(true)
+            .possible_values(&["lint", "warning", "error"])
+            .help("Fails when a finding has this severity or a higher one [default: error]"))
+        .arg(Arg::with_name("warn-as-error")
+            .long("warn-as-error")
+            .conflicts_with("deny")
+            .help("Fails on warnings too, same as --deny warning"))
+        .arg(Arg::with_name("baseline")
+            .long("baseline")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Only reports the findings which are not recorded in the baseline FILE"))
+        .arg(Arg::with_name("write-baseline")
+            .long("write-baseline")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with_all(&["baseline", "fix", "diff"])
+            .help("Records the findings of the file in the baseline FILE, which is created when missing"))
+        .arg(Arg::with_name("stats")
+            .long("stats")
+            .value_name("FORMAT")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .possible_values(&["table", "json"])
+            .conflicts_with_all(&["fix", "diff", "baseline", "write-baseline"])
+            .help("Lints the unit files of every INPUT and reports the time taken and the findings by lint, \
+                   severity and file, instead of the findings [default: table]"))
+        .arg(Arg::with_name("allow-plugins")
+            .long("allow-plugins")
+            .help("Runs the plugins of the configuration, which are not run otherwise as they may be any program"))
+        .arg(Arg::with_name("no-cache")
+            .long("no-cache")
+            .help("Lints the file even when its findings are cached"))
+        .arg(Arg::with_name("cache-dir")
+            .long("cache-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .conflicts_with("no-cache")
+            .help("Caches the findings of the files in DIR [default: $XDG_CACHE_HOME/systemd-linter]"))
+        .get_matches_safe()
+        .unwrap_or_else(|err| match err.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => err.exit(),
+            _ => {
+                let _ = writeln!(std::io::stderr(), "{}", err.message);
+                std::process::exit(EXIT_USAGE)
+            }
+        });
+
+    let threshold = if matches.is_present("warn-as-error") {
+        lint::LintSeverity::Warning
+    } else {
+        matches.value_of("deny")
+            .and_then(lint::LintSeverity::from_name)
+            .unwrap_or(lint::LintSeverity::Error)
+    };
+
+    let registry = lint::Registry::new();
+
+    if matches.is_present("list-lints") {
+        for lint in registry.lints() {
+            let groups: Vec<&str> = lint.groups().iter().map(|group| group.name()).collect();
+            println!("{:>6}  {:<30} {:<8} {}", lint.code() as u32, lint.name(), format!("{:?}", lint.default_severity()), groups.join(","));
+        }
+        return;
+    }
+
+    if let Some(id) = matches.value_of("explain") {
+        match registry.find(id) {
+            Some(lint) => explain(lint),
+            None => error_and_exit(format!("unknown lint: {}", id)),
+        }
+        return;
+    }
 
-    let filepath = Path::new(matches.value_of("INPUT").expect("clap should ensure INPUT is set"));
+    if matches.is_present("stats") {
+        return stats(&matches, &registry, threshold);
+    }
+
+    let inputs: Vec<&str> = matches.values_of("INPUT").expect("clap should ensure INPUT is set").collect();
+    if inputs.len() > 1 {
+        error_and_exit("a single INPUT is linted at a time, unless --stats is given".into())
+    }
+    let filepath = Path::new(inputs[0]);
     if !filepath.exists() {
         error_and_exit("path does not exists !".into())
     }
 
-    let mut contents = String::with_capacity(4096);
-    let mut file = File::open(filepath)
-        .unwrap_or_else(|err| format_res_and_exit(err, "file is not readable"));
+    let mut file = UnitFile::read(&registry, filepath).unwrap_or_else(fail);
+    apply_arguments(&matches, &registry, &mut file);
+
+    if matches.is_present("fix") || matches.is_present("diff") {
+        let (bom, original) = file.raw_source();
+        let fixed = fix::fix(&original, filepath, &registry, &file.settings, matches.is_present("unsafe-fixes"));
+
+        if matches.is_present("diff") {
+            print!("{}", diff::unified_diff(&original, &fixed.source, &filepath.to_string_lossy()));
+            return;
+        }
+
+        if fixed.source != original {
+            if !file.is_text() {
+                error_and_exit("refusing to fix a file which is not valid UTF-8 text".into())
+            }
+            File::create(filepath)
+                .and_then(|mut file| file.write_all(format!("{}{}", bom, fixed.source).as_bytes()))
+                .unwrap_or_else(|err| format_res_and_exit(err, "error when writing file"));
+            for description in fixed.applied {
+                println!("** {}: {}\n", "Fixed".green(), description);
+            }
+            file.set_source(&fixed.source);
+        }
+    }
+
+    // the plugins are not part of the key: they may change without the settings
+    let cache = if matches.is_present("no-cache") || !file.settings.plugins.is_empty() {
+        None
+    } else {
+        matches.value_of("cache-dir").map(std::path::PathBuf::from)
+            .or_else(cache::Cache::default_dir)
+            .map(|dir| (cache::Cache::new(&dir), cache::Key::new(filepath, file.contents(), &file.settings)))
+    };
 
-    file.read_to_string(&mut contents)
-        .unwrap_or_else(|err| format_res_and_exit(err, "error when reading file"));
+    let cached = cache.as_ref().and_then(|(cache, key)| cache.get(key, &registry));
+    let mut findings = cached.unwrap_or_else(|| {
+        let unit = file.parse().unwrap_or_else(fail);
 
-    let unit_file = systemd_parser::parse_string(&contents)
-        .unwrap_or_else(|err| format_res_and_exit(err, "PARSE ERROR"));
+        let mut findings = vec!();
+        file.check(&registry, &unit, &mut findings);
+        if let Some((ref cache, ref key)) = cache {
+            // best effort, the next run lints the file again
+            let _ = cache.put(key, &findings);
+        }
+        findings
+    });
 
+    if let Some(baseline_path) = matches.value_of("write-baseline").map(Path::new) {
+        let mut baseline = if baseline_path.exists() {
+            baseline::Baseline::load(baseline_path)
+                .unwrap_or_else(|err| error_and_exit(format!("invalid baseline: {}", err)))
+        } else {
+            baseline::Baseline::default()
+        };
+        baseline.record(&baseline::file_key(baseline_path, filepath), &findings);
+        baseline.save(baseline_path).unwrap_or_else(error_and_exit);
+        println!("** {}: {} finding(s) recorded in {}\n", "Baseline".green(), findings.len(), baseline_path.display());
+        return;
+    }
 
-    let mut has_errors = false;
+    if let Some(baseline_path) = matches.value_of("baseline").map(Path::new) {
+        let baseline = baseline::Baseline::load(baseline_path)
+            .unwrap_or_else(|err| error_and_exit(format!("invalid baseline: {}", err)));
+        let filtered = baseline.filter(&baseline::file_key(baseline_path, filepath), findings);
+        for entry in filtered.resolved {
+            let lint = registry.find(&entry.code.to_string()).map(|lint| lint.name());
+            let name = entry.rule.as_ref().map(|rule| &rule[..]).or(lint).unwrap_or("");
+            println!("** {}[{}]: {}", "Resolved".green(), name, entry.message);
+            println!("   not found anymore, remove it from the baseline with --write-baseline\n");
+        }
+        findings = filtered.new;
+    }
 
-    for lint_f in lint::ALL_LINTS.iter() {
-        let res = lint_f(&unit_file);
-        has_errors = has_errors || res.is_ok();
+    for res in &findings {
+        print_finding(&registry, res);
+    }
 
-        println!("** {:?}\n", res);
+    let failing = findings.iter().filter(|res| res.severity() >= threshold).count();
+    if failing > 0 {
+        exit_with(EXIT_FINDINGS, format!("{} finding(s) at or above the {} severity", failing, threshold.name()))
     }
+}
+
+/// Override the settings of the configuration of the file by the arguments
+fn apply_arguments(matches: &ArgMatches, registry: &lint::Registry, file: &mut UnitFile) {
 
-    if has_errors {
-        error_and_exit(String::from("Lint errors. Exiting"))
+    let settings = &mut file.settings;
+    settings.allow_plugins = matches.is_present("allow-plugins");
+
+    if let Some(version) = matches.value_of("systemd-version") {
+        let version = version.parse()
+            .unwrap_or_else(|_| error_and_exit(format!("invalid systemd version: {}", version)));
+        settings.context.systemd_version = Some(version);
+    }
+
+    if let Some(selectors) = matches.values_of("select") {
+        let mut selected = vec!();
+        for selector in selectors {
+            selected.extend(registry.select_ids(selector, &file.rule_ids).unwrap_or_else(error_and_exit));
+        }
+        settings.selected = Some(selected);
+    }
+    if let Some(selectors) = matches.values_of("ignore") {
+        for selector in selectors {
+            for id in registry.select_ids(selector, &file.rule_ids).unwrap_or_else(error_and_exit) {
+                settings.severities.push((id, lint::LintSeverity::Ignore));
+            }
+        }
+    }
+}
+
+/// `--stats`: lint the unit files of every INPUT, then print the report instead of
+/// the findings. The exit status is the same as when printing them.
+fn stats(matches: &ArgMatches, registry: &lint::Registry, threshold: lint::LintSeverity) {
+    use std::time::Instant;
+
+    let mut stats = stats::Stats::default();
+    let mut failing = 0;
+
+    let start = Instant::now();
+    lint::preload_directives();
+    stats.loaded_database(start.elapsed());
+
+    for input in matches.values_of("INPUT").expect("clap should ensure INPUT is set") {
+        let files = systemd_linter::unit_files(std::path::Path::new(input))
+            .unwrap_or_else(|err| error_and_exit(err.to_string()));
+
+        for filepath in files {
+            let mut file = UnitFile::read(registry, &filepath).unwrap_or_else(fail);
+            apply_arguments(matches, registry, &mut file);
+
+            let start = Instant::now();
+            let unit = file.parse();
+            stats.parsed(start.elapsed(), unit.is_ok());
+            let unit = match unit {
+                Ok(unit) => unit,
+                Err(_) => continue,
+            };
+
+            let mut findings = vec!();
+            file.check(registry, &unit, &mut stats.sink(&mut findings));
+            stats.checked(&filepath, findings.len());
+            failing += findings.iter().filter(|res| res.severity() >= threshold).count();
+        }
+    }
+
+    match matches.value_of("stats") {
+        Some("json") => print!("{}", stats.to_json(registry)),
+        _ => print!("{}", stats.to_table(registry)),
+    }
+
+    if stats.parse_errors() > 0 {
+        exit_with(EXIT_PARSE_ERROR, format!("{} file(s) could not be parsed", stats.parse_errors()))
+    }
+    if failing > 0 {
+        exit_with(EXIT_FINDINGS, format!("{} finding(s) at or above the {} severity", failing, threshold.name()))
+    }
+}
+
+/// `** Error[unknown-directive] [Service] Foo=: message`, then the available fixes
+fn print_finding(registry: &lint::Registry, res: &lint::LintResult) {
+    use colored::*;
+    use lint::LintSeverity;
+
+    let severity = format!("{:?}", res.severity());
+    let severity = match res.severity() {
+        LintSeverity::Error => severity.red(),
+        LintSeverity::Warning => severity.yellow(),
+        LintSeverity::Lint | LintSeverity::Ignore => severity.cyan(),
+    };
+    let lint = registry.find(&(res.code() as u32).to_string());
+    let name = res.rule().or_else(|| lint.map(|lint| lint.name())).unwrap_or("");
+    let location = res.location().to_string();
+
+    if location.is_empty() {
+        println!("** {}[{}]: {}", severity, name, res.message());
+    } else {
+        println!("** {}[{}] {}: {}", severity, name, location, res.message());
+    }
+    for fix in res.fixes() {
+        println!("   fix{}: {}", if fix.safe { "" } else { " (unsafe)" }, fix.description);
+    }
+    if let Some(url) = lint.and_then(|lint| lint.docs_url()) {
+        println!("   docs: {}", url);
+    }
+    println!();
+}
+
+fn explain(lint: &dyn lint::Lint) {
+    println!("{} ({:?}, {})", lint.name(), lint.code(), lint.code() as u32);
+    println!("default severity: {:?}", lint.default_severity());
+    let groups: Vec<&str> = lint.groups().iter().map(|group| group.name()).collect();
+    println!("groups: {}", groups.join(", "));
+    if !lint.categories().is_empty() {
+        println!("categories: {}", lint.categories().join(", "));
+    }
+    if let Some(unit_types) = lint.unit_types() {
+        let names: Vec<String> = unit_types.iter().map(|unit_type| format!("{:?}", unit_type)).collect();
+        println!("unit types: {}", names.join(", "));
+    }
+    println!("\n{}", lint.rationale());
+    if let Some(url) = lint.docs_url() {
+        println!("\nsee {}", url);
+    }
+}
+
+/// Parse errors have their own exit status
+fn fail<T>(err: systemd_linter::Error) -> T {
+    match err {
+        systemd_linter::Error::Parse(_) => exit_with(EXIT_PARSE_ERROR, format!("PARSE ERROR: {}", err)),
+        _ => error_and_exit(err.to_string()),
     }
 }
 
@@ -61,14 +376,19 @@ fn format_res_and_exit<T, Err: std::error::Error>(err: Err, msg: &str) -> T {
     error_and_exit(msg)
 }
 
-#[allow(unreachable_code)]
+/// Usage and I/O errors
 fn error_and_exit<T>(msg: String) -> T {
+    exit_with(EXIT_USAGE, msg)
+}
+
+#[allow(unreachable_code)]
+fn exit_with<T>(status: i32, msg: String) -> T {
     use colored::*;
     use std::io::stderr;
     use std::io::Write;
     use std::process::exit;
 
     let _ = writeln!(stderr(), "{}: {}", "Error".red().bold(), msg.red());
-    exit(1);
+    exit(status);
     unreachable!()
 }
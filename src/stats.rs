@@ -0,0 +1,245 @@
+use lint::{Lint, LintCode, LintResult, LintSeverity, LintSink, Registry};
+use rustc_serialize::json::{self, Json};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// How many of the files with the most findings are reported
+pub const TOP_FILES: usize = 10;
+
+/// What `--stats` reports about a run over many files: how long parsing and each
+/// lint took, and which lints and files have the most findings
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    files: usize,
+    parse_errors: usize,
+    parse_time: Duration,
+    database_time: Duration,
+    /// By lint name: the custom rules have theirs
+    lints: BTreeMap<String, LintTime>,
+    findings: BTreeMap<u32, usize>,
+    severities: BTreeMap<LintSeverity, usize>,
+    files_findings: Vec<(String, usize)>,
+}
+
+#[derive(Clone, Debug)]
+struct LintTime {
+    code: LintCode,
+    time: Duration,
+}
+
+/// Forwards the findings to the inner sink, counting them and the time of the lints
+pub struct StatsSink<'a> {
+    stats: &'a mut Stats,
+    inner: &'a mut dyn LintSink,
+}
+
+impl<'a> LintSink for StatsSink<'a> {
+    fn report(&mut self, result: LintResult) {
+        *self.stats.findings.entry(result.code() as u32).or_insert(0) += 1;
+        *self.stats.severities.entry(result.severity()).or_insert(0) += 1;
+        self.inner.report(result)
+    }
+
+    fn timed(&mut self, lint: &dyn Lint, elapsed: Duration) {
+        let time = self.stats.lints
+            .entry(lint.name().into())
+            .or_insert(LintTime { code: lint.code(), time: Duration::default() });
+        time.time += elapsed;
+    }
+}
+
+impl Stats {
+
+    /// A file was parsed, successfully or not
+    pub fn parsed(&mut self, elapsed: Duration, ok: bool) {
+        self.files += 1;
+        self.parse_time += elapsed;
+        if !ok {
+            self.parse_errors += 1;
+        }
+    }
+
+    pub fn loaded_database(&mut self, elapsed: Duration) {
+        self.database_time = elapsed;
+    }
+
+    pub fn parse_errors(&self) -> usize {
+        self.parse_errors
+    }
+
+    /// The sink to check a file with
+    pub fn sink<'a>(&'a mut self, inner: &'a mut dyn LintSink) -> StatsSink<'a> {
+        StatsSink { stats: self, inner }
+    }
+
+    /// A file was checked, with that many findings
+    pub fn checked(&mut self, file: &Path, findings: usize) {
+        if findings > 0 {
+            self.files_findings.push((file.display().to_string(), findings));
+        }
+    }
+
+    /// The files with the most findings, then by name
+    fn top_files(&self) -> Vec<&(String, usize)> {
+        let mut files: Vec<&(String, usize)> = self.files_findings.iter().collect();
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files.truncate(TOP_FILES);
+        files
+    }
+
+    /// The slowest lints first
+    fn slowest_lints(&self) -> Vec<(&String, &LintTime)> {
+        let mut lints: Vec<(&String, &LintTime)> = self.lints.iter().collect();
+        lints.sort_by(|a, b| b.1.time.cmp(&a.1.time).then_with(|| a.0.cmp(b.0)));
+        lints
+    }
+
+    pub fn to_table(&self, registry: &Registry) -> String {
+
+        let total: usize = self.findings.values().sum();
+        let lint_time = self.lints.values().map(|time| time.time).sum::<Duration>();
+        let mut res = format!(
+            "files: {} ({} parse error(s))\ndatabase load time: {}\nparse time: {}\nlint time: {}\nfindings: {}\n",
+            self.files, self.parse_errors, millis(self.database_time), millis(self.parse_time), millis(lint_time), total,
+        );
+
+        res.push_str(&format!("\n{:<30} {:>6} {:>12}\n", "lint", "code", "time"));
+        for (name, time) in self.slowest_lints() {
+            res.push_str(&format!("{:<30} {:>6} {:>12}\n", name, time.code as u32, millis(time.time)));
+        }
+
+        res.push_str(&format!("\n{:>6} {:<30} {:>8}\n", "code", "lint", "findings"));
+        for (&code, count) in &self.findings {
+            res.push_str(&format!("{:>6} {:<30} {:>8}\n", code, lint_name(registry, code), count));
+        }
+
+        res.push_str(&format!("\n{:<10} {:>8}\n", "severity", "findings"));
+        for (severity, count) in self.severities.iter().rev() {
+            res.push_str(&format!("{:<10} {:>8}\n", severity.name(), count));
+        }
+
+        res.push_str(&format!("\n{:>8} {}\n", "findings", "file"));
+        for &(ref file, count) in self.top_files() {
+            res.push_str(&format!("{:>8} {}\n", count, file));
+        }
+
+        res
+    }
+
+    pub fn to_json(&self, registry: &Registry) -> String {
+
+        let object = |fields: Vec<(&str, Json)>| {
+            Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+        };
+
+        let lints = self.slowest_lints()
+            .into_iter()
+            .map(|(name, time)| object(vec![
+                ("name", Json::String(name.clone())),
+                ("code", Json::U64(time.code as u64)),
+                ("time_ms", Json::F64(as_millis(time.time))),
+            ]))
+            .collect();
+        let findings = self.findings
+            .iter()
+            .map(|(&code, &count)| object(vec![
+                ("code", Json::U64(code.into())),
+                ("lint", Json::String(lint_name(registry, code).into())),
+                ("findings", Json::U64(count as u64)),
+            ]))
+            .collect();
+        let severities = self.severities
+            .iter()
+            .map(|(severity, &count)| (severity.name().to_string(), Json::U64(count as u64)))
+            .collect();
+        let top_files = self.top_files()
+            .into_iter()
+            .map(|&(ref file, count)| object(vec![
+                ("file", Json::String(file.clone())),
+                ("findings", Json::U64(count as u64)),
+            ]))
+            .collect();
+
+        let stats = object(vec![
+            ("files", Json::U64(self.files as u64)),
+            ("parse_errors", Json::U64(self.parse_errors as u64)),
+            ("database_time_ms", Json::F64(as_millis(self.database_time))),
+            ("parse_time_ms", Json::F64(as_millis(self.parse_time))),
+            ("lints", Json::Array(lints)),
+            ("findings", Json::Array(findings)),
+            ("severities", Json::Object(severities)),
+            ("top_files", Json::Array(top_files)),
+        ]);
+        format!("{}\n", json::as_pretty_json(&stats))
+    }
+}
+
+fn lint_name(registry: &Registry, code: u32) -> &str {
+    registry.find(&code.to_string()).map_or("", |lint| lint.name())
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// e.g. `12.345ms`
+fn millis(duration: Duration) -> String {
+    format!("{:.3}ms", as_millis(duration))
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+    use lint::Settings;
+    use systemd_parser;
+
+    fn lint(stats: &mut Stats, file: &str, input: &str) {
+        let unit = systemd_parser::parse_string(input).unwrap();
+        stats.parsed(Duration::from_millis(1), true);
+        let mut findings = vec!();
+        Registry::new().check(&unit, &Settings::default(), &mut stats.sink(&mut findings));
+        stats.checked(Path::new(file), findings.len());
+    }
+
+    #[test]
+    fn it_should_count_the_findings_by_code_severity_and_file() {
+        let mut stats = Stats::default();
+
+        lint(&mut stats, "foo.service", "[Service]\nFoo=1\nExecStart=/bin/true\n");
+        lint(&mut stats, "bar.service", "[Unit]\nDescription=bar\n[Service]\nExecStart=/bin/true\n");
+        lint(&mut stats, "baz.service", "[Service]\nType=Simple\nExecStart=/bin/true\n");
+
+        assert_eq!(3, stats.files);
+        assert_eq!(Some(&1), stats.findings.get(&(LintCode::ErrorUnknownDirective as u32)));
+        assert_eq!(Some(&2), stats.findings.get(&(LintCode::LintMissingDescription as u32)));
+        assert_eq!(Some(&2), stats.severities.get(&LintSeverity::Error));
+        let top_files: Vec<(&str, usize)> = stats.top_files().iter().map(|&&(ref file, count)| (&file[..], count)).collect();
+        assert_eq!(vec![("foo.service", 3), ("baz.service", 2), ("bar.service", 1)], top_files);
+    }
+
+    #[test]
+    fn it_should_time_the_lints_which_ran() {
+        let mut stats = Stats::default();
+
+        lint(&mut stats, "foo.socket", "[Socket]\nListenStream=80\n");
+
+        assert_eq!(Some(LintCode::ErrorInvalidListenAddress), stats.lints.get("invalid-listen-address").map(|time| time.code));
+        assert!(!stats.lints.contains_key("service-execstart-not-set"));
+    }
+
+    #[test]
+    fn it_should_report_as_json() {
+        let mut stats = Stats::default();
+        lint(&mut stats, "foo.service", "[Service]\nFoo=1\nExecStart=/bin/true\n");
+        stats.parsed(Duration::from_millis(1), false);
+
+        let report = Json::from_str(&stats.to_json(&Registry::new())).unwrap();
+
+        assert_eq!(Some(2), report.find("files").and_then(Json::as_u64));
+        assert_eq!(Some(1), report.find("parse_errors").and_then(Json::as_u64));
+        assert_eq!(Some(1), report.find_path(&["severities", "error"]).and_then(Json::as_u64));
+        let top_file = &report.find("top_files").and_then(Json::as_array).unwrap()[0];
+        assert_eq!(Some("foo.service"), top_file.find("file").and_then(Json::as_string));
+    }
+}
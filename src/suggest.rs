@@ -0,0 +1,123 @@
+
+use std::cmp;
+
+/// How many candidates are suggested, at most
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Edit distance in chars, where swapping two adjacent chars counts as one edit
+/// (optimal string alignment distance)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j]: distance between a[..i] and b[..j]
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..a.len() + 1 {
+        for j in 1..b.len() + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = cmp::min(
+                distances[i - 1][j - 1] + cost,
+                cmp::min(distances[i - 1][j], distances[i][j - 1]) + 1,
+            );
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = cmp::min(distance, distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// The candidates which `word` is probably a misspelling of: the closest ones, if
+/// they are close enough. A candidate equal to the word but for the case is the
+/// only suggestion.
+pub fn closest<'a, I>(word: &str, candidates: I) -> Vec<&'a str>
+    where I: IntoIterator<Item = &'a str> {
+
+    let lowercase = word.to_lowercase();
+    let max_distance = cmp::max(1, word.chars().count() / 3);
+
+    let mut res: Vec<(usize, &str)> = vec!();
+    for candidate in candidates {
+        if candidate == word {
+            continue;
+        }
+        let candidate_lowercase = candidate.to_lowercase();
+        if candidate_lowercase == lowercase {
+            return vec![candidate];
+        }
+
+        let distance = edit_distance(&lowercase, &candidate_lowercase);
+        if distance <= max_distance && !res.iter().any(|&(_, found)| found == candidate) {
+            res.push((distance, candidate));
+        }
+    }
+
+    let best = res.iter().map(|&(distance, _)| distance).min();
+    res.sort();
+    res.into_iter()
+        .filter(|&(distance, _)| Some(distance) == best)
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// ` (did you mean A or B?)`, or nothing without suggestions
+pub fn did_you_mean(suggestions: &[&str]) -> String {
+    match suggestions.split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(" (did you mean {}?)", last),
+        Some((last, others)) => format!(" (did you mean {} or {}?)", others.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+
+    #[test]
+    fn edit_distance_should_count_insertions_deletions_substitutions_and_swaps() {
+        assert_eq!(0, edit_distance("ExecStart", "ExecStart"));
+        assert_eq!(1, edit_distance("ExecStrat", "ExecStart"));
+        assert_eq!(2, edit_distance("ExecStrt", "ExecStar"));
+        assert_eq!(1, edit_distance("Desciption", "Description"));
+        assert_eq!(1, edit_distance("Services", "Service"));
+        assert_eq!(3, edit_distance("", "abc"));
+    }
+
+    #[test]
+    fn closest_should_keep_the_closest_candidates() {
+        let candidates = vec!["ExecStop", "ExecStart", "ExecReload", "Restart"];
+
+        assert_eq!(vec!["ExecStart"], closest("ExecStrat", candidates.clone()));
+        assert_eq!(vec!["ExecStart", "ExecStop"], closest("ExecSta", candidates));
+    }
+
+    #[test]
+    fn closest_should_prefer_a_case_fix() {
+        let candidates = vec!["Services", "service"];
+
+        assert_eq!(vec!["service"], closest("Service", candidates));
+    }
+
+    #[test]
+    fn closest_should_not_suggest_distant_candidates() {
+        assert!(closest("Foo", vec!["ExecStart"]).is_empty());
+    }
+
+    #[test]
+    fn did_you_mean_should_list_the_suggestions() {
+        assert_eq!("", did_you_mean(&[]));
+        assert_eq!(" (did you mean Unit?)", did_you_mean(&["Unit"]));
+        assert_eq!(" (did you mean a, b or c?)", did_you_mean(&["a", "b", "c"]));
+    }
+}
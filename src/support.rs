@@ -1,10 +1,43 @@
+//! Helpers on the units of `systemd_parser`, used by the lints and available to
+//! the lints of other crates.
 
 use systemd_parser::items::*;
 
+/// Look up the value of a directive, when it is set once
 pub trait SystemdUnitExt {
     /// Key exists and have value Solo(expected_value)
+    ///
+    /// ```
+    /// extern crate systemd_linter;
+    /// extern crate systemd_parser;
+    ///
+    /// use systemd_linter::support::SystemdUnitExt;
+    ///
+    /// # fn main() {
+    /// let unit = systemd_parser::parse_string("[Service]\nType=oneshot\nExecStart=/bin/true\n").unwrap();
+    ///
+    /// assert!(unit.key_have_solo_value("Type", "oneshot"));
+    /// assert!(!unit.key_have_solo_value("Type", "simple"));
+    /// assert!(!unit.key_have_solo_value("User", "nobody"));
+    /// # }
+    /// ```
     fn key_have_solo_value(&self, key: &str, expected_value: &str) -> bool;
     /// Key may exists, in this case it has value Solo(expected_value)
+    ///
+    /// ```
+    /// extern crate systemd_linter;
+    /// extern crate systemd_parser;
+    ///
+    /// use systemd_linter::support::SystemdUnitExt;
+    ///
+    /// # fn main() {
+    /// let unit = systemd_parser::parse_string("[Service]\nExecStart=/bin/true\n").unwrap();
+    ///
+    /// // the default type of a service is simple
+    /// assert!(unit.key_may_have_solo_value("Type", "simple"));
+    /// assert!(!unit.key_may_have_solo_value("ExecStart", "/bin/false"));
+    /// # }
+    /// ```
     fn key_may_have_solo_value(&self, key: &str, expected_value: &str) -> bool;
 }
 
@@ -12,7 +45,7 @@ impl SystemdUnitExt for SystemdUnit {
     fn key_have_solo_value(&self, key: &str, expected_value: &str) -> bool {
 
         match self.lookup_by_key(key) {
-            Some(&DirectiveEntry::Solo(ref entry)) => entry.value() == Some(expected_value),
+            Some(DirectiveEntry::Solo(entry)) => entry.value() == Some(expected_value),
             _ => false,
         }
     }
@@ -20,13 +53,42 @@ impl SystemdUnitExt for SystemdUnit {
     fn key_may_have_solo_value(&self, key: &str, expected_value: &str) -> bool {
 
         match self.lookup_by_key(key) {
-            Some(&DirectiveEntry::Solo(ref entry)) => entry.value() == Some(expected_value),
+            Some(DirectiveEntry::Solo(entry)) => entry.value() == Some(expected_value),
             None => true,
             _ => false,
         }
     }
 }
 
+/// Read the values of a directive, whether it is set once or many times
+pub trait DirectiveEntryExt {
+    /// All the non-empty values of the entry, in order of appearance
+    ///
+    /// ```
+    /// extern crate systemd_linter;
+    /// extern crate systemd_parser;
+    ///
+    /// use systemd_linter::support::DirectiveEntryExt;
+    ///
+    /// # fn main() {
+    /// let input = "[Service]\nExecStartPre=/bin/true\nExecStartPre=\nExecStartPre=/bin/false\nExecStart=/bin/true\n";
+    /// let unit = systemd_parser::parse_string(input).unwrap();
+    ///
+    /// assert_eq!(vec!["/bin/true", "/bin/false"], unit.lookup_by_key("ExecStartPre").unwrap().values());
+    /// # }
+    /// ```
+    fn values(&self) -> Vec<&str>;
+}
+
+impl DirectiveEntryExt for DirectiveEntry {
+    fn values(&self) -> Vec<&str> {
+        match *self {
+            DirectiveEntry::Solo(ref entry) => entry.value().into_iter().collect(),
+            DirectiveEntry::Many(ref entries) => entries.iter().filter_map(|entry| entry.value()).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     pub use super::*;
@@ -54,7 +116,7 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(false == unit.key_have_solo_value("Description", "not this"))
+            assert!(!unit.key_have_solo_value("Description", "not this"))
         }
 
         #[test]
@@ -65,7 +127,7 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(false == unit.key_have_solo_value("ExecStart", ""))
+            assert!(!unit.key_have_solo_value("ExecStart", ""))
         }
 
         #[test]
@@ -78,7 +140,7 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(false == unit.key_have_solo_value("ExecStartPre", "/bin/true"))
+            assert!(!unit.key_have_solo_value("ExecStartPre", "/bin/true"))
         }
     }
 
@@ -105,7 +167,7 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(false == unit.key_may_have_solo_value("Description", "not this"))
+            assert!(!unit.key_may_have_solo_value("Description", "not this"))
         }
 
         #[test]
@@ -116,7 +178,7 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(true == unit.key_may_have_solo_value("ExecStart", ""))
+            assert!(unit.key_may_have_solo_value("ExecStart", ""))
         }
 
         #[test]
@@ -129,7 +191,36 @@ mod tests {
             ";
             let unit = systemd_parser::parse_string(input).unwrap();
 
-            assert!(false == unit.key_may_have_solo_value("ExecStartPre", "/bin/true"))
+            assert!(!unit.key_may_have_solo_value("ExecStartPre", "/bin/true"))
+        }
+    }
+
+    mod values {
+        pub use super::*;
+        use systemd_parser;
+
+        #[test]
+        fn it_should_return_the_solo_value() {
+            let input = "
+                [Unit]
+                Description= a dummy unit
+            ";
+            let unit = systemd_parser::parse_string(input).unwrap();
+
+            assert_eq!(vec!["a dummy unit"], unit.lookup_by_key("Description").unwrap().values())
+        }
+
+        #[test]
+        fn it_should_return_all_the_values_but_the_empty_ones() {
+            let input = "
+                [Service]
+                ExecStartPre=/bin/true
+                ExecStartPre=
+                ExecStartPre=/bin/false
+            ";
+            let unit = systemd_parser::parse_string(input).unwrap();
+
+            assert_eq!(vec!["/bin/true", "/bin/false"], unit.lookup_by_key("ExecStartPre").unwrap().values())
         }
     }
 }
@@ -0,0 +1,240 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Writes the unit in a directory of its own, so that no configuration file is found
+fn unit_file(test: &str, name: &str, contents: &str) -> PathBuf {
+
+    let dir = env::temp_dir().join(format!("systemd-linter-cli-{}", test));
+    fs::create_dir_all(&dir).expect("temporary directory is writable");
+    let path = dir.join(name);
+    fs::write(&path, contents).expect("unit file is writable");
+    path
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_systemd-linter"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .env("XDG_CACHE_HOME", env::temp_dir().join("systemd-linter-cli-cache"))
+        .env_remove("CLICOLOR_FORCE")
+        .output()
+        .expect("the binary runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+static CLEAN_UNIT: &str = "
+[Unit]
+Description=a dummy unit
+[Service]
+Type=simple
+ExecStart=/bin/true
+";
+
+static UNIT_WITH_WARNING: &str = "
+[Unit]
+Description=a dummy unit
+[Service]
+Type=simple
+Type=forking
+ExecStart=/bin/true
+";
+
+static UNIT_WITH_ERROR: &str = "
+[Unit]
+Description=a dummy unit
+[Service]
+Type=Simple
+ExecStart=/bin/true
+";
+
+#[test]
+fn it_should_exit_with_0_when_there_is_no_finding() {
+    let path = unit_file("clean", "dummy.service", CLEAN_UNIT);
+
+    let output = run(&[path.to_str().unwrap()]);
+
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("", stdout(&output))
+}
+
+#[test]
+fn it_should_exit_with_1_on_errors_and_print_them() {
+    let path = unit_file("error", "dummy.service", UNIT_WITH_ERROR);
+
+    let output = run(&[path.to_str().unwrap()]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "** Error[invalid-value] [Service] Type=: Invalid value in Type=Simple: expected simple, exec, forking, \
+         oneshot, dbus, notify, notify-reload or idle (did you mean simple?)\n   \
+         fix (unsafe): Replace Type=Simple by Type=simple\n   \
+         docs: https://github.com/mackwic/systemd-linter/blob/master/docs/lints.md#invalid-value\n\n",
+        stdout(&output)
+    )
+}
+
+#[test]
+fn it_should_exit_with_0_on_warnings_by_default() {
+    let path = unit_file("warning", "dummy.service", UNIT_WITH_WARNING);
+
+    let output = run(&[path.to_str().unwrap()]);
+
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).starts_with("** Warning[duplicate-directive] [Service] Type=: "), "{}", stdout(&output))
+}
+
+#[test]
+fn it_should_fail_on_warnings_with_deny_or_warn_as_error() {
+    let path = unit_file("deny", "dummy.service", UNIT_WITH_WARNING);
+    let path = path.to_str().unwrap();
+
+    assert_eq!(Some(1), run(&["--deny", "warning", path]).status.code());
+    assert_eq!(Some(1), run(&["--warn-as-error", path]).status.code());
+    assert_eq!(Some(0), run(&["--deny", "error", path]).status.code());
+}
+
+#[test]
+fn it_should_report_the_invalid_bytes_as_findings() {
+    let path = unit_file("encoding", "dummy.service", &CLEAN_UNIT.replace("/bin/true", "/bin/true\0"));
+    let path = path.to_str().unwrap();
+
+    let output = run(&["--no-cache", path]);
+
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).starts_with("** Warning[invalid-encoding]: NUL byte at byte 74\n"), "{}", stdout(&output));
+    assert_eq!(Some(1), run(&["--no-cache", "--deny", "warning", path]).status.code());
+}
+
+#[test]
+fn it_should_exit_with_2_on_usage_and_io_errors() {
+    let path = unit_file("usage", "dummy.service", CLEAN_UNIT);
+    let path = path.to_str().unwrap();
+
+    assert_eq!(Some(2), run(&["--bogus", path]).status.code());
+    assert_eq!(Some(2), run(&["--deny", "fatal", path]).status.code());
+    assert_eq!(Some(2), run(&["--deny", "warning", "--warn-as-error", path]).status.code());
+    assert_eq!(Some(2), run(&["--select", "no-such-lint", path]).status.code());
+    assert_eq!(Some(2), run(&["/no/such/dir/dummy.service"]).status.code());
+}
+
+#[test]
+fn it_should_exit_with_3_on_parse_errors() {
+    let path = unit_file("parse", "dummy.service", "[Unit\nDescription=broken\n");
+
+    let output = run(&[path.to_str().unwrap()]);
+
+    assert_eq!(Some(3), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("PARSE ERROR"))
+}
+
+#[test]
+fn it_should_only_report_the_findings_missing_from_the_baseline() {
+    let path = unit_file("baseline", "dummy.service", "[Service]\nType=Simple\nExecStart=/bin/true\n");
+    let baseline = path.with_file_name("baseline.toml");
+    let _ = fs::remove_file(&baseline);
+    let (path, baseline) = (path.to_str().unwrap(), baseline.to_str().unwrap());
+
+    assert_eq!(Some(0), run(&["--write-baseline", baseline, path]).status.code());
+    assert_eq!(Some(0), run(&["--baseline", baseline, path]).status.code());
+
+    fs::write(path, UNIT_WITH_ERROR.replace("Type=", "Restart=sometimes\nType=")).unwrap();
+    let output = run(&["--baseline", baseline, path]);
+
+    assert_eq!(Some(1), output.status.code());
+    let stdout = stdout(&output);
+    assert!(stdout.contains("Restart=sometimes"), "{}", stdout);
+    assert!(!stdout.contains("Type=Simple"), "{}", stdout);
+    assert!(stdout.contains("** Resolved[missing-description]"), "{}", stdout)
+}
+
+#[cfg(unix)]
+#[test]
+fn it_should_report_the_diagnostics_of_the_plugins() {
+    let path = unit_file("plugin", "dummy.service", CLEAN_UNIT);
+    fs::write(path.with_file_name(".systemd-lint.toml"), r#"
+        [[plugin]]
+        name = "house-rules"
+        command = ["sh", "-c", "grep -q X-Owner= || echo '{\"diagnostics\": [{\"code\": \"x-owner\", \"severity\": \"error\", \"message\": \"X-Owner= is required\", \"location\": {\"category\": \"Unit\"}}]}'"]
+    "#).unwrap();
+
+    let path = path.to_str().unwrap();
+
+    let output = run(&[path]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stdout(&output).starts_with("** Error[plugin-failure]: Plugin house-rules was not run"), "{}", stdout(&output));
+
+    let output = run(&["--allow-plugins", path]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("** Error[house-rules/x-owner] [Unit]: X-Owner= is required\n\n", stdout(&output));
+
+    assert_eq!(Some(0), run(&["--allow-plugins", "--ignore", "house-rules/x-owner", path]).status.code());
+    assert_eq!(Some(0), run(&["--allow-plugins", "--select", "house-rules/x-team", path]).status.code());
+    assert_eq!(Some(1), run(&["--allow-plugins", "--select", "house-rules", path]).status.code());
+    assert_eq!(Some(2), run(&["--allow-plugins", "--select", "other-rules/x-owner", path]).status.code());
+}
+
+#[test]
+fn it_should_report_statistics_over_the_unit_files_of_the_inputs() {
+    let path = unit_file("stats", "error.service", UNIT_WITH_ERROR);
+    let dir = path.parent().unwrap();
+    fs::write(dir.join("clean.service"), CLEAN_UNIT).unwrap();
+    fs::write(dir.join("notes.txt"), "not a unit").unwrap();
+
+    let output = run(&["--stats=json", dir.to_str().unwrap()]);
+
+    assert_eq!(Some(1), output.status.code());
+    let report = stdout(&output);
+    assert!(report.contains("\"files\": 2,"), "{}", report);
+    assert!(report.contains("\"lint\": \"invalid-value\""), "{}", report);
+    assert!(report.contains("error.service\",\n"), "{}", report);
+
+    let output = run(&["--stats", path.to_str().unwrap(), dir.join("clean.service").to_str().unwrap()]);
+
+    assert!(stdout(&output).starts_with("files: 2 (0 parse error(s))\n"), "{}", stdout(&output));
+    assert_eq!(Some(2), run(&["--stats", "--fix", path.to_str().unwrap()]).status.code());
+}
+
+#[test]
+fn it_should_reuse_the_findings_of_unchanged_files() {
+    let path = unit_file("cache", "dummy.service", UNIT_WITH_ERROR);
+    let cache = path.with_file_name("cache");
+    let _ = fs::remove_dir_all(&cache);
+    let (path, cache) = (path.to_str().unwrap(), cache.to_str().unwrap());
+
+    let first = run(&["--cache-dir", cache, path]);
+    assert!(stdout(&first).contains("Type=Simple"), "{}", stdout(&first));
+    let entries: Vec<PathBuf> = fs::read_dir(cache).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert_eq!(1, entries.len());
+
+    // a cached finding which the linter would not report tells a hit
+    let entry = fs::read_to_string(&entries[0]).unwrap();
+    fs::write(&entries[0], entry.replace("Type=Simple", "Type=Cached")).unwrap();
+
+    let cached = run(&["--cache-dir", cache, path]);
+    assert_eq!(first.status.code(), cached.status.code());
+    assert_eq!(stdout(&first).replace("Type=Simple", "Type=Cached"), stdout(&cached));
+    assert_eq!(stdout(&first), stdout(&run(&["--no-cache", path])));
+    assert!(!stdout(&run(&["--cache-dir", cache, "--select", "invalid-value", path])).contains("Type=Cached"));
+
+    fs::write(path, UNIT_WITH_ERROR.replace("a dummy unit", "another dummy unit")).unwrap();
+    assert_eq!(stdout(&first), stdout(&run(&["--cache-dir", cache, path])));
+}
+
+#[test]
+fn it_should_keep_the_bom_and_line_endings_of_fixed_files() {
+    let path = unit_file("fix-crlf", "dummy.service", "");
+    fs::write(&path, "\u{FEFF}[Unit]\r\nDescription=a dummy unit\r\n[Service]\r\nExecStart=/bin/true\r\n# the end\n").unwrap();
+
+    let output = run(&["--no-cache", "--fix", path.to_str().unwrap()]);
+
+    assert_eq!(Some(0), output.status.code(), "{}", stdout(&output));
+    assert_eq!(
+        "\u{FEFF}[Unit]\r\nDescription=a dummy unit\r\n[Service]\r\nExecStart=/bin/true\r\nType=simple\r\n# the end\n",
+        fs::read_to_string(&path).unwrap()
+    )
+}
@@ -0,0 +1,127 @@
+extern crate systemd_linter;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use systemd_linter::{Error, LintCode, LintSeverity, Location, Settings};
+
+/// A directory of its own for each test, so that no configuration file is found
+fn test_dir(test: &str) -> PathBuf {
+
+    let dir = env::temp_dir().join(format!("systemd-linter-library-{}", test));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temporary directory is writable");
+    dir
+}
+
+static UNIT_WITH_ERROR: &str = "
+[Unit]
+Description=a dummy unit
+[Service]
+Type=Simple
+ExecStart=/bin/true
+";
+
+#[test]
+fn lint_str_should_return_the_diagnostics_with_the_name_of_their_lint() {
+    let diagnostics = systemd_linter::lint_str(UNIT_WITH_ERROR, &Settings::default()).unwrap();
+
+    assert_eq!(1, diagnostics.len());
+    assert_eq!("invalid-value", diagnostics[0].lint());
+    assert_eq!(LintCode::ErrorInvalidValue, diagnostics[0].code());
+    assert_eq!(LintSeverity::Error, diagnostics[0].severity());
+    assert_eq!(&Location::Directive { category: "Service".into(), key: "Type".into() }, diagnostics[0].location());
+    assert_eq!("Replace Type=Simple by Type=simple", diagnostics[0].fixes()[0].description)
+}
+
+#[test]
+fn lint_file_should_apply_the_configuration_of_the_unit() {
+    let dir = test_dir("config");
+    let path = dir.join("dummy.service");
+    fs::write(&path, UNIT_WITH_ERROR).unwrap();
+    fs::write(dir.join(".systemd-lint.toml"), "[severity]\ninvalid-value = \"warning\"\n").unwrap();
+
+    let diagnostics = systemd_linter::lint_file(&path).unwrap();
+
+    assert_eq!(vec![LintSeverity::Warning], diagnostics.iter().map(|diagnostic| diagnostic.severity()).collect::<Vec<_>>())
+}
+
+#[test]
+fn lint_file_should_report_the_invalid_bytes_of_the_unit() {
+    let dir = test_dir("encoding");
+    let path = dir.join("dummy.service");
+    fs::write(&path, b"[Unit]\nDescription=caf\xE9\n[Service]\nType=simple\nExecStart=/bin/true\0\n").unwrap();
+
+    let diagnostics = systemd_linter::lint_file(&path).unwrap();
+
+    let lints: Vec<&str> = diagnostics.iter().map(|diagnostic| diagnostic.lint()).collect();
+    assert_eq!(vec!["invalid-encoding", "invalid-encoding"], lints);
+    assert_eq!("NUL byte at byte 65", diagnostics[1].message());
+
+    fs::write(dir.join(".systemd-lint.toml"), "[severity]\ninvalid-encoding = \"ignore\"\n").unwrap();
+    assert!(systemd_linter::lint_file(&path).unwrap().is_empty())
+}
+
+#[test]
+fn lint_file_should_tell_parse_errors_apart() {
+    let dir = test_dir("parse");
+    let path = dir.join("dummy.service");
+    fs::write(&path, "[Unit\nDescription=broken\n").unwrap();
+
+    match systemd_linter::lint_file(&path) {
+        Err(Error::Parse(_)) => {}
+        res => panic!("expected a parse error, got {:?}", res),
+    }
+    match systemd_linter::lint_file(&dir.join("missing.service")) {
+        Err(Error::Io(ref missing, _)) => assert_eq!(&dir.join("missing.service"), missing),
+        res => panic!("expected an I/O error, got {:?}", res),
+    }
+}
+
+#[test]
+fn lint_paths_should_walk_the_directories_for_unit_files() {
+    let dir = test_dir("paths");
+    fs::create_dir_all(dir.join("foo.service.d")).unwrap();
+    fs::write(dir.join("b.service"), UNIT_WITH_ERROR).unwrap();
+    fs::write(dir.join("a.socket"), "[Unit]\nDescription=a dummy socket\n[Socket]\nListenStream=80\n").unwrap();
+    fs::write(dir.join("foo.service.d/override.conf"), "[Service]\nUser=nobody\n").unwrap();
+    fs::write(dir.join("README.md"), "not a unit").unwrap();
+
+    let res = systemd_linter::lint_paths(&[&dir]);
+
+    let files: Vec<PathBuf> = res.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(vec![dir.join("a.socket"), dir.join("b.service"), dir.join("foo.service.d/override.conf")], files);
+    let counts: Vec<usize> = res.iter().map(|(_, diagnostics)| diagnostics.as_ref().unwrap().len()).collect();
+    assert_eq!(vec![0, 1, 0], counts)
+}
+
+#[test]
+fn lint_paths_should_skip_the_conf_files_out_of_drop_in_directories() {
+    let dir = test_dir("daemons");
+    fs::write(dir.join("journald.conf"), "[Journal]\nStorage=persistent\n").unwrap();
+    fs::write(dir.join("b.service"), UNIT_WITH_ERROR).unwrap();
+
+    let res = systemd_linter::lint_paths(&[&dir]);
+
+    let files: Vec<PathBuf> = res.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(vec![dir.join("b.service")], files)
+}
+
+#[cfg(unix)]
+#[test]
+fn lint_paths_should_not_follow_symbolic_links() {
+    use std::os::unix::fs::symlink;
+
+    let dir = test_dir("symlinks");
+    fs::create_dir_all(dir.join("multi-user.target.wants")).unwrap();
+    fs::write(dir.join("b.service"), UNIT_WITH_ERROR).unwrap();
+    symlink(dir.join("b.service"), dir.join("multi-user.target.wants/b.service")).unwrap();
+    symlink(&dir, dir.join("multi-user.target.wants/loop")).unwrap();
+
+    let res = systemd_linter::lint_paths(&[&dir]);
+
+    let files: Vec<PathBuf> = res.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(vec![dir.join("b.service")], files);
+    // a link given as a path is linted
+    assert_eq!(1, systemd_linter::lint_paths(&[dir.join("multi-user.target.wants/b.service")]).len())
+}
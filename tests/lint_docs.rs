@@ -0,0 +1,54 @@
+extern crate systemd_linter;
+
+use std::env;
+use std::fs;
+use systemd_linter::Registry;
+
+static LINTS_DOCS: &str = "docs/lints.md";
+
+/// The documentation of the built-in lints, the target of their `docs_url`
+fn render(registry: &Registry) -> String {
+
+    let mut res = String::from(
+        "# Lints\n\n\
+         <!-- generated from the lint registry: UPDATE_LINT_DOCS=1 cargo test --test lint_docs -->\n");
+
+    for lint in registry.lints() {
+        let groups: Vec<&str> = lint.groups().iter().map(|group| group.name()).collect();
+        res.push_str(&format!("\n## {}\n\n", lint.name()));
+        res.push_str(&format!("- code: `{}` (`{:?}`)\n", lint.code() as u32, lint.code()));
+        res.push_str(&format!("- default severity: {}\n", lint.default_severity().name()));
+        res.push_str(&format!("- groups: {}\n", groups.join(", ")));
+        if !lint.categories().is_empty() {
+            res.push_str(&format!("- categories: {}\n", lint.categories().join(", ")));
+        }
+        if let Some(unit_types) = lint.unit_types() {
+            let names: Vec<String> = unit_types.iter().map(|unit_type| format!("{:?}", unit_type)).collect();
+            res.push_str(&format!("- unit types: {}\n", names.join(", ")));
+        }
+        res.push_str(&format!("\n{}\n", lint.rationale()));
+    }
+
+    res
+}
+
+#[test]
+fn the_lint_docs_should_be_up_to_date() {
+    let expected = render(&Registry::new());
+
+    if env::var_os("UPDATE_LINT_DOCS").is_some() {
+        fs::write(LINTS_DOCS, &expected).expect("the docs are writable");
+    }
+
+    let docs = fs::read_to_string(LINTS_DOCS).unwrap_or_default();
+    assert!(docs == expected, "{} is out of date, run UPDATE_LINT_DOCS=1 cargo test --test lint_docs", LINTS_DOCS)
+}
+
+#[test]
+fn every_documented_lint_should_link_to_its_section() {
+    for lint in Registry::new().lints() {
+        if let Some(url) = lint.docs_url() {
+            assert!(url.ends_with(&format!("/{}#{}", LINTS_DOCS, lint.name())), "{}", url);
+        }
+    }
+}